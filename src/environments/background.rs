@@ -0,0 +1,22 @@
+use crate::utilities::{color::Color, point::Point};
+
+pub type Environment = Box<dyn Background>;
+
+/// Light arriving from infinitely far away, seen by rays that miss every shape
+#[typetag::serde(tag = "type")]
+pub trait Background: Send + Sync {
+    /// Light arriving from `direction`
+    fn color(&self, direction: &Point) -> Color;
+    /// Whether the scene should sample directions with `sample` to light shapes directly
+    fn is_emitter(&self) -> bool {
+        false
+    }
+    /// Pick a unit direction that light arrives from
+    fn sample(&self) -> Option<Point> {
+        None
+    }
+    /// Solid angle density of `sample` picking `direction`
+    fn pdf(&self, _direction: &Point) -> f64 {
+        0.
+    }
+}
@@ -0,0 +1,26 @@
+use crate::{
+    environments::background::Background,
+    utilities::{color::Color, point::Point},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// The same light from every direction; black turns the background off
+#[derive(Serialize, Deserialize)]
+pub struct Constant {
+    color: Color,
+}
+
+impl Constant {
+    #[allow(dead_code)]
+    pub fn new(color: Color) -> Self {
+        Self { color }
+    }
+}
+
+#[typetag::serde]
+impl Background for Constant {
+    fn color(&self, _: &Point) -> Color {
+        self.color
+    }
+}
@@ -0,0 +1,211 @@
+use std::f64::consts::PI;
+
+use rand::Rng;
+
+use crate::{
+    environments::background::Background,
+    utilities::{color::Color, distribution::Distribution2D, image::Image, point::Point},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Scene file representation of an environment map; the image is loaded from `path`
+#[derive(Deserialize)]
+struct EquirectangularFile {
+    path: String,
+    #[serde(default = "Equirectangular::default_intensity")]
+    intensity: f64,
+    #[serde(default)]
+    rotation: f64,
+}
+
+/// A high dynamic range image wrapped around the scene, with longitude along its width and
+/// latitude along its height
+///
+/// The center of the image is seen looking down `-z`, and the top row is straight up. Bright
+/// parts of the image, like the sun, are sampled directly so that they can light the scene.
+#[derive(Serialize, Deserialize)]
+#[serde(from = "EquirectangularFile")]
+pub struct Equirectangular {
+    /// Path to a `.hdr`, `.pfm` or `.exr` image, relative to the working directory
+    path: String,
+    /// Scale applied to every pixel
+    intensity: f64,
+    /// Rotation of the image around the y axis, in degrees
+    rotation: f64,
+    #[serde(skip_serializing)]
+    image: Image,
+    /// Picks pixels in proportion to the light they send towards the scene
+    #[serde(skip_serializing)]
+    distribution: Distribution2D,
+}
+
+impl From<EquirectangularFile> for Equirectangular {
+    fn from(file: EquirectangularFile) -> Self {
+        Equirectangular::new(&file.path, file.intensity, file.rotation)
+    }
+}
+
+impl Equirectangular {
+    fn default_intensity() -> f64 {
+        1.
+    }
+
+    pub fn new(path: &str, intensity: f64, rotation: f64) -> Self {
+        Self::from_image(path, Image::load(path), intensity, rotation)
+    }
+
+    fn from_image(path: &str, image: Image, intensity: f64, rotation: f64) -> Self {
+        // Rows near the poles cover less of the sphere, so they are picked less often
+        let width = image.width as usize;
+        let height = image.height as f64;
+        let weights: Vec<f64> = image
+            .buffer
+            .iter()
+            .enumerate()
+            .map(|(index, color)| {
+                let theta = PI * ((index / width) as f64 + 0.5) / height;
+                color.luminance().max(0.) * theta.sin()
+            })
+            .collect();
+        let distribution = Distribution2D::new(&weights, width);
+
+        Self {
+            path: path.to_string(),
+            intensity,
+            rotation,
+            image,
+            distribution,
+        }
+    }
+
+    /// Position `(u, v)` of a direction in the image, where `v` is 0 at the top row
+    fn to_image(&self, direction: &Point) -> (f64, f64) {
+        let direction = direction.normalized();
+        let phi = direction.x.atan2(-direction.z) - self.rotation.to_radians();
+        let u = (0.5 + phi / (2. * PI)).rem_euclid(1.);
+        let v = direction.y.clamp(-1., 1.).acos() / PI;
+        (u, v)
+    }
+
+    /// Unit direction seen at position `(u, v)` in the image
+    fn to_direction(&self, u: f64, v: f64) -> Point {
+        let phi = (u - 0.5) * 2. * PI + self.rotation.to_radians();
+        let theta = v * PI;
+        Point::new(
+            theta.sin() * phi.sin(),
+            theta.cos(),
+            -theta.sin() * phi.cos(),
+        )
+    }
+}
+
+#[typetag::serde]
+impl Background for Equirectangular {
+    fn color(&self, direction: &Point) -> Color {
+        let (u, v) = self.to_image(direction);
+        let col = ((u * self.image.width as f64) as u64).min(self.image.width - 1);
+        let row = ((v * self.image.height as f64) as u64).min(self.image.height - 1);
+        self.image.buffer[(row * self.image.width + col) as usize] * self.intensity
+    }
+
+    fn is_emitter(&self) -> bool {
+        true
+    }
+
+    fn sample(&self) -> Option<Point> {
+        let mut rng = rand::thread_rng();
+        let ((u, v), pdf) = self.distribution.sample(rng.gen(), rng.gen());
+        (pdf > 0.).then(|| self.to_direction(u, v))
+    }
+
+    /// Converts the density over the image to solid angle, using the area of the sphere each
+    /// pixel covers
+    fn pdf(&self, direction: &Point) -> f64 {
+        let (u, v) = self.to_image(direction);
+        let sin_theta = (v * PI).sin();
+        match sin_theta > 0. {
+            true => self.distribution.pdf(u, v) / (2. * PI * PI * sin_theta),
+            false => 0.,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use crate::{
+        environments::{background::Background, equirectangular::Equirectangular},
+        utilities::{color::Color, image::Image, point::Point},
+    };
+
+    /// A dim map with one bright pixel just above the horizon, towards `-z`
+    fn sun_map() -> Equirectangular {
+        let mut image = Image::from_dimensions(16, 8);
+        image.buffer.fill(Color::gray(0.1));
+        image.buffer[3 * 16 + 8] = Color::gray(1000.);
+        Equirectangular::from_image("sun.hdr", image, 1., 0.)
+    }
+
+    #[test]
+    fn can_map_directions() {
+        let map = sun_map();
+        [
+            Point::new(0., 0.2, -1.),
+            Point::new(1., 0.5, 0.),
+            Point::new(-0.3, -0.8, 0.4),
+        ]
+        .iter()
+        .for_each(|direction| {
+            let (u, v) = map.to_image(direction);
+            let round_trip = map.to_direction(u, v);
+            assert!((round_trip - direction.normalized()).len() < 1e-9);
+        });
+        assert_eq!(map.to_image(&Point::new(0., 0., -1.)), (0.5, 0.5));
+    }
+
+    #[test]
+    fn can_look_up_color() {
+        let map = sun_map();
+        let (u, v) = (8.5 / 16., 3.5 / 8.);
+        assert_eq!(map.color(&map.to_direction(u, v)), Color::gray(1000.));
+        assert_eq!(map.color(&Point::new(0., 1., 0.)), Color::gray(0.1));
+    }
+
+    #[test]
+    fn can_rotate() {
+        let image = Image::from_dimensions(4, 2);
+        let map = Equirectangular::from_image("map.hdr", image, 1., 90.);
+        // Rotating a quarter turn brings the center of the image to `+x`
+        let (u, _) = map.to_image(&Point::new(1., 0., 0.));
+        assert!((u - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn can_sample_sun() {
+        let map = sun_map();
+        let samples = 1000;
+        let sun = (0..samples)
+            .filter(|_| map.color(&map.sample().unwrap()).r > 1.)
+            .count();
+        assert!(sun as f64 > 0.9 * samples as f64);
+    }
+
+    #[test]
+    fn can_integrate_pdf() {
+        let map = sun_map();
+        let (width, height) = (16, 8);
+        let total: f64 = (0..height)
+            .flat_map(|row| (0..width).map(move |col| (col, row)))
+            .map(|(col, row)| {
+                let v = (row as f64 + 0.5) / height as f64;
+                let direction = map.to_direction((col as f64 + 0.5) / width as f64, v);
+                // Solid angle covered by the pixel
+                let area = (2. * PI / width as f64) * (PI / height as f64) * (v * PI).sin();
+                map.pdf(&direction) * area
+            })
+            .sum();
+        assert!((total - 1.).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,50 @@
+use crate::{
+    environments::background::Background,
+    utilities::{color::Color, point::Point},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// A linear blend from `bottom` straight down to `top` straight up
+#[derive(Serialize, Deserialize)]
+pub struct Gradient {
+    bottom: Color,
+    top: Color,
+}
+
+impl Gradient {
+    pub fn new(bottom: Color, top: Color) -> Self {
+        Self { bottom, top }
+    }
+}
+
+impl Default for Gradient {
+    /// White to light blue sky
+    fn default() -> Self {
+        Self::new(Color::gray(1.), Color::rgb(0.5, 0.5, 0.9))
+    }
+}
+
+#[typetag::serde]
+impl Background for Gradient {
+    fn color(&self, direction: &Point) -> Color {
+        let t = 0.5 * (direction.normalized().y + 1.0);
+        (1.0 - t) * self.bottom + t * self.top
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        environments::{background::Background, gradient::Gradient},
+        utilities::{color::Color, point::Point},
+    };
+
+    #[test]
+    fn can_blend() {
+        let gradient = Gradient::new(Color::gray(0.), Color::gray(1.));
+        assert_eq!(gradient.color(&Point::new(0., -2., 0.)), Color::gray(0.));
+        assert_eq!(gradient.color(&Point::new(0., 3., 0.)), Color::gray(1.));
+        assert_eq!(gradient.color(&Point::new(1., 0., 0.)), Color::gray(0.5));
+    }
+}
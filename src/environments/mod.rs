@@ -0,0 +1,5 @@
+pub mod background;
+pub mod constant;
+pub mod equirectangular;
+pub mod gradient;
+pub mod sky;
@@ -0,0 +1,371 @@
+use std::f64::consts::PI;
+
+use rand::Rng;
+
+use crate::{
+    environments::background::Background,
+    utilities::{color::Color, point::Point},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Converts the model's luminance, in kcd/m², to the renderer's units so that a clear midday
+/// sky is about as bright as the default gradient
+const SCALE: f64 = 0.03;
+/// Illuminance of the sun above the atmosphere, in klx
+const SUN_ILLUMINANCE: f64 = 128.;
+/// Chance of `sample` aiming at the sun disk instead of the rest of the sky
+const SUN_PROBABILITY: f64 = 0.5;
+
+/// Scene file representation of a sky; everything but the sun's position is optional
+#[derive(Deserialize)]
+struct SkyFile {
+    elevation: f64,
+    azimuth: f64,
+    #[serde(default = "Sky::default_turbidity")]
+    turbidity: f64,
+    #[serde(default = "Sky::default_intensity")]
+    intensity: f64,
+    #[serde(default = "Sky::default_sun_size")]
+    sun_size: f64,
+}
+
+/// Clear daylight sky with a sun disk, following the analytic model from Preetham, Shirley and
+/// Smits, "A Practical Analytic Model for Daylight"
+///
+/// https://courses.cs.duke.edu/fall01/cps124/resources/p91-preetham.pdf
+///
+/// Directions below the horizon see the sky at the horizon. The sun is sampled directly, so it
+/// can replace a large `Light` sphere as the key light of an outdoor scene.
+#[derive(Serialize, Deserialize)]
+#[serde(from = "SkyFile")]
+pub struct Sky {
+    /// Angle of the sun above the horizon, in degrees between 0 and 90
+    elevation: f64,
+    /// Angle of the sun around the y axis, in degrees, where 0 is towards `-z` and 90 is `+x`
+    azimuth: f64,
+    /// Haziness of the atmosphere, from 2 for a very clear sky to 10 for a hazy one
+    turbidity: f64,
+    /// Scale applied to both the sky and the sun
+    intensity: f64,
+    /// Angular diameter of the sun, in degrees; larger suns cast softer shadows
+    sun_size: f64,
+    /// Unit vector pointing at the center of the sun
+    #[serde(skip_serializing)]
+    sun: Point,
+    /// Cosine of the angle between the center and the edge of the sun disk
+    #[serde(skip_serializing)]
+    sun_cos_max: f64,
+    /// Light arriving from each direction inside the sun disk
+    #[serde(skip_serializing)]
+    sun_color: Color,
+    /// Distribution coefficients `A` through `E` for `Y`, `x` and `y`
+    #[serde(skip_serializing)]
+    coefficients: [[f64; 5]; 3],
+    /// `Y`, `x` and `y` straight up, divided by the distribution at the zenith
+    #[serde(skip_serializing)]
+    zenith: [f64; 3],
+}
+
+impl From<SkyFile> for Sky {
+    fn from(file: SkyFile) -> Self {
+        Sky::new(
+            file.elevation,
+            file.azimuth,
+            file.turbidity,
+            file.intensity,
+            file.sun_size,
+        )
+    }
+}
+
+impl Sky {
+    fn default_turbidity() -> f64 {
+        3.
+    }
+
+    fn default_intensity() -> f64 {
+        1.
+    }
+
+    fn default_sun_size() -> f64 {
+        0.53
+    }
+
+    pub fn new(
+        elevation: f64,
+        azimuth: f64,
+        turbidity: f64,
+        intensity: f64,
+        sun_size: f64,
+    ) -> Self {
+        let (elevation_rad, azimuth_rad) = (elevation.to_radians(), azimuth.to_radians());
+        let sun = Point::new(
+            elevation_rad.cos() * azimuth_rad.sin(),
+            elevation_rad.sin(),
+            -elevation_rad.cos() * azimuth_rad.cos(),
+        );
+        let sun_cos_max = (sun_size.to_radians() / 2.).cos();
+
+        // Table 2 of the paper, in the same `Y`, `x`, `y` order
+        let t = turbidity;
+        let coefficients = [
+            [
+                0.1787 * t - 1.4630,
+                -0.3554 * t + 0.4275,
+                -0.0227 * t + 5.3251,
+                0.1206 * t - 2.5771,
+                -0.0670 * t + 0.3703,
+            ],
+            [
+                -0.0193 * t - 0.2592,
+                -0.0665 * t + 0.0008,
+                -0.0004 * t + 0.2125,
+                -0.0641 * t - 0.8989,
+                -0.0033 * t + 0.0452,
+            ],
+            [
+                -0.0167 * t - 0.2608,
+                -0.0950 * t + 0.0092,
+                -0.0079 * t + 0.2102,
+                -0.0441 * t - 1.6537,
+                -0.0109 * t + 0.0529,
+            ],
+        ];
+
+        // Appendix A.2, with the sun's angle from the zenith
+        let theta_sun = PI / 2. - elevation_rad.clamp(0., PI / 2.);
+        let chi = (4. / 9. - t / 120.) * (PI - 2. * theta_sun);
+        let luminance = (4.0453 * t - 4.9710) * chi.tan() - 0.2155 * t + 2.4192;
+        let chromaticity = |rows: [[f64; 4]; 3]| {
+            let powers = [theta_sun.powi(3), theta_sun.powi(2), theta_sun, 1.];
+            let row = |coefficients: [f64; 4]| {
+                (0..4)
+                    .map(|index| coefficients[index] * powers[index])
+                    .sum::<f64>()
+            };
+            t * t * row(rows[0]) + t * row(rows[1]) + row(rows[2])
+        };
+        let x = chromaticity([
+            [0.00166, -0.00375, 0.00209, 0.],
+            [-0.02903, 0.06377, -0.03202, 0.00394],
+            [0.11693, -0.21196, 0.06052, 0.25886],
+        ]);
+        let y = chromaticity([
+            [0.00275, -0.00610, 0.00317, 0.],
+            [-0.04214, 0.08970, -0.04153, 0.00516],
+            [0.15346, -0.26756, 0.06670, 0.26688],
+        ]);
+        let zenith = [luminance, x, y];
+        let zenith =
+            std::array::from_fn(|index| zenith[index] / perez(&coefficients[index], 0., theta_sun));
+
+        // Sunlight is reddened by the air it passes through, following appendix A.1
+        let sun_solid_angle = 2. * PI * (1. - sun_cos_max);
+        let [r, g, b] = sun_transmittance(elevation, turbidity)
+            .map(|transmittance| SCALE * SUN_ILLUMINANCE * transmittance / sun_solid_angle);
+
+        Self {
+            elevation,
+            azimuth,
+            turbidity,
+            intensity,
+            sun_size,
+            sun,
+            sun_cos_max,
+            sun_color: Color::rgb(r, g, b),
+            coefficients,
+            zenith,
+        }
+    }
+
+    /// Light scattered towards the viewer by the atmosphere, without the sun disk
+    fn sky_color(&self, direction: &Point) -> Color {
+        // Looking below the horizon sees the horizon instead
+        let flat = Point::new(direction.x, 0., direction.z);
+        let direction = match (direction.y < 0., flat.len() > 0.) {
+            (true, true) => flat.normalized(),
+            (true, false) => Point::new(1., 0., 0.),
+            (false, _) => *direction,
+        };
+        let cos_theta = direction.y.max(0.01);
+        let gamma = direction.dot(self.sun).clamp(-1., 1.).acos();
+        let [luminance, x, y]: [f64; 3] = std::array::from_fn(|index| {
+            self.zenith[index] * perez(&self.coefficients[index], cos_theta.acos(), gamma)
+        });
+        let luminance = luminance.max(0.) * SCALE;
+
+        // Convert the chromaticity to XYZ, then to linear sRGB
+        let (big_x, big_z) = (x / y * luminance, (1. - x - y) / y * luminance);
+        Color::rgb(
+            3.2406 * big_x - 1.5372 * luminance - 0.4986 * big_z,
+            -0.9689 * big_x + 1.8758 * luminance + 0.0415 * big_z,
+            0.0557 * big_x - 0.2040 * luminance + 1.0570 * big_z,
+        )
+    }
+
+    fn is_sun(&self, direction: &Point) -> bool {
+        direction.y > 0. && direction.dot(self.sun) >= self.sun_cos_max
+    }
+}
+
+/// The Perez distribution of light across the sky, for a view `theta` from the zenith and
+/// `gamma` from the sun
+fn perez(coefficients: &[f64; 5], theta: f64, gamma: f64) -> f64 {
+    let [a, b, c, d, e] = *coefficients;
+    (1. + a * (b / theta.cos().max(0.01)).exp())
+        * (1. + c * (d * gamma).exp() + e * gamma.cos().powi(2))
+}
+
+/// Fraction of red, green and blue sunlight that reaches the ground through air molecules and
+/// haze, for a sun `elevation` degrees above the horizon
+fn sun_transmittance(elevation: f64, turbidity: f64) -> [f64; 3] {
+    if elevation <= 0. {
+        return [0.; 3];
+    }
+    // Relative optical mass of the air along the sun's path, from Kasten and Young
+    let zenith = 90. - elevation;
+    let mass = 1. / (zenith.to_radians().cos() + 0.15 * (93.885 - zenith).powf(-1.253));
+    let beta = 0.04608 * turbidity - 0.04586;
+
+    // Wavelengths in micrometers
+    [0.680, 0.550, 0.440].map(|wavelength: f64| {
+        let rayleigh = (-0.008735 * wavelength.powf(-4.08) * mass).exp();
+        let aerosol = (-beta * wavelength.powf(-1.3) * mass).exp();
+        rayleigh * aerosol
+    })
+}
+
+#[typetag::serde]
+impl Background for Sky {
+    fn color(&self, direction: &Point) -> Color {
+        let direction = direction.normalized();
+        let sky = self.sky_color(&direction);
+        let color = match self.is_sun(&direction) {
+            true => sky + self.sun_color,
+            false => sky,
+        };
+        color * self.intensity
+    }
+
+    fn is_emitter(&self) -> bool {
+        true
+    }
+
+    /// Picks the sun disk or a uniform direction above the horizon
+    fn sample(&self) -> Option<Point> {
+        let mut rng = rand::thread_rng();
+        if self.sun.y > 0. && rng.gen::<f64>() < SUN_PROBABILITY {
+            let cos_theta = 1. + rng.gen::<f64>() * (self.sun_cos_max - 1.);
+            let sin_theta = (1. - cos_theta.powi(2)).max(0.).sqrt();
+            let phi = 2. * PI * rng.gen::<f64>();
+            let (u, v) = self.sun.orthonormal_basis();
+            let direction =
+                sin_theta * phi.cos() * u + sin_theta * phi.sin() * v + cos_theta * self.sun;
+            // A large sun near the horizon dips below it, where `pdf` gives no density, so
+            // those samples are dropped instead of lighting the scene from underneath
+            return (direction.y > 0.).then_some(direction);
+        }
+        let direction = Point::random_in_sphere().normalized();
+        Some(Point::new(direction.x, direction.y.abs(), direction.z))
+    }
+
+    fn pdf(&self, direction: &Point) -> f64 {
+        let direction = direction.normalized();
+        if direction.y <= 0. {
+            return 0.;
+        }
+        let sun_probability = match self.sun.y > 0. {
+            true => SUN_PROBABILITY,
+            false => 0.,
+        };
+        let sun = match self.is_sun(&direction) {
+            true => sun_probability / (2. * PI * (1. - self.sun_cos_max)),
+            false => 0.,
+        };
+        sun + (1. - sun_probability) / (2. * PI)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        environments::{background::Background, sky::Sky},
+        utilities::point::Point,
+    };
+
+    #[test]
+    fn can_place_sun() {
+        let sky = Sky::new(30., 90., 3., 1., 0.53);
+        assert!((sky.sun - Point::new(0.75f64.sqrt(), 0.5, 0.)).len() < 1e-12);
+        assert!(sky.color(&sky.sun).g > 1000. * sky.color(&Point::new(0., 1., 0.)).g);
+    }
+
+    #[test]
+    fn can_color_sky() {
+        let sky = Sky::new(60., 0., 3., 1., 0.53);
+        let zenith = sky.color(&Point::new(0., 1., 0.));
+        // A clear sky is blue overhead
+        assert!(zenith.b > zenith.r && zenith.r > 0.);
+        // Looking below the horizon sees the horizon
+        let away = Point::new(0., 0., 1.);
+        assert_eq!(sky.color(&Point::new(0., -0.5, 1.)), sky.color(&away));
+    }
+
+    #[test]
+    fn can_redden_sunset() {
+        let noon = Sky::new(80., 0., 3., 1., 0.53).sun_color;
+        let sunset = Sky::new(3., 0., 3., 1., 0.53).sun_color;
+        assert!(noon.g > sunset.g);
+        assert!(sunset.r / sunset.b > noon.r / noon.b);
+    }
+
+    #[test]
+    fn can_sample_sun() {
+        let sky = Sky::new(45., 30., 3., 1., 2.);
+        let samples = 2000;
+        let hits = (0..samples)
+            .map(|_| sky.sample().unwrap())
+            .inspect(|direction| assert!(sky.pdf(direction) > 0.))
+            .filter(|direction| sky.is_sun(direction))
+            .count();
+        assert!((hits as f64 / samples as f64 - 0.5).abs() < 0.05);
+    }
+
+    #[test]
+    fn can_clip_sun_to_horizon() {
+        // A sun 10 degrees across, with its center 2 degrees up, is partly below the horizon
+        let sky = Sky::new(2., 0., 3., 1., 10.);
+        let samples: Vec<_> = (0..2000).map(|_| sky.sample()).collect();
+        assert!(samples.iter().any(|sample| sample.is_none()));
+        samples
+            .iter()
+            .flatten()
+            .for_each(|direction| assert!(sky.pdf(direction) > 0., "{direction:?}"));
+    }
+
+    #[test]
+    fn can_integrate_pdf() {
+        let sky = Sky::new(20., -40., 4., 1., 5.);
+        // Midpoint rule over the upper hemisphere, fine enough to resolve the sun disk
+        let (rings, segments) = (1000, 1000);
+        let step = (
+            std::f64::consts::PI / 2. / rings as f64,
+            2. * std::f64::consts::PI / segments as f64,
+        );
+        let total: f64 = (0..rings)
+            .flat_map(|ring| (0..segments).map(move |segment| (ring, segment)))
+            .map(|(ring, segment)| {
+                let theta = (ring as f64 + 0.5) * step.0;
+                let phi = (segment as f64 + 0.5) * step.1;
+                let direction = Point::new(
+                    theta.sin() * phi.cos(),
+                    theta.cos(),
+                    theta.sin() * phi.sin(),
+                );
+                sky.pdf(&direction) * theta.sin() * step.0 * step.1
+            })
+            .sum();
+        assert!((total - 1.).abs() < 0.01);
+    }
+}
@@ -1,55 +1,199 @@
 #![forbid(unsafe_code)]
 
+mod environments;
 mod materials;
 mod shapes;
+mod textures;
 mod utilities;
 
 use crate::{
-    shapes::{hit::Hittable, world::World},
+    shapes::hit::Hit,
     utilities::{
-        color::Color, image::Image, progress::build_progress_bar, ray::Ray, scene::Scene,
+        cli::Args,
+        color::Color,
+        progress::build_progress_bar,
+        ray::Ray,
+        scene::{Emitter, Scene},
         scenebuilder::build_scene,
+        spectrum::Wavelength,
     },
 };
 
+use clap::Parser;
 use format_num::format_num;
 use rand::Rng;
 use rayon::prelude::*;
 
 use std::{env, time::Instant};
 
-fn ray_color(ray: &Ray, world: &World, depth: u64) -> Color {
+/// Trace a ray through the scene and return the light it carries back
+///
+/// `scatter_pdf` is the density the previous bounce picked the ray's direction with, used to
+/// weigh emission against the light sampling done at that bounce; `None` counts emission in
+/// full, as for camera rays and rays from materials that cannot be lit directly.
+///
+/// `throughput` is the fraction of the returned light that reaches the camera, which decides
+/// how likely the path is to continue once Russian roulette starts.
+fn ray_color(
+    ray: &Ray,
+    scene: &Scene,
+    depth: u64,
+    scatter_pdf: Option<f64>,
+    throughput: Color,
+) -> Color {
     if depth == 0 {
         return Color::default();
     }
 
-    if let Some(hit) = world.hit(ray, 0.001, f64::INFINITY) {
-        // Hit, generate a color using the material
-        if let Some((attenuation, scattered)) = hit.material.scatter(ray, &hit) {
-            attenuation * ray_color(&scattered, world, depth - 1)
-        } else {
-            hit.material.emit()
+    let surface = scene.hit_shape(ray, 0.001, f64::INFINITY);
+
+    // Particles in the atmosphere may scatter the ray before it reaches the surface
+    if let Some(atmosphere) = &scene.atmosphere {
+        if let Some(hit) = atmosphere.hit(ray, surface.as_ref().map(|(_, hit)| hit.time)) {
+            return scatter_color(ray, &hit, scene, depth, throughput);
         }
+    }
+
+    if let Some((shape, hit)) = surface {
+        // Hit, add the light the surface gives off to the light its material scatters
+        let emitted = hit.material.emit();
+        let weight = match scatter_pdf {
+            Some(pdf) if !emitted.is_black() => power_heuristic(
+                pdf,
+                scene.light_pdf(Emitter::Shape(shape), &ray.origin, &ray.direction, ray.time),
+            ),
+            _ => 1.,
+        };
+        emitted * weight + scatter_color(ray, &hit, scene, depth, throughput)
     } else {
-        // Miss, generate sky
-        let unit_direction = ray.direction.normalized();
-        let t = 0.5 * (unit_direction.y + 1.0);
-        // Generate a linear gradient from max color to min color for each hue
-        (1.0 - t) * Color::gray(1.0) + t * Color::rgb(0.5, 0.5, 0.9)
-        // Color::default()
+        // Miss, look up the environment
+        let weight = match scatter_pdf {
+            Some(pdf) => power_heuristic(
+                pdf,
+                scene.light_pdf(Emitter::Environment, &ray.origin, &ray.direction, ray.time),
+            ),
+            None => 1.,
+        };
+        scene.environment.color(&ray.direction) * weight
+    }
+}
+
+/// Light that the material at `hit` scatters towards the camera along `ray`, both straight
+/// from a sampled light and from following the ray on to its next bounce
+fn scatter_color(ray: &Ray, hit: &Hit, scene: &Scene, depth: u64, throughput: Color) -> Color {
+    // `eval` also counts the rays that `scatter` absorbs or whose samples it rejects, so lights
+    // are sampled whether or not the path goes on
+    let direct = sample_direct_light(ray, hit, scene);
+    let Some((mut attenuation, mut scattered)) = hit.material.scatter(ray, hit) else {
+        return direct;
+    };
+    // Only dispersive materials narrow the light down, the rest pass it along unchanged
+    if scattered.wavelength == Wavelength::Rgb {
+        scattered.wavelength = ray.wavelength;
+    }
+
+    // End paths that carry little light at random, boosting the ones that survive
+    let render = &scene.settings.render;
+    if render.max_depth - depth >= render.roulette_depth {
+        let survival = survival_probability(throughput * attenuation);
+        if rand::thread_rng().gen::<f64>() >= survival {
+            return direct;
+        }
+        attenuation = attenuation * (1. / survival);
+    }
+
+    let next_pdf = hit.material.pdf(ray, hit, &scattered.direction);
+    direct
+        + attenuation
+            * ray_color(
+                &scattered,
+                scene,
+                depth - 1,
+                next_pdf,
+                throughput * attenuation,
+            )
+}
+
+/// Estimate the light reaching `hit` straight from one of the scene's lights by casting a
+/// shadow ray towards it, weighted against finding the same light with `scatter`
+///
+/// Materials that cannot be lit directly only receive light from rays that `scatter` happens
+/// to send towards it.
+fn sample_direct_light(ray: &Ray, hit: &Hit, scene: &Scene) -> Color {
+    // Whether the material can be lit does not depend on the direction, so it is checked
+    // before picking a light that would only be thrown away
+    if hit.material.pdf(ray, hit, &hit.normal).is_none() {
+        return Color::default();
+    }
+    let Some((light, direction)) = scene.sample_light(&hit.point, ray.time) else {
+        return Color::default();
+    };
+    let reflectance = match hit.material.eval(ray, hit, &direction) {
+        Some(reflectance) if !reflectance.is_black() => reflectance,
+        _ => return Color::default(),
+    };
+
+    // The light only contributes if nothing else is in the way
+    let shadow_ray = Ray::new(hit.point, direction, ray.time);
+    let (emitted, distance) = match (light, scene.hit_shape(&shadow_ray, 0.001, f64::INFINITY)) {
+        (Emitter::Shape(index), Some((shape, light_hit))) if shape == index => (
+            light_hit.material.emit(),
+            Some(light_hit.time * direction.len()),
+        ),
+        (Emitter::Environment, None) => (scene.environment.color(&direction), None),
+        _ => return Color::default(),
+    };
+    // Some of it is lost to the atmosphere on the way
+    let emitted = match &scene.atmosphere {
+        Some(atmosphere) => emitted * atmosphere.transmittance(distance),
+        None => emitted,
+    };
+
+    let light_pdf = scene.light_pdf(light, &hit.point, &direction, ray.time);
+    if light_pdf <= 0. {
+        return Color::default();
+    }
+    let scatter_pdf = hit.material.pdf(ray, hit, &direction).unwrap_or(0.);
+    reflectance * emitted * (power_heuristic(light_pdf, scatter_pdf) / light_pdf)
+}
+
+/// Chance of a path continuing under Russian roulette, following its brightest channel
+///
+/// https://pbr-book.org/3ed-2018/Light_Transport_I_Surface_Reflection/Path_Tracing#RussianRoulette
+fn survival_probability(throughput: Color) -> f64 {
+    throughput.max_channel().clamp(0., 0.95)
+}
+
+/// Multiple importance sampling weight for a sample drawn with density `pdf`, when `other`
+/// is the density another strategy would have drawn it with
+///
+/// https://pbr-book.org/3ed-2018/Monte_Carlo_Integration/Importance_Sampling#MultipleImportanceSampling
+fn power_heuristic(pdf: f64, other: f64) -> f64 {
+    let (pdf, other) = (pdf.powi(2), other.powi(2));
+    match pdf + other > 0. {
+        true => pdf / (pdf + other),
+        false => 0.,
     }
 }
 
 fn main() {
-    let mut scene = Scene::load(
-        env::current_dir().unwrap().to_str().unwrap(),
-        "scenes/test",
-    );
-    // let mut scene = build_scene();
-    // scene.save(
-    //     env::current_dir().unwrap().to_str().unwrap(),
-    //     "scenes/triangle",
-    // );
+    let args = Args::parse();
+    let current_dir = env::current_dir().unwrap();
+    let current_dir = current_dir.to_str().unwrap();
+
+    let mut scene = match args.example {
+        true => build_scene(),
+        false => Scene::load(current_dir, args.scene_name()),
+    };
+    args.apply(&mut scene);
+
+    if let Some(filename) = &args.save_scene {
+        scene.save(
+            current_dir,
+            filename.strip_suffix(".scene").unwrap_or(filename),
+        );
+        return;
+    }
 
     let pb = build_progress_bar(scene.image.pixels());
     let mut pixels_rendered = 0;
@@ -67,7 +211,8 @@ fn main() {
 
                 // Generate random rays for each pixel
                 let mut rng = rand::thread_rng();
-                for _ in 0..scene.settings.render.msaa_samples as u64 {
+                let samples = scene.settings.render.msaa_samples as u64;
+                for sample in 0..samples {
                     // Get random endpoint
                     let random_u: f64 = rng.gen();
                     let random_v: f64 = rng.gen();
@@ -75,10 +220,21 @@ fn main() {
                     // Create valid (u, v) direction for ray
                     let u = ((col as f64) + random_u) / ((scene.image.width - 1) as f64);
                     let v = ((row as f64) + random_v) / ((scene.image.height - 1) as f64);
-                    let r = scene.camera.get_ray(u, v);
+                    let mut r = scene.camera.get_ray(u, v);
+                    if scene.settings.render.spectral {
+                        // Spread the pixel's hero wavelengths evenly across the spectrum
+                        let stratum = (sample as f64 + rng.gen::<f64>()) / samples as f64;
+                        r.wavelength = Wavelength::sample(stratum);
+                    }
 
                     // Get the pixel color
-                    let pixel = ray_color(&r, &scene.world, scene.settings.render.max_depth);
+                    let pixel = ray_color(
+                        &r,
+                        &scene,
+                        scene.settings.render.max_depth,
+                        None,
+                        Color::gray(1.),
+                    );
                     red_component += pixel.r;
                     green_component += pixel.g;
                     blue_component += pixel.b;
@@ -111,8 +267,100 @@ fn main() {
         format_num!(",d", scene.image.buffer.len() as f64 / elapsed as f64)
     );
 
-    scene.render(
-        env::current_dir().unwrap().to_str().unwrap(),
-        "render/sky_gradient",
-    );
+    let (filename, format) = args.output();
+    scene.render(current_dir, &filename, format);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        environments::constant::Constant,
+        materials::{diffuse::Lambertian, light::Light},
+        power_heuristic, ray_color,
+        shapes::{disk::Disk, plane::Plane},
+        survival_probability,
+        utilities::{
+            camera::{Camera, CameraSettings},
+            color::Color,
+            colorspace::ColorSpace,
+            image::Image,
+            point::Point,
+            ray::Ray,
+            scene::{RenderSettings, Scene, Settings},
+        },
+    };
+
+    /// A gray floor under a round light of radius 1, one unit above the origin, in the dark
+    fn lit_floor(probability: f64) -> Scene {
+        // Direct light and one bounce, without Russian roulette
+        let render = RenderSettings::new(1., 2, ColorSpace::Srgb, 0., 1.);
+        let camera = CameraSettings::new(
+            Point::new(0., 1., 0.),
+            Point::new(-1., 0.5, 0.),
+            Point::origin(),
+            40.,
+            1.,
+            0.,
+            1.,
+            0.,
+            1.,
+        );
+        let floor = Plane::new(
+            Point::origin(),
+            Point::new(0., 1., 0.),
+            Box::new(Lambertian::new(Color::gray(0.5).into(), probability)),
+        );
+        let light = Disk::new(
+            Point::new(0., 1., 0.),
+            Point::new(0., -1., 0.),
+            1.,
+            Box::new(Light::new(Color::gray(1.), 1.)),
+        );
+        let world = vec![Box::new(floor) as _, Box::new(light) as _];
+        let view = Camera::new(&camera);
+        let mut scene = Scene::new(
+            Settings::new(render, camera),
+            Image::from_dimensions(1, 1),
+            view,
+            world,
+        );
+        scene.environment = Box::new(Constant::new(Color::default()));
+        scene
+    }
+
+    #[test]
+    fn can_weigh_strategies() {
+        assert_eq!(power_heuristic(1., 0.), 1.);
+        assert_eq!(power_heuristic(0., 1.), 0.);
+        assert_eq!(power_heuristic(0., 0.), 0.);
+        assert_eq!(power_heuristic(3., 1.), 0.9);
+        assert_eq!(power_heuristic(3., 1.) + power_heuristic(1., 3.), 1.);
+    }
+
+    #[test]
+    fn can_get_survival_probability() {
+        assert_eq!(survival_probability(Color::rgb(0.1, 0.4, 0.2)), 0.4);
+        assert_eq!(survival_probability(Color::gray(3.)), 0.95);
+        assert_eq!(survival_probability(Color::gray(-1.)), 0.);
+    }
+
+    #[test]
+    fn can_light_absorbing_surface() {
+        // The light covers a solid angle whose projected area is pi / 2 as seen from the
+        // origin, so the floor there reflects probability * albedo / 2 of its brightness
+        let ray = Ray::new(Point::new(-1., 0.5, 0.), Point::new(1., -0.5, 0.), 0.);
+        [0.5, 1.].iter().for_each(|&probability| {
+            let scene = lit_floor(probability);
+            let samples = 20000;
+            let total: f64 = (0..samples)
+                .map(|_| ray_color(&ray, &scene, 2, None, Color::gray(1.)).r)
+                .sum();
+            let radiance = total / samples as f64;
+            let expected = probability * 0.5 / 2.;
+            assert!(
+                (radiance - expected).abs() < 5e-3,
+                "{probability} {radiance}"
+            );
+        });
+    }
 }
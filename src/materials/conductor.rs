@@ -0,0 +1,236 @@
+use rand::Rng;
+
+use crate::{
+    materials::{
+        microfacet::{fresnel_conductor, local, Ggx},
+        scatter::Scatter,
+    },
+    shapes::hit::Hit,
+    utilities::{color::Color, point::Point, ray::Ray},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Complex refractive index `eta + ik` of a metal, sampled at red, green and blue wavelengths
+///
+/// https://refractiveindex.info
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum ComplexIor {
+    Gold,
+    Copper,
+    Aluminium,
+    Custom { eta: Color, k: Color },
+}
+
+impl ComplexIor {
+    pub fn eta_k(&self) -> (Color, Color) {
+        match *self {
+            ComplexIor::Gold => (
+                Color::rgb(0.143, 0.374, 1.442),
+                Color::rgb(3.983, 2.385, 1.603),
+            ),
+            ComplexIor::Copper => (
+                Color::rgb(0.200, 0.924, 1.102),
+                Color::rgb(3.912, 2.452, 2.142),
+            ),
+            ComplexIor::Aluminium => (
+                Color::rgb(1.657, 0.880, 0.521),
+                Color::rgb(9.224, 6.270, 4.837),
+            ),
+            ComplexIor::Custom { eta, k } => (eta, k),
+        }
+    }
+}
+
+/// A metal made of microscopic mirrors facing in directions spread out by `roughness`, with
+/// the color of its reflections coming from its complex refractive index
+///
+/// Unlike `Metal`, the spread follows the GGX distribution and reflectance follows the Fresnel
+/// equations, so highlights stretch and brighten at grazing angles like real metals.
+///
+/// https://www.cs.cornell.edu/~srm/publications/EGSR07-btdf.pdf
+#[derive(Serialize, Deserialize)]
+pub struct Conductor {
+    ior: ComplexIor,
+    /// 0 is a perfect mirror, 1 is fully rough
+    roughness: f64,
+}
+
+impl Conductor {
+    pub fn new(ior: ComplexIor, roughness: f64) -> Self {
+        Self { ior, roughness }
+    }
+
+    fn fresnel(&self, cos_i: f64) -> Color {
+        let (eta, k) = self.ior.eta_k();
+        fresnel_conductor(cos_i, &eta, &k)
+    }
+}
+
+#[typetag::serde]
+impl Scatter for Conductor {
+    fn scatter(&self, ray_in: &Ray, hit: &Hit) -> Option<(Color, Ray)> {
+        if Ggx::is_smooth(self.roughness) {
+            let reflected = ray_in.direction.normalized().reflect(hit.normal);
+            let cos_i = reflected.dot(hit.normal);
+            return Some((
+                self.fresnel(cos_i),
+                Ray::new(hit.point, reflected, ray_in.time),
+            ));
+        }
+
+        let ggx = Ggx::new(self.roughness);
+        let (frame, outgoing, _) = local(ray_in, hit, &hit.normal);
+        let normal = ggx.sample_visible(&outgoing);
+        let incoming = (-outgoing).reflect(normal);
+        if incoming.z <= 0. {
+            return None;
+        }
+
+        // Visible normal sampling cancels everything but Fresnel and the shadowing of the
+        // reflected direction
+        let weight = ggx.shadowing(&outgoing, &incoming) / ggx.masking(&outgoing);
+        Some((
+            self.fresnel(outgoing.dot(normal)) * weight,
+            Ray::new(hit.point, frame.to_world_vector(&incoming), ray_in.time),
+        ))
+    }
+
+    /// `D * G * F / (4 * cos_o)`, the microfacet reflection including the cosine term
+    fn eval(&self, ray_in: &Ray, hit: &Hit, direction: &Point) -> Option<Color> {
+        if Ggx::is_smooth(self.roughness) {
+            return None;
+        }
+        let ggx = Ggx::new(self.roughness);
+        let (_, outgoing, incoming) = local(ray_in, hit, direction);
+        if outgoing.z <= 0. || incoming.z <= 0. {
+            return Some(Color::default());
+        }
+        let normal = (outgoing + incoming).normalized();
+        let value =
+            ggx.distribution(&normal) * ggx.shadowing(&outgoing, &incoming) / (4. * outgoing.z);
+        Some(self.fresnel(outgoing.dot(normal)) * value)
+    }
+
+    /// A smooth conductor only reflects in one direction, like `Mirror`
+    fn pdf(&self, ray_in: &Ray, hit: &Hit, direction: &Point) -> Option<f64> {
+        if Ggx::is_smooth(self.roughness) {
+            return None;
+        }
+        let ggx = Ggx::new(self.roughness);
+        let (_, outgoing, incoming) = local(ray_in, hit, direction);
+        if incoming.z <= 0. {
+            return Some(0.);
+        }
+        let normal = (outgoing + incoming).normalized();
+        let cos = outgoing.dot(normal);
+        match cos > 0. {
+            true => Some(ggx.visible_pdf(&outgoing, &normal) / (4. * cos)),
+            false => Some(0.),
+        }
+    }
+
+    fn emit(&self) -> Color {
+        Color::default()
+    }
+
+    fn random() -> Self
+    where
+        Self: Sized,
+    {
+        let mut rng = rand::thread_rng();
+        let ior = match rng.gen_range(0..3) {
+            0 => ComplexIor::Gold,
+            1 => ComplexIor::Copper,
+            _ => ComplexIor::Aluminium,
+        };
+        Self::new(ior, rng.gen_range(0.05..0.6))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        materials::{
+            conductor::{ComplexIor, Conductor},
+            microfacet::{local, Ggx},
+            scatter::{tests::integrate, Material, Scatter},
+        },
+        shapes::hit::Hit,
+        utilities::{color::Color, point::Point, ray::Ray},
+    };
+
+    fn setup() -> (Material, Ray) {
+        let material: Material = Box::new(Conductor::new(ComplexIor::Gold, 0.));
+        let ray_in = Ray::new(Point::new(-1., 1., 0.), Point::new(1., -1., 0.), 0.);
+        (material, ray_in)
+    }
+
+    #[test]
+    fn can_match_scatter() {
+        let (material, ray_in) = setup();
+        let hit = Hit::new(Point::origin(), Point::new(0., 1., 0.), &material, 1., true);
+        let conductor = Conductor::new(ComplexIor::Copper, 0.4);
+        for _ in 0..100 {
+            let Some((attenuation, scattered)) = conductor.scatter(&ray_in, &hit) else {
+                continue;
+            };
+            let pdf = conductor.pdf(&ray_in, &hit, &scattered.direction).unwrap();
+            let eval = conductor.eval(&ray_in, &hit, &scattered.direction).unwrap();
+            assert!(pdf > 0.);
+            assert!((eval.g / pdf - attenuation.g).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn can_integrate_density() {
+        let (material, ray_in) = setup();
+        let hit = Hit::new(Point::origin(), Point::new(0., 1., 0.), &material, 1., true);
+        let conductor = Conductor::new(ComplexIor::Aluminium, 0.5);
+        let integral = integrate(400, |direction| {
+            conductor.pdf(&ray_in, &hit, direction).unwrap()
+        });
+        // Facets that reflect below the surface lose their samples, so the density integrates
+        // to the share of visible facets that reflect above it
+        let ggx = Ggx::new(0.5);
+        let (_, outgoing, _) = local(&ray_in, &hit, &hit.normal);
+        let kept = integrate(400, |normal| match (-outgoing).reflect(*normal).z > 0. {
+            true => ggx.visible_pdf(&outgoing, normal),
+            false => 0.,
+        });
+        assert!(kept < 1.);
+        assert!((integral - kept).abs() < 1e-2, "{integral} {kept}");
+    }
+
+    #[test]
+    fn can_reflect_smooth() {
+        let (material, ray_in) = setup();
+        let hit = Hit::new(Point::origin(), Point::new(0., 1., 0.), &material, 1., true);
+        let conductor = Conductor::new(ComplexIor::Gold, 0.);
+        let (attenuation, scattered) = conductor.scatter(&ray_in, &hit).unwrap();
+        assert!((scattered.direction - Point::new(1., 1., 0.).normalized()).len() < 1e-9);
+        assert!(attenuation.r > attenuation.b);
+        assert!(conductor.pdf(&ray_in, &hit, &scattered.direction).is_none());
+        assert!(conductor
+            .eval(&ray_in, &hit, &scattered.direction)
+            .is_none());
+    }
+
+    #[test]
+    fn can_deserialize() {
+        let yaml = "type: Conductor\nior:\n  type: Custom\n  eta: {r: 0.2, g: 0.2, b: 0.2, a: 255}\n  k: {r: 3.0, g: 3.0, b: 3.0, a: 255}\nroughness: 0.3\n";
+        let material: Material = serde_yml::from_str(yaml).unwrap();
+        let hit = Hit::new(Point::origin(), Point::new(0., 1., 0.), &material, 1., true);
+        let ray_in = Ray::new(Point::new(0., 1., 0.), Point::new(0., -1., 0.), 0.);
+        let eval = material
+            .eval(&ray_in, &hit, &Point::new(0., 1., 0.))
+            .unwrap();
+        assert!(eval.r > 0.);
+        assert_eq!(eval.r, eval.b);
+        let gold: Material =
+            serde_yml::from_str("type: Conductor\nior:\n  type: Gold\nroughness: 0.0\n").unwrap();
+        assert!(gold.eval(&ray_in, &hit, &Point::new(0., 1., 0.)).is_none());
+        assert_eq!(Color::default(), material.emit());
+    }
+}
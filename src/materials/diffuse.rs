@@ -1,24 +1,49 @@
+use std::f64::consts::PI;
+
 use rand::Rng;
 
 use crate::{
     materials::scatter::Scatter,
     shapes::hit::Hit,
+    textures::texture::{self, Texture},
     utilities::{color::Color, point::Point, ray::Ray},
 };
 
 use serde::{Deserialize, Serialize};
 
+/// Scene file representation of a Lambertian, whose probability is clamped like in `new`
+#[derive(Deserialize)]
+struct LambertianFile {
+    #[serde(deserialize_with = "texture::deserialize")]
+    albedo: Box<dyn Texture>,
+    #[serde(default = "Lambertian::default_probability")]
+    probability: f64,
+}
+
 #[derive(Serialize, Deserialize)]
+#[serde(from = "LambertianFile")]
 pub struct Lambertian {
-    albedo: Color,
+    albedo: Box<dyn Texture>,
+    /// 0..1 chance that light reaching the surface scatters instead of being absorbed, which
+    /// darkens it like a lower `albedo` while ending the absorbed paths early
     probability: f64,
 }
 
+impl From<LambertianFile> for Lambertian {
+    fn from(file: LambertianFile) -> Self {
+        Lambertian::new(file.albedo, file.probability)
+    }
+}
+
 impl Lambertian {
-    pub fn new(albedo: Color, probability: f64) -> Self {
+    fn default_probability() -> f64 {
+        1.
+    }
+
+    pub fn new(albedo: Box<dyn Texture>, probability: f64) -> Self {
         Self {
             albedo,
-            probability,
+            probability: probability.clamp(0., 1.),
         }
     }
 }
@@ -26,13 +51,31 @@ impl Lambertian {
 #[typetag::serde]
 impl Scatter for Lambertian {
     fn scatter(&self, ray_in: &Ray, hit: &Hit) -> Option<(Color, Ray)> {
+        if rand::thread_rng().gen::<f64>() >= self.probability {
+            return None;
+        }
         let mut target = hit.normal + Point::random_in_sphere().normalized();
         if target.is_near_zero() {
             target = hit.normal
         }
         let scattered = Ray::new(hit.point, target, ray_in.time);
 
-        Some((self.albedo, scattered))
+        Some((
+            self.albedo.value(hit.uv, &hit.local, ray_in.time),
+            scattered,
+        ))
+    }
+
+    /// `scatter` picks directions with a cosine distribution when the light is not absorbed,
+    /// so this is `albedo * probability * cos / pi`
+    fn eval(&self, ray_in: &Ray, hit: &Hit, direction: &Point) -> Option<Color> {
+        let cosine = hit.normal.dot(direction.normalized()).max(0.);
+        Some(self.albedo.value(hit.uv, &hit.local, ray_in.time) * (self.probability * cosine / PI))
+    }
+
+    /// Absorbed light has no direction, so the density only integrates to `probability`
+    fn pdf(&self, _: &Ray, hit: &Hit, direction: &Point) -> Option<f64> {
+        Some(self.probability * hit.normal.dot(direction.normalized()).max(0.) / PI)
     }
 
     fn emit(&self) -> Color {
@@ -45,8 +88,103 @@ impl Scatter for Lambertian {
     {
         let mut rng = rand::thread_rng();
         Self {
-            albedo: Color::random(),
+            albedo: Color::random().into(),
             probability: rng.gen_range(0.0..1.0),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use crate::{
+        materials::{
+            diffuse::Lambertian,
+            scatter::{tests::integrate, Material, Scatter},
+        },
+        shapes::hit::Hit,
+        utilities::{color::Color, point::Point, ray::Ray},
+    };
+
+    #[test]
+    fn can_absorb() {
+        let material: Material = Box::new(Lambertian::new(Color::gray(1.).into(), 1.));
+        let hit = Hit::new(Point::origin(), Point::new(0., 1., 0.), &material, 1., true);
+        let ray_in = Ray::new(Point::new(-1., 1., 0.), Point::new(1., -1., 0.), 0.);
+        let samples = 20000;
+        [0., 0.3, 1.].iter().for_each(|&probability| {
+            let lambertian = Lambertian::new(Color::gray(0.8).into(), probability);
+            let scattered = (0..samples)
+                .filter(|_| lambertian.scatter(&ray_in, &hit).is_some())
+                .count();
+            let fraction = scattered as f64 / samples as f64;
+            assert!((fraction - probability).abs() < 0.02, "{fraction}");
+        });
+    }
+
+    #[test]
+    fn can_match_scatter() {
+        let material: Material = Box::new(Lambertian::new(Color::gray(1.).into(), 1.));
+        let hit = Hit::new(Point::origin(), Point::new(0., 1., 0.), &material, 1., true);
+        let ray_in = Ray::new(Point::new(-1., 1., 0.), Point::new(1., -1., 0.), 0.);
+        let lambertian = Lambertian::new(Color::rgb(0.9, 0.5, 0.1).into(), 0.5);
+        for _ in 0..100 {
+            let Some((attenuation, scattered)) = lambertian.scatter(&ray_in, &hit) else {
+                continue;
+            };
+            let pdf = lambertian.pdf(&ray_in, &hit, &scattered.direction).unwrap();
+            let eval = lambertian
+                .eval(&ray_in, &hit, &scattered.direction)
+                .unwrap();
+            assert!(pdf > 0.);
+            assert!((eval.g / pdf - attenuation.g).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn can_integrate_density() {
+        let material: Material = Box::new(Lambertian::new(Color::gray(1.).into(), 1.));
+        let hit = Hit::new(Point::origin(), Point::new(0., 1., 0.), &material, 1., true);
+        let ray_in = Ray::new(Point::new(-1., 1., 0.), Point::new(1., -1., 0.), 0.);
+        [0.25, 1.].iter().for_each(|&probability| {
+            let lambertian = Lambertian::new(Color::gray(1.).into(), probability);
+            let integral = integrate(200, |direction| {
+                lambertian.pdf(&ray_in, &hit, direction).unwrap()
+            });
+            assert!((integral - probability).abs() < 1e-3, "{integral}");
+        });
+    }
+
+    #[test]
+    fn can_default_probability() {
+        let yaml = "type: Lambertian\nalbedo: {r: 0.5, g: 0.5, b: 0.5, a: 255}\n";
+        let material: Material = serde_yml::from_str(yaml).unwrap();
+        let hit = Hit::new(Point::origin(), Point::new(0., 1., 0.), &material, 1., true);
+        let ray_in = Ray::new(Point::new(-1., 1., 0.), Point::new(1., -1., 0.), 0.);
+        assert!((0..100).all(|_| material.scatter(&ray_in, &hit).is_some()));
+        assert_eq!(Lambertian::new(Color::gray(1.).into(), 2.).probability, 1.);
+    }
+
+    #[test]
+    fn can_clamp_probability() {
+        let albedo = "type: Lambertian\nalbedo: {r: 0.5, g: 0.5, b: 0.5, a: 255}\n";
+        [(2., 1.), (-0.5, 0.)]
+            .iter()
+            .for_each(|&(probability, clamped)| {
+                let yaml = format!("{albedo}probability: {probability}\n");
+                let material: Material = serde_yml::from_str(&yaml).unwrap();
+                let hit = Hit::new(Point::origin(), Point::new(0., 1., 0.), &material, 1., true);
+                let ray_in = Ray::new(Point::new(-1., 1., 0.), Point::new(1., -1., 0.), 0.);
+                // Straight up, where the cosine is 1
+                let up = Point::new(0., 1., 0.);
+                let pdf = material.pdf(&ray_in, &hit, &up).unwrap();
+                let eval = material.eval(&ray_in, &hit, &up).unwrap();
+                assert!((pdf - clamped / PI).abs() < 1e-12, "{probability} {pdf}");
+                assert!(
+                    (eval.r - 0.5 * clamped / PI).abs() < 1e-12,
+                    "{probability} {eval:?}"
+                );
+            });
+    }
+}
@@ -0,0 +1,282 @@
+use rand::Rng;
+
+use crate::{
+    materials::{
+        microfacet::{fresnel_dielectric, local, Ggx},
+        scatter::Scatter,
+    },
+    shapes::hit::Hit,
+    textures::texture::{self, Texture},
+    utilities::{color::Color, point::Point, ray::Ray},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Frosted glass: a dielectric whose surface is made of microscopic facets facing in directions
+/// spread out by `roughness`, which blur both its reflections and what is seen through it
+///
+/// https://www.cs.cornell.edu/~srm/publications/EGSR07-btdf.pdf
+#[derive(Serialize, Deserialize)]
+pub struct RoughDielectric {
+    #[serde(deserialize_with = "texture::deserialize")]
+    albedo: Box<dyn Texture>,
+    /// air = 1.0, glass = 1.3–1.7, diamond = 2.4
+    refraction_index: f64,
+    /// 0 is smooth like `Dielectric`, 1 is fully rough
+    roughness: f64,
+}
+
+impl RoughDielectric {
+    pub fn new(albedo: Box<dyn Texture>, refraction_index: f64, roughness: f64) -> Self {
+        Self {
+            albedo,
+            refraction_index,
+            roughness,
+        }
+    }
+
+    /// Ratio of the refractive index across the surface to the one the ray arrives through
+    fn eta(&self, hit: &Hit) -> f64 {
+        match hit.front_face {
+            true => self.refraction_index,
+            false => 1. / self.refraction_index,
+        }
+    }
+
+    /// Microfacet normal that turns `outgoing` into `incoming`, facing the same side as the
+    /// surface, or `None` if no facet can
+    fn half_vector(outgoing: &Point, incoming: &Point, eta: f64) -> Option<Point> {
+        let reflect = incoming.z > 0.;
+        let scale = match reflect {
+            true => 1.,
+            false => eta,
+        };
+        let mut normal = (*outgoing + scale * *incoming).normalized();
+        if normal.z < 0. {
+            normal = -normal;
+        }
+        let valid = outgoing.dot(normal) > 0.
+            && match reflect {
+                true => incoming.dot(normal) > 0.,
+                false => incoming.dot(normal) < 0.,
+            };
+        valid.then_some(normal)
+    }
+
+    /// Direction light from `outgoing` bends into through a facet facing `normal`
+    fn refract(outgoing: &Point, normal: &Point, eta: f64) -> Point {
+        let cos_o = outgoing.dot(*normal);
+        let cos_t = (1. - (1. - cos_o * cos_o) / (eta * eta)).max(0.).sqrt();
+        -*outgoing / eta + (cos_o / eta - cos_t) * *normal
+    }
+
+    /// Reflect or refract `outgoing` off a facet facing `normal`, picking between them in
+    /// proportion to the facet's reflectance
+    pub fn pick(outgoing: &Point, normal: &Point, eta: f64) -> Point {
+        let cos_o = outgoing.dot(*normal);
+        match rand::thread_rng().gen::<f64>() < fresnel_dielectric(cos_o, eta) {
+            true => (-*outgoing).reflect(*normal),
+            false => Self::refract(outgoing, normal, eta),
+        }
+    }
+
+    /// Microfacet reflection and transmission between directions in the frame of the surface
+    /// normal, including the cosine term
+    pub fn eval_local(ggx: &Ggx, eta: f64, outgoing: &Point, incoming: &Point) -> f64 {
+        let Some(normal) = Self::half_vector(outgoing, incoming, eta) else {
+            return 0.;
+        };
+        let cos_o = outgoing.dot(normal);
+        let reflectance = fresnel_dielectric(cos_o, eta);
+        let facets = ggx.distribution(&normal) * ggx.shadowing(outgoing, incoming);
+        match incoming.z > 0. {
+            true => facets * reflectance / (4. * outgoing.z),
+            false => {
+                let cos_i = incoming.dot(normal);
+                let spread = (cos_i + cos_o / eta).powi(2);
+                facets * (1. - reflectance) * (cos_i * cos_o).abs() / (outgoing.z * spread)
+            }
+        }
+    }
+
+    /// Density of picking `incoming` from a visible facet, in the frame of the surface normal
+    pub fn pdf_local(ggx: &Ggx, eta: f64, outgoing: &Point, incoming: &Point) -> f64 {
+        let Some(normal) = Self::half_vector(outgoing, incoming, eta) else {
+            return 0.;
+        };
+        let cos_o = outgoing.dot(normal);
+        let reflectance = fresnel_dielectric(cos_o, eta);
+        let density = ggx.visible_pdf(outgoing, &normal);
+        match incoming.z > 0. {
+            true => density / (4. * cos_o) * reflectance,
+            false => {
+                let cos_i = incoming.dot(normal);
+                let spread = (cos_i + cos_o / eta).powi(2);
+                density * cos_i.abs() / spread * (1. - reflectance)
+            }
+        }
+    }
+}
+
+#[typetag::serde]
+impl Scatter for RoughDielectric {
+    fn scatter(&self, ray_in: &Ray, hit: &Hit) -> Option<(Color, Ray)> {
+        let smooth = Ggx::is_smooth(self.roughness);
+        let ggx = Ggx::new(self.roughness);
+        let eta = self.eta(hit);
+        let (frame, outgoing, _) = local(ray_in, hit, &hit.normal);
+        let normal = match smooth {
+            true => Point::new(0., 0., 1.),
+            false => ggx.sample_visible(&outgoing),
+        };
+
+        let incoming = Self::pick(&outgoing, &normal, eta);
+        if !smooth && (incoming.z > 0.) != (incoming.dot(normal) > 0.) {
+            return None;
+        }
+
+        // Visible normal sampling cancels everything but the shadowing of the new direction
+        let weight = match smooth {
+            true => 1.,
+            false => ggx.shadowing(&outgoing, &incoming) / ggx.masking(&outgoing),
+        };
+        Some((
+            self.albedo.value(hit.uv, &hit.local, ray_in.time) * weight,
+            Ray::new(
+                hit.point,
+                frame.to_world_vector(&incoming.normalized()),
+                ray_in.time,
+            ),
+        ))
+    }
+
+    /// Microfacet reflection and transmission including the cosine term
+    fn eval(&self, ray_in: &Ray, hit: &Hit, direction: &Point) -> Option<Color> {
+        if Ggx::is_smooth(self.roughness) {
+            return None;
+        }
+        let ggx = Ggx::new(self.roughness);
+        let eta = self.eta(hit);
+        let (_, outgoing, incoming) = local(ray_in, hit, direction);
+        let value = Self::eval_local(&ggx, eta, &outgoing, &incoming);
+        Some(self.albedo.value(hit.uv, &hit.local, ray_in.time) * value)
+    }
+
+    /// A smooth dielectric only scatters in the directions it picks, like `Dielectric`
+    fn pdf(&self, ray_in: &Ray, hit: &Hit, direction: &Point) -> Option<f64> {
+        if Ggx::is_smooth(self.roughness) {
+            return None;
+        }
+        let ggx = Ggx::new(self.roughness);
+        let eta = self.eta(hit);
+        let (_, outgoing, incoming) = local(ray_in, hit, direction);
+        Some(Self::pdf_local(&ggx, eta, &outgoing, &incoming))
+    }
+
+    fn emit(&self) -> Color {
+        Color::default()
+    }
+
+    fn random() -> Self
+    where
+        Self: Sized,
+    {
+        let mut rng = rand::thread_rng();
+        Self::new(
+            Color::gray(rng.gen_range(0.7..1.0)).into(),
+            rng.gen_range(1.3..1.8),
+            rng.gen_range(0.05..0.5),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        materials::{
+            frosted::RoughDielectric,
+            microfacet::{fresnel_dielectric, local, Ggx},
+            scatter::{tests::integrate, Material, Scatter},
+        },
+        shapes::hit::Hit,
+        utilities::{color::Color, point::Point, ray::Ray},
+    };
+
+    #[test]
+    fn can_match_scatter() {
+        let glass = RoughDielectric::new(Color::rgb(1., 0.9, 0.8).into(), 1.5, 0.3);
+        let material: Material = Box::new(RoughDielectric::new(Color::gray(1.).into(), 1.5, 0.));
+        let ray_in = Ray::new(Point::new(-1., 1., 0.), Point::new(1., -1., 0.), 0.);
+        let (mut reflected, mut refracted) = (0, 0);
+        [true, false].iter().for_each(|&front_face| {
+            let hit = Hit::new(
+                Point::origin(),
+                Point::new(0., 1., 0.),
+                &material,
+                1.,
+                front_face,
+            );
+            for _ in 0..500 {
+                let Some((attenuation, scattered)) = glass.scatter(&ray_in, &hit) else {
+                    continue;
+                };
+                match scattered.direction.y > 0. {
+                    true => reflected += 1,
+                    false => refracted += 1,
+                }
+                let pdf = glass.pdf(&ray_in, &hit, &scattered.direction).unwrap();
+                let eval = glass.eval(&ray_in, &hit, &scattered.direction).unwrap();
+                assert!(pdf > 0.);
+                assert!((eval.b / pdf - attenuation.b).abs() < 1e-6);
+            }
+        });
+        assert!(reflected > 0 && refracted > reflected);
+    }
+
+    #[test]
+    fn can_integrate_density() {
+        let glass = RoughDielectric::new(Color::gray(1.).into(), 1.5, 0.5);
+        let material: Material = Box::new(RoughDielectric::new(Color::gray(1.).into(), 1.5, 0.));
+        let hit = Hit::new(Point::origin(), Point::new(0., 1., 0.), &material, 1., true);
+        let ray_in = Ray::new(Point::new(0., 1., 1.), Point::new(0., -1., -1.), 0.);
+        let integral = integrate(400, |direction| {
+            glass.pdf(&ray_in, &hit, direction).unwrap()
+        });
+        // Samples that reflect below or refract above the surface are lost, so the density
+        // integrates to the share of visible facets that send light to the right side of it
+        let ggx = Ggx::new(0.5);
+        let eta = glass.eta(&hit);
+        let (_, outgoing, _) = local(&ray_in, &hit, &hit.normal);
+        let kept = integrate(400, |normal| {
+            let reflectance = fresnel_dielectric(outgoing.dot(*normal), eta);
+            let reflected = match (-outgoing).reflect(*normal).z > 0. {
+                true => reflectance,
+                false => 0.,
+            };
+            let refracted = match RoughDielectric::refract(&outgoing, normal, eta).z < 0. {
+                true => 1. - reflectance,
+                false => 0.,
+            };
+            ggx.visible_pdf(&outgoing, normal) * (reflected + refracted)
+        });
+        assert!(kept < 1.);
+        assert!((integral - kept).abs() < 1e-2, "{integral} {kept}");
+    }
+
+    #[test]
+    fn can_refract_smooth() {
+        let glass = RoughDielectric::new(Color::gray(1.).into(), 1.5, 0.);
+        let material: Material = Box::new(RoughDielectric::new(Color::gray(1.).into(), 1.5, 0.));
+        let hit = Hit::new(Point::origin(), Point::new(0., 1., 0.), &material, 1., true);
+        let ray_in = Ray::new(Point::new(0., 1., 0.), Point::new(0., -1., 0.), 0.);
+        // Head on, 96% of light goes straight through
+        let through = (0..1000)
+            .filter(|_| {
+                let (_, scattered) = glass.scatter(&ray_in, &hit).unwrap();
+                (scattered.direction - Point::new(0., -1., 0.)).len() < 1e-9
+            })
+            .count();
+        assert!(through > 930, "{through}");
+        assert!(glass.pdf(&ray_in, &hit, &Point::new(0., -1., 0.)).is_none());
+    }
+}
@@ -3,24 +3,95 @@ use rand::Rng;
 use crate::{
     materials::scatter::Scatter,
     shapes::hit::Hit,
-    utilities::{color::Color, ray::Ray},
+    textures::texture::{self, Texture},
+    utilities::{color::Color, ray::Ray, spectrum::Dispersion},
 };
 
 use serde::{Deserialize, Serialize};
 
+/// Scene file representation of a dielectric, which may give a dispersion model instead of a
+/// single refractive index
+#[derive(Deserialize)]
+struct DielectricFile {
+    #[serde(deserialize_with = "texture::deserialize")]
+    albedo: Box<dyn Texture>,
+    refraction_index: Option<f64>,
+    dispersion: Option<Dispersion>,
+    absorption: Option<Color>,
+}
+
 #[derive(Serialize, Deserialize)]
+#[serde(from = "DielectricFile")]
 pub struct Dielectric {
-    albedo: Color,
+    albedo: Box<dyn Texture>,
     /// air = 1.0, glass = 1.3–1.7, diamond = 2.4
     /// Higher indeces mean more refractive effects
     refraction_index: f64,
+    /// How the refractive index changes with wavelength in spectral mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dispersion: Option<Dispersion>,
+    /// Beer-Lambert absorption coefficient of each channel, per unit of distance travelled
+    /// inside, so that thick glass looks darker and more saturated than thin glass. Light keeps
+    /// `exp(-absorption * distance)`, so values above 1 are valid.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    absorption: Option<Color>,
+}
+
+impl From<DielectricFile> for Dielectric {
+    fn from(file: DielectricFile) -> Self {
+        let refraction_index = match (file.refraction_index, file.dispersion) {
+            (Some(refraction_index), _) => refraction_index,
+            (None, Some(dispersion)) => {
+                dispersion.refraction_index(Dispersion::REFERENCE_WAVELENGTH)
+            }
+            (None, None) => panic!("Dielectric needs a refraction_index or a dispersion model"),
+        };
+        Self {
+            albedo: file.albedo,
+            refraction_index,
+            dispersion: file.dispersion,
+            absorption: file.absorption,
+        }
+    }
 }
 
 impl Dielectric {
-    pub fn new(albedo: Color, refraction_index: f64) -> Self {
+    pub fn new(albedo: Box<dyn Texture>, refraction_index: f64) -> Self {
         Self {
             albedo,
             refraction_index,
+            dispersion: None,
+            absorption: None,
+        }
+    }
+
+    /// Bend each wavelength differently, splitting white light into a rainbow in spectral mode;
+    /// `refraction_index` is still used without it
+    #[allow(dead_code)]
+    pub fn with_dispersion(mut self, dispersion: Dispersion) -> Self {
+        self.dispersion = Some(dispersion);
+        self
+    }
+
+    /// Absorb light inside with a Beer-Lambert coefficient per channel and unit of distance;
+    /// a white `albedo` then leaves all of the tint to the thickness
+    #[allow(dead_code)]
+    pub fn with_absorption(mut self, absorption: Color) -> Self {
+        self.absorption = Some(absorption);
+        self
+    }
+
+    /// Fraction of light left after travelling `distance` through the inside
+    ///
+    /// https://en.wikipedia.org/wiki/Beer%E2%80%93Lambert_law
+    pub fn transmittance(&self, distance: f64) -> Color {
+        match self.absorption {
+            Some(absorption) => Color::rgb(
+                (-absorption.r * distance).exp(),
+                (-absorption.g * distance).exp(),
+                (-absorption.b * distance).exp(),
+            ),
+            None => Color::gray(1.),
         }
     }
 
@@ -35,10 +106,20 @@ impl Dielectric {
 #[typetag::serde]
 impl Scatter for Dielectric {
     fn scatter(&self, ray_in: &Ray, hit: &Hit) -> Option<(Color, Ray)> {
-        let refraction_ratio = hit
-            .front_face
-            .then(|| 1. / self.refraction_index)
-            .unwrap_or(self.refraction_index);
+        // Every wavelength takes a different path through dispersive glass, so the ray can
+        // only follow its hero wavelength
+        let (refraction_index, wavelength, weight) =
+            match (self.dispersion, ray_in.wavelength.hero()) {
+                (Some(dispersion), Some(hero)) => {
+                    let (wavelength, weight) = ray_in.wavelength.narrow();
+                    (dispersion.refraction_index(hero), wavelength, weight)
+                }
+                _ => (self.refraction_index, ray_in.wavelength, Color::gray(1.)),
+            };
+        let refraction_ratio = match hit.front_face {
+            true => 1. / refraction_index,
+            false => refraction_index,
+        };
 
         let unit_direction = ray_in.direction.normalized();
         let cos_theta = (-1. * unit_direction).dot(hit.normal).min(1.);
@@ -48,12 +129,23 @@ impl Scatter for Dielectric {
         let cannot_refract = refraction_ratio * sin_theta > 1.;
         let will_reflect = rng.gen::<f64>() < Self::reflectance(cos_theta, refraction_ratio);
 
-        let direction = (cannot_refract || will_reflect)
-            .then(|| unit_direction.reflect(hit.normal))
-            .unwrap_or_else(|| unit_direction.refract(hit.normal, refraction_ratio));
+        let direction = match cannot_refract || will_reflect {
+            true => unit_direction.reflect(hit.normal),
+            false => unit_direction.refract(hit.normal, refraction_ratio),
+        };
 
-        let scattered = Ray::new(hit.point, direction, ray_in.time);
-        Some((self.albedo, scattered))
+        // Hitting the inside of the surface means the ray travelled through the glass to get
+        // here, losing some of its light along the way
+        let absorbed = match hit.front_face {
+            true => Color::gray(1.),
+            false => self.transmittance(hit.time * ray_in.direction.len()),
+        };
+
+        let scattered = Ray::new(hit.point, direction, ray_in.time).with_wavelength(wavelength);
+        Some((
+            self.albedo.value(hit.uv, &hit.local, ray_in.time) * weight * absorbed,
+            scattered,
+        ))
     }
 
     fn emit(&self) -> Color {
@@ -66,8 +158,115 @@ impl Scatter for Dielectric {
     {
         let mut rng = rand::thread_rng();
         Self {
-            albedo: Color::gray(rng.gen_range(0.5..1.0)),
+            albedo: Color::gray(rng.gen_range(0.5..1.0)).into(),
             refraction_index: rng.gen_range(-1.0..2.0),
+            dispersion: None,
+            absorption: None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        materials::{
+            glass::Dielectric,
+            scatter::{Material, Scatter},
+        },
+        shapes::hit::Hit,
+        utilities::{
+            color::Color,
+            point::Point,
+            ray::Ray,
+            spectrum::{response, Dispersion, Wavelength},
+        },
+    };
+
+    const FLINT: Dispersion = Dispersion::Cauchy {
+        a: 1.6,
+        b: 0.02,
+        c: 0.,
+    };
+
+    #[test]
+    fn can_disperse() {
+        let glass = Dielectric::new(Color::gray(1.).into(), 1.5).with_dispersion(FLINT);
+        let material: Material = Box::new(Dielectric::new(Color::gray(1.).into(), 1.5));
+        let hit = Hit::new(Point::origin(), Point::new(0., 1., 0.), &material, 1., true);
+        let direction = Point::new(1., -1., 0.);
+
+        // Blue light bends further towards the normal than red
+        let bend = |wavelength: f64| {
+            let ray_in = Ray::new(Point::new(-1., 1., 0.), direction, 0.)
+                .with_wavelength(Wavelength::Hero(wavelength));
+            loop {
+                let (attenuation, scattered) = glass.scatter(&ray_in, &hit).unwrap();
+                assert_eq!(scattered.wavelength, Wavelength::Single(wavelength));
+                assert_eq!(attenuation, response(wavelength));
+                if scattered.direction.y < 0. {
+                    break scattered.direction.normalized().x;
+                }
+            }
+        };
+        assert!(bend(450.) < bend(650.));
+
+        // Without spectral mode the glass refracts with its single index
+        let ray_in = Ray::new(Point::new(-1., 1., 0.), direction, 0.);
+        let (attenuation, scattered) = glass.scatter(&ray_in, &hit).unwrap();
+        assert_eq!(scattered.wavelength, Wavelength::Rgb);
+        assert_eq!(attenuation, Color::gray(1.));
+    }
+
+    #[test]
+    fn can_deserialize_dispersion() {
+        let yaml = "type: Dielectric\nalbedo: {r: 1.0, g: 1.0, b: 1.0, a: 255}\ndispersion:\n  type: Cauchy\n  a: 1.6\n  b: 0.02\n";
+        let material: Material = serde_yml::from_str(yaml).unwrap();
+        let hit = Hit::new(Point::origin(), Point::new(0., 1., 0.), &material, 1., true);
+        let ray_in = Ray::new(Point::new(0., 1., 0.), Point::new(0., -1., 0.), 0.)
+            .with_wavelength(Wavelength::Hero(500.));
+        let (_, scattered) = material.scatter(&ray_in, &hit).unwrap();
+        assert_eq!(scattered.wavelength, Wavelength::Single(500.));
+
+        let saved = serde_yml::to_string(&material).unwrap();
+        assert!(saved.contains("refraction_index: 1.657"), "{saved}");
+    }
+
+    #[test]
+    fn can_absorb() {
+        let glass =
+            Dielectric::new(Color::gray(1.).into(), 1.5).with_absorption(Color::rgb(0.1, 0.5, 1.));
+        let material: Material = Box::new(Dielectric::new(Color::gray(1.).into(), 1.5));
+        let ray_in = Ray::new(Point::new(0., 2., 0.), Point::new(0., -2., 0.), 0.);
+        let hit = |time: f64, front_face: bool| {
+            Hit::new(
+                Point::origin(),
+                Point::new(0., 1., 0.),
+                &material,
+                time,
+                front_face,
+            )
+        };
+
+        // Entering the glass absorbs nothing
+        assert_eq!(
+            glass.scatter(&ray_in, &hit(1., true)).unwrap().0,
+            Color::gray(1.)
+        );
+
+        // Leaving after 2 units of distance inside, blue is absorbed the most
+        let (attenuation, _) = glass.scatter(&ray_in, &hit(1., false)).unwrap();
+        assert!((attenuation.r - (-0.2f64).exp()).abs() < 1e-12);
+        assert!((attenuation.b - (-2f64).exp()).abs() < 1e-12);
+
+        // Thicker glass is darker
+        let thick = hit(3., false);
+        assert!(glass.scatter(&ray_in, &thick).unwrap().0.g < attenuation.g);
+    }
+
+    #[test]
+    #[should_panic(expected = "Dielectric needs a refraction_index or a dispersion model")]
+    fn can_require_index() {
+        let yaml = "type: Dielectric\nalbedo: {r: 1.0, g: 1.0, b: 1.0, a: 255}\n";
+        let _: Material = serde_yml::from_str(yaml).unwrap();
+    }
+}
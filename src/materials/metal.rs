@@ -1,8 +1,11 @@
+use std::f64::consts::PI;
+
 use rand::Rng;
 
 use crate::{
     materials::scatter::Scatter,
     shapes::hit::Hit,
+    textures::texture::{self, Texture},
     utilities::{color::Color, point::Point, ray::Ray},
 };
 
@@ -10,15 +13,33 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize)]
 pub struct Metal {
-    albedo: Color,
+    #[serde(deserialize_with = "texture::deserialize")]
+    albedo: Box<dyn Texture>,
     /// 0..1 range of matte, higher means less reflective
     matte: f64,
 }
 
 impl Metal {
-    pub fn new(albedo: Color, matte: f64) -> Self {
+    pub fn new(albedo: Box<dyn Texture>, matte: f64) -> Self {
         Self { albedo, matte }
     }
+
+    /// Density of the direction of `reflected + matte * p` for `p` uniform in the unit ball
+    ///
+    /// Every point of the ball centered on `reflected` with radius `matte` is equally likely,
+    /// so the density of a direction is the volume of that ball along it, `∫ t² dt`, divided
+    /// by the ball's volume.
+    fn density(&self, reflected: Point, direction: Point) -> f64 {
+        // Solve |t * direction - reflected| = matte for the chord through the ball
+        let along = direction.dot(reflected);
+        let discriminant = along.powi(2) - (reflected.len().powi(2) - self.matte.powi(2));
+        if discriminant <= 0. {
+            return 0.;
+        }
+        let near = (along - discriminant.sqrt()).max(0.);
+        let far = (along + discriminant.sqrt()).max(0.);
+        (far.powi(3) - near.powi(3)) / (4. * PI * self.matte.powi(3))
+    }
 }
 
 #[typetag::serde]
@@ -31,11 +52,33 @@ impl Scatter for Metal {
         );
 
         match scattered.direction.dot(hit.normal) > 0.0 {
-            true => Some((self.albedo, scattered)),
+            true => Some((
+                self.albedo.value(hit.uv, &hit.local, ray_in.time),
+                scattered,
+            )),
             false => None,
         }
     }
 
+    /// Scattering picks directions in proportion to `density`, weighted by `albedo`, so this is
+    /// `albedo * density` above the surface
+    fn eval(&self, ray_in: &Ray, hit: &Hit, direction: &Point) -> Option<Color> {
+        let pdf = self.pdf(ray_in, hit, direction)?;
+        match direction.dot(hit.normal) > 0. {
+            true => Some(self.albedo.value(hit.uv, &hit.local, ray_in.time) * pdf),
+            false => Some(Color::default()),
+        }
+    }
+
+    /// A perfectly smooth metal only reflects in one direction, like `Mirror`
+    fn pdf(&self, ray_in: &Ray, hit: &Hit, direction: &Point) -> Option<f64> {
+        if self.matte <= 0. {
+            return None;
+        }
+        let reflected = ray_in.direction.reflect(hit.normal).normalized();
+        Some(self.density(reflected, direction.normalized()))
+    }
+
     fn emit(&self) -> Color {
         Color::default()
     }
@@ -46,8 +89,65 @@ impl Scatter for Metal {
     {
         let mut rng = rand::thread_rng();
         Self {
-            albedo: Color::random(),
+            albedo: Color::random().into(),
             matte: rng.gen_range(0.1..1.0),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use crate::{
+        materials::{
+            metal::Metal,
+            scatter::{Material, Scatter},
+        },
+        shapes::hit::Hit,
+        utilities::{color::Color, point::Point, ray::Ray},
+    };
+
+    #[test]
+    fn can_integrate_density() {
+        // Averaging the density over uniformly random directions estimates its integral / 4π
+        let reflected = Point::new(0., 0., 1.);
+        [0.5, 1., 1.5].iter().for_each(|&matte| {
+            let metal = Metal::new(Color::gray(1.).into(), matte);
+            let samples = 100000;
+            let total: f64 = (0..samples)
+                .map(|_| metal.density(reflected, Point::random_in_sphere().normalized()))
+                .sum();
+            let integral = 4. * PI * total / samples as f64;
+            assert!((integral - 1.).abs() < 0.1, "{matte} {integral}");
+        });
+    }
+
+    #[test]
+    fn can_match_scatter() {
+        let metal = Metal::new(Color::rgb(0.9, 0.5, 0.1).into(), 0.2);
+        let material: Material = Box::new(Metal::new(Color::gray(1.).into(), 0.));
+        let hit = Hit::new(Point::origin(), Point::new(0., 1., 0.), &material, 1., true);
+        let ray_in = Ray::new(Point::new(-1., 1., 0.), Point::new(1., -1., 0.), 0.);
+        for _ in 0..100 {
+            let Some((attenuation, scattered)) = metal.scatter(&ray_in, &hit) else {
+                continue;
+            };
+            let pdf = metal.pdf(&ray_in, &hit, &scattered.direction).unwrap();
+            let eval = metal.eval(&ray_in, &hit, &scattered.direction).unwrap();
+            assert!(pdf > 0.);
+            assert!((eval.r / pdf - attenuation.r).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn can_skip_smooth_metal() {
+        let metal = Metal::new(Color::gray(1.).into(), 0.);
+        let material: Material = Box::new(Metal::new(Color::gray(1.).into(), 0.));
+        let hit = Hit::new(Point::origin(), Point::new(0., 1., 0.), &material, 1., true);
+        let ray_in = Ray::new(Point::new(-1., 1., 0.), Point::new(1., -1., 0.), 0.);
+        let direction = Point::new(1., 1., 0.);
+        assert!(metal.pdf(&ray_in, &hit, &direction).is_none());
+        assert!(metal.eval(&ray_in, &hit, &direction).is_none());
+    }
+}
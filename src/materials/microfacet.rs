@@ -0,0 +1,213 @@
+use std::f64::consts::PI;
+
+use rand::Rng;
+
+use crate::{
+    shapes::hit::Hit,
+    utilities::{color::Color, frame::Frame, point::Point, ray::Ray},
+};
+
+/// The GGX (Trowbridge-Reitz) distribution of microfacet normals used by rough materials
+///
+/// Directions are in a local frame where the surface normal is `+z`. Perceptual `roughness`
+/// in 0..1 is squared into the distribution's width, so that roughness changes look even.
+///
+/// https://jcgt.org/published/0003/02/03/paper.pdf
+pub struct Ggx {
+    alpha: f64,
+}
+
+impl Ggx {
+    pub fn new(roughness: f64) -> Self {
+        Self {
+            alpha: roughness.clamp(0., 1.).powi(2).max(1e-4),
+        }
+    }
+
+    /// Whether the surface is smooth enough to treat as a perfect mirror or window
+    pub fn is_smooth(roughness: f64) -> bool {
+        roughness < 1e-3
+    }
+
+    /// Density of microfacet normals facing `normal`, per unit of projected area
+    pub fn distribution(&self, normal: &Point) -> f64 {
+        if normal.z <= 0. {
+            return 0.;
+        }
+        let alpha2 = self.alpha * self.alpha;
+        let denominator = normal.z * normal.z * (alpha2 - 1.) + 1.;
+        alpha2 / (PI * denominator * denominator)
+    }
+
+    /// Smith's auxiliary function for the microfacets hidden from `direction`
+    fn lambda(&self, direction: &Point) -> f64 {
+        let cos2 = direction.z * direction.z;
+        if cos2 <= 0. {
+            return f64::INFINITY;
+        }
+        let tan2 = (1. - cos2).max(0.) / cos2;
+        ((1. + self.alpha * self.alpha * tan2).sqrt() - 1.) / 2.
+    }
+
+    /// Fraction of microfacets visible from `direction`
+    pub fn masking(&self, direction: &Point) -> f64 {
+        1. / (1. + self.lambda(direction))
+    }
+
+    /// Fraction of microfacets visible from both directions, with the height correlated form
+    pub fn shadowing(&self, outgoing: &Point, incoming: &Point) -> f64 {
+        1. / (1. + self.lambda(outgoing) + self.lambda(incoming))
+    }
+
+    /// Pick a microfacet normal among those visible from `outgoing`
+    ///
+    /// https://jcgt.org/published/0007/04/01/paper.pdf
+    pub fn sample_visible(&self, outgoing: &Point) -> Point {
+        let mut rng = rand::thread_rng();
+        // Stretch the view into the space where the distribution is a hemisphere
+        let view =
+            Point::new(self.alpha * outgoing.x, self.alpha * outgoing.y, outgoing.z).normalized();
+        let length2 = view.x * view.x + view.y * view.y;
+        let t_1 = match length2 > 0. {
+            true => Point::new(-view.y, view.x, 0.) / length2.sqrt(),
+            false => Point::new(1., 0., 0.),
+        };
+        let t_2 = view.cross(t_1);
+
+        // Sample the projected disk, squashed where the hemisphere hides it
+        let radius = rng.gen::<f64>().sqrt();
+        let phi = 2. * PI * rng.gen::<f64>();
+        let p_1 = radius * phi.cos();
+        let blend = 0.5 * (1. + view.z);
+        let p_2 = (1. - blend) * (1. - p_1 * p_1).sqrt() + blend * radius * phi.sin();
+        let height = (1. - p_1 * p_1 - p_2 * p_2).max(0.).sqrt();
+        let normal = p_1 * t_1 + p_2 * t_2 + height * view;
+
+        Point::new(
+            self.alpha * normal.x,
+            self.alpha * normal.y,
+            normal.z.max(0.),
+        )
+        .normalized()
+    }
+
+    /// Density of `sample_visible` picking `normal` when seen from `outgoing`
+    pub fn visible_pdf(&self, outgoing: &Point, normal: &Point) -> f64 {
+        if outgoing.z <= 0. {
+            return 0.;
+        }
+        self.masking(outgoing) * outgoing.dot(*normal).max(0.) * self.distribution(normal)
+            / outgoing.z
+    }
+}
+
+/// Directions towards the viewer and `direction` in the frame of the surface normal, along
+/// with the frame to bring scattered directions back
+pub fn local(ray_in: &Ray, hit: &Hit, direction: &Point) -> (Frame, Point, Point) {
+    let frame = Frame::new(Point::origin(), hit.normal);
+    let outgoing = frame.to_local_vector(&-ray_in.direction.normalized());
+    let incoming = frame.to_local_vector(&direction.normalized());
+    (frame, outgoing, incoming)
+}
+
+/// Fraction of light a smooth dielectric reflects at an angle with cosine `cos_i`, where `eta`
+/// is the ratio of the refractive index across the surface to the one the light is in
+///
+/// https://pbr-book.org/3ed-2018/Reflection_Models/Specular_Reflection_and_Transmission#FresnelReflectance
+pub fn fresnel_dielectric(cos_i: f64, eta: f64) -> f64 {
+    let cos_i = cos_i.clamp(0., 1.);
+    let sin2_t = (1. - cos_i * cos_i) / (eta * eta);
+    if sin2_t >= 1. {
+        return 1.;
+    }
+    let cos_t = (1. - sin2_t).sqrt();
+    let parallel = (eta * cos_i - cos_t) / (eta * cos_i + cos_t);
+    let perpendicular = (cos_i - eta * cos_t) / (cos_i + eta * cos_t);
+    (parallel * parallel + perpendicular * perpendicular) / 2.
+}
+
+/// Fraction of light a conductor with complex refractive index `eta + ik` reflects at an angle
+/// with cosine `cos_i`, for each channel
+///
+/// https://seblagarde.wordpress.com/2013/04/29/memo-on-fresnel-equations/
+pub fn fresnel_conductor(cos_i: f64, eta: &Color, k: &Color) -> Color {
+    let channel = |eta: f64, k: f64| {
+        let cos2 = cos_i.clamp(0., 1.).powi(2);
+        let sin2 = 1. - cos2;
+        let t_0 = eta * eta - k * k - sin2;
+        let a2_plus_b2 = (t_0 * t_0 + 4. * eta * eta * k * k).sqrt();
+        let t_1 = a2_plus_b2 + cos2;
+        let a = (0.5 * (a2_plus_b2 + t_0)).max(0.).sqrt();
+        let t_2 = 2. * cos_i * a;
+        let perpendicular = (t_1 - t_2) / (t_1 + t_2);
+        let t_3 = cos2 * a2_plus_b2 + sin2 * sin2;
+        let t_4 = t_2 * sin2;
+        let parallel = perpendicular * (t_3 - t_4) / (t_3 + t_4);
+        (parallel + perpendicular) / 2.
+    };
+    Color::rgb(
+        channel(eta.r, k.r),
+        channel(eta.g, k.g),
+        channel(eta.b, k.b),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use crate::{
+        materials::{
+            microfacet::{fresnel_conductor, fresnel_dielectric, Ggx},
+            scatter::tests::integrate,
+        },
+        utilities::{color::Color, point::Point},
+    };
+
+    #[test]
+    fn can_integrate_distribution() {
+        // Projected onto the surface, the microfacets cover exactly its area. The distribution
+        // only depends on the angle to the normal, so integrate over its cosine.
+        [0.2, 0.5, 1.].iter().for_each(|&roughness| {
+            let ggx = Ggx::new(roughness);
+            let steps = 100000;
+            let total: f64 = (0..steps)
+                .map(|step| {
+                    let cos = (step as f64 + 0.5) / steps as f64;
+                    let normal = Point::new((1. - cos * cos).sqrt(), 0., cos);
+                    ggx.distribution(&normal) * cos
+                })
+                .sum();
+            let integral = 2. * PI * total / steps as f64;
+            assert!((integral - 1.).abs() < 1e-3, "{roughness} {integral}");
+        });
+    }
+
+    #[test]
+    fn can_sample_visible() {
+        let ggx = Ggx::new(0.6);
+        let outgoing = Point::new(0.6, 0., 0.8);
+        // Visible normals face the viewer, and their density integrates to 1
+        (0..1000).for_each(|_| assert!(ggx.sample_visible(&outgoing).dot(outgoing) >= 0.));
+        let integral = integrate(200, |normal| ggx.visible_pdf(&outgoing, normal));
+        assert!((integral - 1.).abs() < 1e-2, "{integral}");
+    }
+
+    #[test]
+    fn can_get_fresnel() {
+        // 4% of light reflects off glass head on, and all of it past the critical angle
+        assert!((fresnel_dielectric(1., 1.5) - 0.04).abs() < 1e-9);
+        assert_eq!(fresnel_dielectric(0.1, 1. / 1.5), 1.);
+        assert!((fresnel_dielectric(0., 1.5) - 1.).abs() < 1e-9);
+
+        // Without absorption a conductor reflects like a dielectric
+        let reflectance = fresnel_conductor(0.7, &Color::gray(1.5), &Color::gray(0.));
+        assert!((reflectance.r - fresnel_dielectric(0.7, 1.5)).abs() < 1e-9);
+        let gold = fresnel_conductor(
+            1.,
+            &Color::rgb(0.143, 0.374, 1.442),
+            &Color::rgb(3.983, 2.385, 1.603),
+        );
+        assert!(gold.r > gold.g && gold.g > gold.b);
+    }
+}
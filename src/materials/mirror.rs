@@ -1,6 +1,7 @@
 use crate::{
     materials::scatter::Scatter,
     shapes::hit::Hit,
+    textures::texture::{self, Texture},
     utilities::{color::Color, ray::Ray},
 };
 
@@ -8,11 +9,13 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize)]
 pub struct Mirror {
-    albedo: Color,
+    #[serde(deserialize_with = "texture::deserialize")]
+    albedo: Box<dyn Texture>,
 }
 
 impl Mirror {
-    pub fn new(albedo: Color) -> Self {
+    #[allow(dead_code)]
+    pub fn new(albedo: Box<dyn Texture>) -> Self {
         Self { albedo }
     }
 }
@@ -24,7 +27,10 @@ impl Scatter for Mirror {
         let scattered = Ray::new(hit.point, reflected, ray_in.time);
 
         match scattered.direction.dot(hit.normal) > 0.0 {
-            true => Some((self.albedo, scattered)),
+            true => Some((
+                self.albedo.value(hit.uv, &hit.local, ray_in.time),
+                scattered,
+            )),
             false => None,
         }
     }
@@ -37,9 +43,8 @@ impl Scatter for Mirror {
     where
         Self: Sized,
     {
-        let mut rng = rand::thread_rng();
         Self {
-            albedo: Color::random(),
+            albedo: Color::random().into(),
         }
     }
 }
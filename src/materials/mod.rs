@@ -1,8 +1,13 @@
+pub mod conductor;
 pub mod diffuse;
+pub mod frosted;
 pub mod glass;
 pub mod light;
 pub mod metal;
+pub mod microfacet;
 pub mod mirror;
 pub mod normal;
+pub mod phase;
+pub mod principled;
 pub mod scatter;
 pub mod transparent;
@@ -15,6 +15,7 @@ pub struct Normal {
 }
 
 impl Normal {
+    #[allow(dead_code)]
     pub fn new(brightness: f64, intensity: f64) -> Self {
         Self {
             brightness,
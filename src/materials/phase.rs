@@ -0,0 +1,177 @@
+use std::f64::consts::PI;
+
+use rand::Rng;
+
+use crate::{
+    materials::scatter::Scatter,
+    shapes::hit::Hit,
+    textures::texture::{self, Texture},
+    utilities::{color::Color, point::Point, ray::Ray},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Scene file representation of a phase function, whose asymmetry is clamped like in `new`
+#[derive(Deserialize)]
+struct PhaseFile {
+    #[serde(deserialize_with = "texture::deserialize")]
+    albedo: Box<dyn Texture>,
+    #[serde(default)]
+    asymmetry: f64,
+}
+
+/// Light scattering off the particles inside a `Volume` or the scene's atmosphere, spread out
+/// with the Henyey-Greenstein phase function
+///
+/// An `asymmetry` of 0 scatters evenly in every direction, positive values scatter forwards
+/// like haze, and negative values back towards the light.
+///
+/// https://pbr-book.org/3ed-2018/Volume_Scattering/Phase_Functions#TheHenyeyndashGreensteinPhaseFunction
+#[derive(Serialize, Deserialize)]
+#[serde(from = "PhaseFile")]
+pub struct Phase {
+    albedo: Box<dyn Texture>,
+    asymmetry: f64,
+}
+
+impl From<PhaseFile> for Phase {
+    fn from(file: PhaseFile) -> Self {
+        Phase::new(file.albedo, file.asymmetry)
+    }
+}
+
+impl Phase {
+    /// An `asymmetry` of ±1 or more would send every ray straight on or back, which the phase
+    /// function cannot represent, so it is limited to ±0.99
+    pub fn new(albedo: Box<dyn Texture>, asymmetry: f64) -> Self {
+        Self {
+            albedo,
+            asymmetry: asymmetry.clamp(-0.99, 0.99),
+        }
+    }
+
+    /// Density of turning by an angle with cosine `cos_theta` from the direction of travel
+    fn density(&self, cos_theta: f64) -> f64 {
+        let g = self.asymmetry;
+        let denominator = 1. + g * g - 2. * g * cos_theta;
+        (1. - g * g) / (4. * PI * denominator * denominator.sqrt())
+    }
+
+    fn cos_theta(ray_in: &Ray, direction: &Point) -> f64 {
+        ray_in
+            .direction
+            .normalized()
+            .dot(direction.normalized())
+            .clamp(-1., 1.)
+    }
+}
+
+#[typetag::serde]
+impl Scatter for Phase {
+    fn scatter(&self, ray_in: &Ray, hit: &Hit) -> Option<(Color, Ray)> {
+        let mut rng = rand::thread_rng();
+        let g = self.asymmetry;
+        let sample = rng.gen::<f64>();
+        let cos_theta = match g.abs() < 1e-3 {
+            true => 1. - 2. * sample,
+            false => {
+                let term = (1. - g * g) / (1. - g + 2. * g * sample);
+                ((1. + g * g - term * term) / (2. * g)).clamp(-1., 1.)
+            }
+        };
+        let sin_theta = (1. - cos_theta * cos_theta).max(0.).sqrt();
+        let phi = 2. * PI * rng.gen::<f64>();
+
+        let forward = ray_in.direction.normalized();
+        let (u, v) = forward.orthonormal_basis();
+        let direction = sin_theta * phi.cos() * u + sin_theta * phi.sin() * v + cos_theta * forward;
+        Some((
+            self.albedo.value(hit.uv, &hit.local, ray_in.time),
+            Ray::new(hit.point, direction, ray_in.time),
+        ))
+    }
+
+    /// `scatter` follows the phase function exactly, so this is `albedo * phase`
+    fn eval(&self, ray_in: &Ray, hit: &Hit, direction: &Point) -> Option<Color> {
+        let density = self.density(Self::cos_theta(ray_in, direction));
+        Some(self.albedo.value(hit.uv, &hit.local, ray_in.time) * density)
+    }
+
+    fn pdf(&self, ray_in: &Ray, _: &Hit, direction: &Point) -> Option<f64> {
+        Some(self.density(Self::cos_theta(ray_in, direction)))
+    }
+
+    fn emit(&self) -> Color {
+        Color::default()
+    }
+
+    fn random() -> Self
+    where
+        Self: Sized,
+    {
+        let mut rng = rand::thread_rng();
+        Self::new(Color::random().into(), rng.gen_range(-0.5..0.5))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use crate::{
+        materials::{
+            phase::Phase,
+            scatter::{Material, Scatter},
+        },
+        shapes::hit::Hit,
+        utilities::{color::Color, point::Point, ray::Ray},
+    };
+
+    #[test]
+    fn can_integrate_density() {
+        [-0.6, 0., 0.3, 0.9].iter().for_each(|&asymmetry| {
+            let phase = Phase::new(Color::gray(1.).into(), asymmetry);
+            let samples = 100000;
+            let total: f64 = (0..samples)
+                .map(|_| phase.density(Point::random_in_sphere().normalized().z))
+                .sum();
+            let integral = 4. * PI * total / samples as f64;
+            assert!((integral - 1.).abs() < 0.1, "{asymmetry} {integral}");
+        });
+    }
+
+    #[test]
+    fn can_clamp_asymmetry() {
+        ["1.0", "-3.0"].iter().for_each(|asymmetry| {
+            let yaml =
+                format!("albedo: {{r: 1.0, g: 1.0, b: 1.0, a: 255}}\nasymmetry: {asymmetry}\n");
+            let phase: Phase = serde_yml::from_str(&yaml).unwrap();
+            assert!(phase.asymmetry.abs() < 1.);
+            [-1., 0., 1.].iter().for_each(|&cos_theta| {
+                let density = phase.density(cos_theta);
+                assert!(
+                    density.is_finite() && density >= 0.,
+                    "{asymmetry} {density}"
+                );
+            });
+        });
+    }
+
+    #[test]
+    fn can_match_scatter() {
+        let phase = Phase::new(Color::rgb(0.9, 0.5, 0.1).into(), 0.7);
+        let material: Material = Box::new(Phase::new(Color::gray(1.).into(), 0.));
+        let hit = Hit::new(Point::origin(), Point::new(0., 1., 0.), &material, 1., true);
+        let ray_in = Ray::new(Point::new(-1., 0., 0.), Point::new(1., 0., 0.), 0.);
+        let mut forward = 0;
+        for _ in 0..1000 {
+            let (attenuation, scattered) = phase.scatter(&ray_in, &hit).unwrap();
+            let pdf = phase.pdf(&ray_in, &hit, &scattered.direction).unwrap();
+            let eval = phase.eval(&ray_in, &hit, &scattered.direction).unwrap();
+            assert!((eval.r / pdf - attenuation.r).abs() < 1e-9);
+            forward += usize::from(scattered.direction.x > 0.);
+        }
+        // Strongly forward scattering keeps most rays heading the same way
+        assert!(forward > 800, "{forward}");
+    }
+}
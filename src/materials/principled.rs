@@ -0,0 +1,469 @@
+use std::f64::consts::PI;
+
+use rand::Rng;
+
+use crate::{
+    materials::{
+        frosted::RoughDielectric,
+        microfacet::{local, Ggx},
+        scatter::Scatter,
+    },
+    shapes::hit::Hit,
+    textures::texture::{self, Texture},
+    utilities::{color::Color, point::Point, ray::Ray},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// One material covering plastics, metals, glass, fabric and lacquered surfaces, with the
+/// parameters of the Disney principled BRDF
+///
+/// The base is a blend of a diffuse layer under a dielectric specular layer, a metal, and
+/// a rough glass, picked by `metallic` and `transmission`. A clear coat sits on top of all
+/// of them and takes its reflected light out of what reaches the base.
+///
+/// https://media.disneyanimation.com/uploads/production/publication_asset/48/asset/s2012_pbs_disney_brdf_notes_v3.pdf
+#[derive(Serialize, Deserialize)]
+pub struct Principled {
+    #[serde(deserialize_with = "texture::deserialize")]
+    base_color: Box<dyn Texture>,
+    /// 0 is a dielectric, 1 is a metal tinted by `base_color`
+    #[serde(default)]
+    metallic: f64,
+    /// Spread of the specular and transmission lobes, from 0 (smooth) to 1
+    #[serde(default = "Principled::default_roughness")]
+    roughness: f64,
+    /// Head on reflectance of the dielectric base, where 0.5 is 4% like most plastics
+    #[serde(default = "Principled::default_specular")]
+    specular: f64,
+    /// How much the dielectric reflections take on the hue of `base_color`
+    #[serde(default)]
+    specular_tint: f64,
+    /// Extra reflection at grazing angles, for cloth
+    #[serde(default)]
+    sheen: f64,
+    /// How much the sheen takes on the hue of `base_color`
+    #[serde(default = "Principled::default_sheen_tint")]
+    sheen_tint: f64,
+    /// Strength of a clear lacquer layer on top
+    #[serde(default)]
+    clearcoat: f64,
+    /// Smoothness of the clear coat, from 0 (satin) to 1 (gloss)
+    #[serde(default = "Principled::default_clearcoat_gloss")]
+    clearcoat_gloss: f64,
+    /// Fraction of the dielectric base that is glass rather than opaque
+    #[serde(default)]
+    transmission: f64,
+    /// Refractive index of the transmitting part
+    #[serde(default = "Principled::default_ior")]
+    ior: f64,
+}
+
+/// Index of each lobe in `Principled::lobes`
+#[derive(Clone, Copy)]
+enum Lobe {
+    Diffuse,
+    Specular,
+    Clearcoat,
+    Transmission,
+}
+
+impl Principled {
+    fn default_roughness() -> f64 {
+        0.5
+    }
+
+    fn default_specular() -> f64 {
+        0.5
+    }
+
+    fn default_sheen_tint() -> f64 {
+        0.5
+    }
+
+    fn default_clearcoat_gloss() -> f64 {
+        1.
+    }
+
+    fn default_ior() -> f64 {
+        1.5
+    }
+
+    pub fn new(base_color: Box<dyn Texture>, metallic: f64, roughness: f64) -> Self {
+        Self {
+            base_color,
+            metallic,
+            roughness,
+            specular: Self::default_specular(),
+            specular_tint: 0.,
+            sheen: 0.,
+            sheen_tint: Self::default_sheen_tint(),
+            clearcoat: 0.,
+            clearcoat_gloss: Self::default_clearcoat_gloss(),
+            transmission: 0.,
+            ior: Self::default_ior(),
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn with_specular(mut self, specular: f64, specular_tint: f64) -> Self {
+        self.specular = specular;
+        self.specular_tint = specular_tint;
+        self
+    }
+
+    #[allow(dead_code)]
+    pub fn with_sheen(mut self, sheen: f64, sheen_tint: f64) -> Self {
+        self.sheen = sheen;
+        self.sheen_tint = sheen_tint;
+        self
+    }
+
+    pub fn with_clearcoat(mut self, clearcoat: f64, clearcoat_gloss: f64) -> Self {
+        self.clearcoat = clearcoat;
+        self.clearcoat_gloss = clearcoat_gloss;
+        self
+    }
+
+    #[allow(dead_code)]
+    pub fn with_transmission(mut self, transmission: f64, ior: f64) -> Self {
+        self.transmission = transmission;
+        self.ior = ior;
+        self
+    }
+
+    /// Strength of the clear coat, which Disney scales down to a quarter
+    fn clearcoat_weight(&self) -> f64 {
+        0.25 * self.clearcoat
+    }
+
+    /// Chance of sampling each lobe, in the order of `Lobe`
+    fn lobes(&self) -> [f64; 4] {
+        let dielectric = 1. - self.metallic;
+        let weights = [
+            dielectric * (1. - self.transmission),
+            1. - dielectric * self.transmission,
+            self.clearcoat_weight(),
+            dielectric * self.transmission,
+        ];
+        let total: f64 = weights.iter().sum();
+        weights.map(|weight| weight / total)
+    }
+
+    fn specular_ggx(&self) -> Ggx {
+        Ggx::new(self.roughness)
+    }
+
+    fn clearcoat_ggx(&self) -> Ggx {
+        let alpha = 0.1 + (0.001 - 0.1) * self.clearcoat_gloss;
+        Ggx::new(alpha.sqrt())
+    }
+
+    fn eta(&self, hit: &Hit) -> f64 {
+        match hit.front_face {
+            true => self.ior,
+            false => 1. / self.ior,
+        }
+    }
+
+    /// Sum of every lobe between directions in the frame of the surface normal, including
+    /// the cosine term
+    fn eval_local(&self, base: Color, eta: f64, outgoing: &Point, incoming: &Point) -> Color {
+        if outgoing.z <= 0. {
+            return Color::default();
+        }
+        let dielectric = 1. - self.metallic;
+        let opaque = dielectric * (1. - self.transmission);
+        let ggx = self.specular_ggx();
+
+        let mut value = base
+            * (dielectric
+                * self.transmission
+                * RoughDielectric::eval_local(&ggx, eta, outgoing, incoming));
+        if incoming.z > 0. {
+            let half = (*outgoing + *incoming).normalized();
+            let cos_d = incoming.dot(half);
+
+            let dielectric_specular =
+                mix(Color::gray(1.), tint(base), self.specular_tint) * (0.08 * self.specular);
+            let specular_color = mix(dielectric_specular, base, self.metallic);
+
+            // Diffuse with Disney's retro-reflection at grazing angles on rough surfaces, lit
+            // by what the specular layer above it lets through
+            let grazing = 0.5 + 2. * self.roughness * cos_d * cos_d;
+            let diffuse = (1. + (grazing - 1.) * schlick_weight(outgoing.z))
+                * (1. + (grazing - 1.) * schlick_weight(incoming.z))
+                / PI;
+            let sheen = mix(Color::gray(1.), tint(base), self.sheen_tint)
+                * (self.sheen * schlick_weight(cos_d));
+            let layer = 1. - schlick(dielectric_specular, outgoing.z).luminance();
+            value = value + (base * diffuse + sheen) * (opaque * layer * incoming.z);
+
+            let facets =
+                ggx.distribution(&half) * ggx.shadowing(outgoing, incoming) / (4. * outgoing.z);
+            value = value
+                + schlick(specular_color, cos_d) * (facets * (1. - dielectric * self.transmission));
+        }
+
+        // The clear coat reflects some light before it reaches the base
+        let coat = self.clearcoat_weight();
+        value = value * (1. - coat * schlick(Color::gray(0.04), outgoing.z).r);
+        if coat > 0. && incoming.z > 0. {
+            let ggx = self.clearcoat_ggx();
+            let half = (*outgoing + *incoming).normalized();
+            let facets =
+                ggx.distribution(&half) * ggx.shadowing(outgoing, incoming) / (4. * outgoing.z);
+            let reflectance = schlick(Color::gray(0.04), incoming.dot(half)).r;
+            value = value + Color::gray(coat * reflectance * facets);
+        }
+        value
+    }
+
+    /// Density of `scatter` picking `incoming`, combining the density of each lobe
+    fn pdf_local(&self, eta: f64, outgoing: &Point, incoming: &Point) -> f64 {
+        if outgoing.z <= 0. {
+            return 0.;
+        }
+        let lobes = self.lobes();
+        let ggx = self.specular_ggx();
+        let mut pdf = lobes[Lobe::Transmission as usize]
+            * RoughDielectric::pdf_local(&ggx, eta, outgoing, incoming);
+        if incoming.z > 0. {
+            pdf += lobes[Lobe::Diffuse as usize] * incoming.z / PI;
+            let half = (*outgoing + *incoming).normalized();
+            let cos = outgoing.dot(half);
+            if cos > 0. {
+                pdf +=
+                    lobes[Lobe::Specular as usize] * ggx.visible_pdf(outgoing, &half) / (4. * cos);
+                pdf += lobes[Lobe::Clearcoat as usize]
+                    * self.clearcoat_ggx().visible_pdf(outgoing, &half)
+                    / (4. * cos);
+            }
+        }
+        pdf
+    }
+}
+
+/// `(1 - cos)^5`, the falloff of Schlick's Fresnel approximation
+fn schlick_weight(cos: f64) -> f64 {
+    (1. - cos).clamp(0., 1.).powi(5)
+}
+
+/// Schlick's approximation for reflectance starting from `f_0` head on
+/// https://en.wikipedia.org/wiki/Schlick's_approximation
+fn schlick(f_0: Color, cos: f64) -> Color {
+    mix(f_0, Color::gray(1.), schlick_weight(cos))
+}
+
+fn mix(from: Color, to: Color, amount: f64) -> Color {
+    from * (1. - amount) + to * amount
+}
+
+/// Hue and saturation of `color` at a luminance of 1
+fn tint(color: Color) -> Color {
+    let luminance = color.luminance();
+    match luminance > 0. {
+        true => color * (1. / luminance),
+        false => Color::gray(1.),
+    }
+}
+
+#[typetag::serde]
+impl Scatter for Principled {
+    fn scatter(&self, ray_in: &Ray, hit: &Hit) -> Option<(Color, Ray)> {
+        let (frame, outgoing, _) = local(ray_in, hit, &hit.normal);
+        if outgoing.z <= 0. {
+            return None;
+        }
+        let eta = self.eta(hit);
+        let mut rng = rand::thread_rng();
+
+        // Pick a lobe to sample, then weigh the direction against every lobe
+        let mut choice = rng.gen::<f64>();
+        let lobe = [
+            Lobe::Diffuse,
+            Lobe::Specular,
+            Lobe::Clearcoat,
+            Lobe::Transmission,
+        ]
+        .into_iter()
+        .zip(self.lobes())
+        .find(|(_, chance)| {
+            choice -= chance;
+            choice < 0.
+        })
+        .map_or(Lobe::Specular, |(lobe, _)| lobe);
+        // A lobe's density only covers the side of the surface it sends light to, so samples
+        // that end up on the other side are lost rather than weighed against the other lobes
+        let incoming = match lobe {
+            Lobe::Diffuse => {
+                let radius = rng.gen::<f64>();
+                let phi = 2. * PI * rng.gen::<f64>();
+                let (x, y) = (radius.sqrt() * phi.cos(), radius.sqrt() * phi.sin());
+                Some(Point::new(x, y, (1. - radius).sqrt()))
+            }
+            Lobe::Specular => {
+                let normal = self.specular_ggx().sample_visible(&outgoing);
+                Some((-outgoing).reflect(normal)).filter(|incoming| incoming.z > 0.)
+            }
+            Lobe::Clearcoat => {
+                let normal = self.clearcoat_ggx().sample_visible(&outgoing);
+                Some((-outgoing).reflect(normal)).filter(|incoming| incoming.z > 0.)
+            }
+            Lobe::Transmission => {
+                let normal = self.specular_ggx().sample_visible(&outgoing);
+                let incoming = RoughDielectric::pick(&outgoing, &normal, eta);
+                ((incoming.z > 0.) == (incoming.dot(normal) > 0.)).then_some(incoming)
+            }
+        }?
+        .normalized();
+
+        let pdf = self.pdf_local(eta, &outgoing, &incoming);
+        if pdf <= 0. {
+            return None;
+        }
+        let base = self.base_color.value(hit.uv, &hit.local, ray_in.time);
+        Some((
+            self.eval_local(base, eta, &outgoing, &incoming) * (1. / pdf),
+            Ray::new(hit.point, frame.to_world_vector(&incoming), ray_in.time),
+        ))
+    }
+
+    fn eval(&self, ray_in: &Ray, hit: &Hit, direction: &Point) -> Option<Color> {
+        let (_, outgoing, incoming) = local(ray_in, hit, direction);
+        let base = self.base_color.value(hit.uv, &hit.local, ray_in.time);
+        Some(self.eval_local(base, self.eta(hit), &outgoing, &incoming))
+    }
+
+    fn pdf(&self, ray_in: &Ray, hit: &Hit, direction: &Point) -> Option<f64> {
+        let (_, outgoing, incoming) = local(ray_in, hit, direction);
+        Some(self.pdf_local(self.eta(hit), &outgoing, &incoming))
+    }
+
+    fn emit(&self) -> Color {
+        Color::default()
+    }
+
+    fn random() -> Self
+    where
+        Self: Sized,
+    {
+        let mut rng = rand::thread_rng();
+        Self::new(
+            Color::random().into(),
+            f64::from(rng.gen_bool(0.3)),
+            rng.gen_range(0.05..0.8),
+        )
+        .with_clearcoat(rng.gen_range(0.0..1.0), rng.gen_range(0.0..1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        materials::{
+            frosted::RoughDielectric,
+            microfacet::{local, Ggx},
+            principled::Principled,
+            scatter::{tests::integrate, Material, Scatter},
+        },
+        shapes::hit::Hit,
+        utilities::{color::Color, point::Point, ray::Ray},
+    };
+
+    fn variants() -> Vec<Principled> {
+        let white = || Color::gray(1.).into();
+        vec![
+            Principled::new(white(), 0., 0.5),
+            Principled::new(white(), 1., 0.3),
+            Principled::new(white(), 0., 0.2).with_clearcoat(1., 0.8),
+            Principled::new(white(), 0., 0.8).with_sheen(1., 0.),
+            Principled::new(white(), 0., 0.3).with_transmission(1., 1.5),
+            Principled::new(white(), 0.4, 0.4)
+                .with_transmission(0.5, 1.3)
+                .with_clearcoat(0.5, 0.5),
+        ]
+    }
+
+    #[test]
+    fn can_integrate_density() {
+        let material: Material = Box::new(Principled::new(Color::gray(1.).into(), 0., 0.5));
+        let hit = Hit::new(Point::origin(), Point::new(0., 1., 0.), &material, 1., true);
+        let ray_in = Ray::new(Point::new(0., 1., 1.), Point::new(0., -1., -1.), 0.);
+        let (_, outgoing, _) = local(&ray_in, &hit, &hit.normal);
+        // Share of the samples from visible facets of `ggx` that reflect above the surface
+        let reflected = |ggx: Ggx| {
+            integrate(400, |normal| match (-outgoing).reflect(*normal).z > 0. {
+                true => ggx.visible_pdf(&outgoing, normal),
+                false => 0.,
+            })
+        };
+        variants().iter().for_each(|principled| {
+            let integral = integrate(400, |direction| {
+                principled.pdf(&ray_in, &hit, direction).unwrap()
+            });
+            // Each lobe loses the samples it sends to the wrong side of the surface, and the
+            // transmission lobe's own density is checked against its losses with frosted glass
+            let [diffuse, specular, clearcoat, transmission] = principled.lobes();
+            let eta = principled.eta(&hit);
+            let transmitted = integrate(400, |direction| {
+                RoughDielectric::pdf_local(&principled.specular_ggx(), eta, &outgoing, direction)
+            });
+            let kept = diffuse
+                + specular * reflected(principled.specular_ggx())
+                + clearcoat * reflected(principled.clearcoat_ggx())
+                + transmission * transmitted;
+            assert!((integral - kept).abs() < 1e-2, "{integral} {kept}");
+        });
+    }
+
+    #[test]
+    fn can_conserve_energy() {
+        // A white material lit from every direction sends back at most the light it receives,
+        // apart from the sliver that Disney's sheen adds on top
+        let material: Material = Box::new(Principled::new(Color::gray(1.).into(), 0., 0.5));
+        let hit = Hit::new(Point::origin(), Point::new(0., 1., 0.), &material, 1., true);
+        let ray_in = Ray::new(Point::new(0., 1., 1.), Point::new(0., -1., -1.), 0.);
+        variants().iter().for_each(|principled| {
+            let albedo = integrate(400, |direction| {
+                principled
+                    .eval(&ray_in, &hit, direction)
+                    .unwrap()
+                    .luminance()
+            });
+            assert!((0.85..1.02).contains(&albedo), "{albedo}");
+        });
+    }
+
+    #[test]
+    fn can_tint_metal() {
+        let gold = Principled::new(Color::rgb(1., 0.7, 0.3).into(), 1., 0.2);
+        let material: Material = Box::new(Principled::new(Color::gray(1.).into(), 0., 0.5));
+        let hit = Hit::new(Point::origin(), Point::new(0., 1., 0.), &material, 1., true);
+        let ray_in = Ray::new(Point::new(0., 1., 1.), Point::new(0., -1., -1.), 0.);
+        let (attenuation, scattered) = gold.scatter(&ray_in, &hit).unwrap();
+        assert!(scattered.direction.y > 0.);
+        assert!(attenuation.r > attenuation.g && attenuation.g > attenuation.b);
+        let eval = gold.eval(&ray_in, &hit, &scattered.direction).unwrap();
+        let pdf = gold.pdf(&ray_in, &hit, &scattered.direction).unwrap();
+        assert!((eval.b / pdf - attenuation.b).abs() < 1e-9);
+    }
+
+    #[test]
+    fn can_deserialize() {
+        let yaml =
+            "type: Principled\nbase_color: {r: 0.8, g: 0.1, b: 0.1, a: 255}\nclearcoat: 1.0\n";
+        let material: Material = serde_yml::from_str(yaml).unwrap();
+        let hit = Hit::new(Point::origin(), Point::new(0., 1., 0.), &material, 1., true);
+        let ray_in = Ray::new(Point::new(0., 1., 1.), Point::new(0., -1., -1.), 0.);
+        let diffuse = material
+            .eval(&ray_in, &hit, &Point::new(1., 1., 0.))
+            .unwrap();
+        assert!(diffuse.r > diffuse.g);
+        // The clear coat's highlight is white
+        let mirrored = material
+            .eval(&ray_in, &hit, &Point::new(0., 1., -1.))
+            .unwrap();
+        assert!(mirrored.g > 10. * diffuse.g);
+    }
+}
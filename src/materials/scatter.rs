@@ -1,6 +1,6 @@
 use crate::{
     shapes::hit::Hit,
-    utilities::{color::Color, ray::Ray},
+    utilities::{color::Color, point::Point, ray::Ray},
 };
 
 pub type Material = Box<dyn Scatter>;
@@ -9,7 +9,46 @@ pub type Material = Box<dyn Scatter>;
 pub trait Scatter: Send + Sync {
     fn scatter(&self, ray_in: &Ray, hit: &Hit) -> Option<(Color, Ray)>;
     fn emit(&self) -> Color;
+    /// Fraction of light arriving from `direction` that `scatter` sends back along `ray_in`,
+    /// including the cosine term, or `None` for materials that only scatter in the directions
+    /// they pick (like `Mirror` and `Dielectric`), which cannot be lit by sampling lights.
+    /// Whether it is `None` must not depend on `direction`.
+    fn eval(&self, _ray_in: &Ray, _hit: &Hit, _direction: &Point) -> Option<Color> {
+        None
+    }
+    /// Solid angle density of `scatter` picking `direction`, or `None` wherever `eval` is
+    fn pdf(&self, _ray_in: &Ray, _hit: &Hit, _direction: &Point) -> Option<f64> {
+        None
+    }
     fn random() -> Self
     where
         Self: Sized;
 }
+
+/// Helpers shared by the tests of every material
+#[cfg(test)]
+pub mod tests {
+    use std::f64::consts::PI;
+
+    use crate::utilities::point::Point;
+
+    /// Integral of `f` over every direction, by the midpoint rule on a grid of `steps` angles
+    /// down from `+z` and twice as many around it, which unlike random directions gives the
+    /// same answer on every run, and stays fine enough near `+z` for sharp microfacet peaks
+    pub fn integrate(steps: usize, f: impl Fn(&Point) -> f64) -> f64 {
+        let step = PI / steps as f64;
+        (0..steps)
+            .map(|band| {
+                let theta = (band as f64 + 0.5) * step;
+                let ring: f64 = (0..2 * steps)
+                    .map(|wedge| {
+                        let phi = (wedge as f64 + 0.5) * step;
+                        let (x, y) = (theta.sin() * phi.cos(), theta.sin() * phi.sin());
+                        f(&Point::new(x, y, theta.cos()))
+                    })
+                    .sum();
+                ring * theta.sin() * step * step
+            })
+            .sum()
+    }
+}
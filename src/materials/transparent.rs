@@ -3,6 +3,7 @@ use rand::Rng;
 use crate::{
     materials::scatter::Scatter,
     shapes::hit::Hit,
+    textures::texture::{self, Texture},
     utilities::{color::Color, ray::Ray},
 };
 
@@ -10,12 +11,14 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize)]
 pub struct Filter {
-    albedo: Color,
+    #[serde(deserialize_with = "texture::deserialize")]
+    albedo: Box<dyn Texture>,
     opacity: f64,
 }
 
 impl Filter {
-    pub fn new(albedo: Color, opacity: f64) -> Self {
+    #[allow(dead_code)]
+    pub fn new(albedo: Box<dyn Texture>, opacity: f64) -> Self {
         Self { albedo, opacity }
     }
 }
@@ -24,7 +27,7 @@ impl Filter {
 impl Scatter for Filter {
     fn scatter(&self, ray_in: &Ray, hit: &Hit) -> Option<(Color, Ray)> {
         let ray_out = Ray::new(hit.point, ray_in.direction, ray_in.time);
-        let color = self.opacity * self.albedo;
+        let color = self.opacity * self.albedo.value(hit.uv, &hit.local, ray_in.time);
         Some((color, ray_out))
     }
 
@@ -38,7 +41,7 @@ impl Scatter for Filter {
     {
         let mut rng = rand::thread_rng();
         Self {
-            albedo: Color::default(),
+            albedo: Color::default().into(),
             opacity: rng.gen_range(0.1..2.0),
         }
     }
@@ -0,0 +1,169 @@
+use crate::utilities::{point::Point, ray::Ray};
+
+/// An axis-aligned bounding box
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl Aabb {
+    /// Create a box from any two opposite corners
+    pub fn new(a: Point, b: Point) -> Self {
+        Self {
+            min: a.min(b),
+            max: a.max(b),
+        }
+    }
+
+    /// A box that contains nothing; the identity for `union`
+    pub fn empty() -> Self {
+        Self {
+            min: Point::new(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+            max: Point::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+        }
+    }
+
+    /// Smallest box containing both boxes
+    pub fn union(&self, rhs: &Aabb) -> Aabb {
+        Aabb {
+            min: self.min.min(rhs.min),
+            max: self.max.max(rhs.max),
+        }
+    }
+
+    /// Box covering the space both boxes share, which is empty if they do not overlap
+    pub fn intersection(&self, rhs: &Aabb) -> Aabb {
+        Aabb {
+            min: self.min.max(rhs.min),
+            max: self.max.min(rhs.max),
+        }
+    }
+
+    /// Smallest box containing this box and a point
+    pub fn grow(&self, point: Point) -> Aabb {
+        Aabb {
+            min: self.min.min(point),
+            max: self.max.max(point),
+        }
+    }
+
+    /// Box around a disk of `radius` at `center` facing `normal`, which is thinner along the
+    /// axes the normal leans towards
+    pub fn around_disk(center: Point, normal: Point, radius: f64) -> Aabb {
+        let normal = normal.normalized();
+        let extent = |component: f64| radius * (1. - component.powi(2)).max(0.).sqrt();
+        let extent = Point::new(extent(normal.x), extent(normal.y), extent(normal.z));
+        Aabb::new(center - extent, center + extent)
+    }
+
+    /// Widen the box by `padding` in every direction
+    pub fn pad(&self, padding: f64) -> Aabb {
+        let padding = Point::new(padding, padding, padding);
+        Aabb {
+            min: self.min - padding,
+            max: self.max + padding,
+        }
+    }
+
+    pub fn centroid(&self) -> Point {
+        (self.min + self.max) / 2.
+    }
+
+    /// Total area of the six faces, used by the surface area heuristic
+    pub fn surface_area(&self) -> f64 {
+        let extent = self.max - self.min;
+        if extent.x < 0. || extent.y < 0. || extent.z < 0. {
+            return 0.;
+        }
+        2. * (extent.x * extent.y + extent.y * extent.z + extent.z * extent.x)
+    }
+
+    /// Index of the axis along which the box is longest
+    pub fn longest_axis(&self) -> usize {
+        let extent = self.max - self.min;
+        if extent.x > extent.y && extent.x > extent.z {
+            0
+        } else if extent.y > extent.z {
+            1
+        } else {
+            2
+        }
+    }
+
+    /// Slab test, given the reciprocal of the ray direction
+    ///
+    /// https://tavianator.com/2011/ray_box.html
+    pub fn hit(&self, ray: &Ray, inverse_direction: Point, time_min: f64, time_max: f64) -> bool {
+        let mut time_min = time_min;
+        let mut time_max = time_max;
+        for axis in 0..3 {
+            let t_0 = (self.min.axis(axis) - ray.origin.axis(axis)) * inverse_direction.axis(axis);
+            let t_1 = (self.max.axis(axis) - ray.origin.axis(axis)) * inverse_direction.axis(axis);
+            time_min = time_min.max(t_0.min(t_1));
+            time_max = time_max.min(t_0.max(t_1));
+            if time_max < time_min {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        shapes::aabb::Aabb,
+        utilities::{point::Point, ray::Ray},
+    };
+
+    fn inverse(direction: Point) -> Point {
+        Point::new(1. / direction.x, 1. / direction.y, 1. / direction.z)
+    }
+
+    #[test]
+    fn can_create() {
+        let b = Aabb::new(Point::new(1., -1., 2.), Point::new(-1., 1., -2.));
+        assert_eq!(b.min, Point::new(-1., -1., -2.));
+        assert_eq!(b.max, Point::new(1., 1., 2.));
+    }
+
+    #[test]
+    fn can_union() {
+        let a = Aabb::new(Point::origin(), Point::new(1., 1., 1.));
+        let b = Aabb::new(Point::new(2., -1., 0.), Point::new(3., 0., 1.));
+        let c = a.union(&b);
+        assert_eq!(c.min, Point::new(0., -1., 0.));
+        assert_eq!(c.max, Point::new(3., 1., 1.));
+        assert_eq!(Aabb::empty().union(&a), a);
+    }
+
+    #[test]
+    fn can_get_surface_area() {
+        let b = Aabb::new(Point::origin(), Point::new(1., 2., 3.));
+        assert_eq!(b.surface_area(), 22.);
+        assert_eq!(Aabb::empty().surface_area(), 0.);
+    }
+
+    #[test]
+    fn can_get_longest_axis() {
+        let b = Aabb::new(Point::origin(), Point::new(1., 5., 3.));
+        assert_eq!(b.longest_axis(), 1);
+    }
+
+    #[test]
+    fn can_hit() {
+        let b = Aabb::new(Point::new(-1., -1., -6.), Point::new(1., 1., -4.));
+        let ray = Ray::new(Point::origin(), Point::new(0., 0., -1.), 0.);
+        assert!(b.hit(&ray, inverse(ray.direction), 0., f64::INFINITY));
+    }
+
+    #[test]
+    fn can_miss() {
+        let b = Aabb::new(Point::new(-1., -1., -6.), Point::new(1., 1., -4.));
+        let ray = Ray::new(Point::origin(), Point::new(0., 1., -1.), 0.);
+        assert!(!b.hit(&ray, inverse(ray.direction), 0., f64::INFINITY));
+        let behind = Ray::new(Point::origin(), Point::new(0., 0., 1.), 0.);
+        assert!(!b.hit(&behind, inverse(behind.direction), 0., f64::INFINITY));
+    }
+}
@@ -0,0 +1,316 @@
+use crate::{
+    shapes::{aabb::Aabb, hit::Hit, world::World},
+    utilities::{point::Point, ray::Ray},
+};
+
+/// Number of buckets centroids are sorted into when evaluating split candidates
+const BINS: usize = 12;
+/// Nodes with this many shapes or fewer may become leaves if splitting does not pay off
+const MAX_LEAF_SIZE: usize = 4;
+/// Cost of visiting a node relative to the cost of intersecting a shape
+const TRAVERSAL_COST: f64 = 0.5;
+
+/// A flattened bounding volume hierarchy node
+///
+/// The left child of an interior node is always stored directly after it
+struct Node {
+    bounds: Aabb,
+    /// For leaves, the first entry in `Bvh::indices`; for interior nodes, the right child
+    offset: usize,
+    /// Number of shapes in a leaf, 0 for interior nodes
+    count: usize,
+    /// Axis the children were split along, used to visit the nearer child first
+    axis: usize,
+}
+
+/// Shape data needed while building the hierarchy
+struct Primitive {
+    index: usize,
+    bounds: Aabb,
+    centroid: Point,
+}
+
+/// Bounding volume hierarchy built with the surface area heuristic
+///
+/// The hierarchy stores indices into a list of shapes (usually a `World`) instead of owning
+/// them, so the world can still be saved to a scene file as-is.
+///
+/// https://pbr-book.org/3ed-2018/Primitives_and_Intersection_Acceleration/Bounding_Volume_Hierarchies
+#[derive(Default)]
+pub struct Bvh {
+    nodes: Vec<Node>,
+    /// Shape indices ordered so that every leaf covers a contiguous range
+    indices: Vec<usize>,
+    /// Shapes without a bounding box, which are tested against every ray
+    unbounded: Vec<usize>,
+}
+
+impl Bvh {
+    pub fn new(world: &World) -> Self {
+        Self::from_bounds(world.iter().map(|shape| shape.bounding_box()))
+    }
+
+    /// Build a hierarchy over a list of bounding boxes, where `None` marks an unbounded shape
+    pub fn from_bounds(bounds: impl IntoIterator<Item = Option<Aabb>>) -> Self {
+        let mut bvh = Bvh::default();
+        let mut primitives = vec![];
+
+        bounds
+            .into_iter()
+            .enumerate()
+            .for_each(|(index, bounds)| match bounds {
+                Some(bounds) => primitives.push(Primitive {
+                    index,
+                    bounds,
+                    centroid: bounds.centroid(),
+                }),
+                None => bvh.unbounded.push(index),
+            });
+
+        if !primitives.is_empty() {
+            bvh.build(primitives);
+        }
+        bvh
+    }
+
+    /// Recursively build the subtree for `primitives`, returning the index of its root node
+    fn build(&mut self, mut primitives: Vec<Primitive>) -> usize {
+        let bounds = primitives
+            .iter()
+            .fold(Aabb::empty(), |acc, p| acc.union(&p.bounds));
+        let node_index = self.nodes.len();
+        self.nodes.push(Node {
+            bounds,
+            offset: 0,
+            count: 0,
+            axis: 0,
+        });
+
+        if let Some((axis, left)) = Self::split(&mut primitives, &bounds) {
+            let right_primitives = primitives.split_off(left);
+            self.build(primitives);
+            let right_index = self.build(right_primitives);
+            let node = &mut self.nodes[node_index];
+            node.offset = right_index;
+            node.axis = axis;
+        } else {
+            let node = &mut self.nodes[node_index];
+            node.offset = self.indices.len();
+            node.count = primitives.len();
+            self.indices.extend(primitives.iter().map(|p| p.index));
+        }
+
+        node_index
+    }
+
+    /// Choose the cheapest split according to the surface area heuristic
+    ///
+    /// Reorders `primitives` so the left side comes first, returning the split axis and the
+    /// size of the left side, or `None` if the node should become a leaf
+    fn split(primitives: &mut [Primitive], bounds: &Aabb) -> Option<(usize, usize)> {
+        if primitives.len() <= 2 {
+            return None;
+        }
+
+        let centroid_bounds = primitives
+            .iter()
+            .fold(Aabb::empty(), |acc, p| acc.grow(p.centroid));
+        let axis = centroid_bounds.longest_axis();
+        let axis_min = centroid_bounds.min.axis(axis);
+        let extent = centroid_bounds.max.axis(axis) - axis_min;
+
+        // Every centroid is in the same place, so no split can separate them
+        if extent <= 0. {
+            return (primitives.len() > MAX_LEAF_SIZE).then_some((axis, primitives.len() / 2));
+        }
+
+        let bin_of = |p: &Primitive| {
+            (((p.centroid.axis(axis) - axis_min) / extent * BINS as f64) as usize).min(BINS - 1)
+        };
+
+        let mut bin_bounds = [Aabb::empty(); BINS];
+        let mut bin_counts = [0usize; BINS];
+        primitives.iter().for_each(|p| {
+            let bin = bin_of(p);
+            bin_bounds[bin] = bin_bounds[bin].union(&p.bounds);
+            bin_counts[bin] += 1;
+        });
+
+        // Cost of splitting after each bin
+        let mut best_cost = f64::INFINITY;
+        let mut best_split = 0;
+        for split in 1..BINS {
+            let (left_bounds, left_count) = (0..split).fold((Aabb::empty(), 0), |(b, c), i| {
+                (b.union(&bin_bounds[i]), c + bin_counts[i])
+            });
+            let (right_bounds, right_count) = (split..BINS)
+                .fold((Aabb::empty(), 0), |(b, c), i| {
+                    (b.union(&bin_bounds[i]), c + bin_counts[i])
+                });
+            if left_count == 0 || right_count == 0 {
+                continue;
+            }
+            let cost = TRAVERSAL_COST
+                + (left_bounds.surface_area() * left_count as f64
+                    + right_bounds.surface_area() * right_count as f64)
+                    / bounds.surface_area();
+            if cost < best_cost {
+                best_cost = cost;
+                best_split = split;
+            }
+        }
+
+        // Intersecting every shape in a leaf is cheaper than splitting
+        if best_split == 0
+            || (best_cost >= primitives.len() as f64 && primitives.len() <= MAX_LEAF_SIZE)
+        {
+            return None;
+        }
+
+        primitives.sort_by_key(|p| bin_of(p) >= best_split);
+        let left = primitives.iter().filter(|p| bin_of(p) < best_split).count();
+        Some((axis, left))
+    }
+
+    /// Find the closest hit in `world`, which must be the world the hierarchy was built from
+    pub fn hit<'a>(
+        &self,
+        world: &'a World,
+        ray: &Ray,
+        time_min: f64,
+        time_max: f64,
+    ) -> Option<(usize, Hit<'a>)> {
+        self.traverse(ray, time_min, time_max, |index, closest_so_far| {
+            world[index].hit(ray, time_min, closest_so_far)
+        })
+    }
+
+    /// Find the closest hit and the index of the shape it belongs to, calling `intersect` with
+    /// the index of each shape whose bounds the ray passes through and the distance to the
+    /// closest hit found so far
+    pub fn traverse<'a>(
+        &self,
+        ray: &Ray,
+        time_min: f64,
+        time_max: f64,
+        intersect: impl Fn(usize, f64) -> Option<Hit<'a>>,
+    ) -> Option<(usize, Hit<'a>)> {
+        let mut hit: Option<(usize, Hit)> = None;
+        let mut closest_so_far = time_max;
+
+        self.unbounded.iter().for_each(|&index| {
+            if let Some(contact) = intersect(index, closest_so_far) {
+                closest_so_far = contact.time;
+                hit = Some((index, contact))
+            }
+        });
+
+        if self.nodes.is_empty() {
+            return hit;
+        }
+
+        let inverse_direction = Point::new(
+            1. / ray.direction.x,
+            1. / ray.direction.y,
+            1. / ray.direction.z,
+        );
+
+        let mut stack = Vec::with_capacity(32);
+        stack.push(0);
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index];
+            if !node
+                .bounds
+                .hit(ray, inverse_direction, time_min, closest_so_far)
+            {
+                continue;
+            }
+
+            if node.count > 0 {
+                self.indices[node.offset..node.offset + node.count]
+                    .iter()
+                    .for_each(|&index| {
+                        if let Some(contact) = intersect(index, closest_so_far) {
+                            closest_so_far = contact.time;
+                            hit = Some((index, contact))
+                        }
+                    });
+            } else if inverse_direction.axis(node.axis) < 0. {
+                // Ray travels towards the right child first, so pop it first
+                stack.push(node_index + 1);
+                stack.push(node.offset);
+            } else {
+                stack.push(node.offset);
+                stack.push(node_index + 1);
+            }
+        }
+
+        hit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        materials::{diffuse::Lambertian, scatter::Scatter},
+        shapes::{bvh::Bvh, hit::Hittable, sphere::Sphere, triangle::Triangle, world::World},
+        utilities::{point::Point, ray::Ray},
+    };
+
+    fn random_world(size: usize) -> World {
+        let mut world: World = vec![];
+        for _ in 0..size {
+            let center = Point::random(-10.0..10.0);
+            world.push(Box::new(Sphere::new(
+                center,
+                center,
+                0.,
+                1.,
+                0.5,
+                Box::new(Lambertian::random()),
+            )));
+            world.push(Box::new(Triangle::new(
+                center,
+                center + Point::random(-1.0..1.0),
+                center + Point::random(-1.0..1.0),
+                Box::new(Lambertian::random()),
+            )));
+        }
+        world
+    }
+
+    #[test]
+    fn can_build_empty() {
+        let world: World = vec![];
+        let bvh = Bvh::new(&world);
+        let ray = Ray::new(Point::origin(), Point::new(0., 0., -1.), 0.);
+        assert!(bvh.hit(&world, &ray, 0.001, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn can_split() {
+        let world = random_world(100);
+        let bvh = Bvh::new(&world);
+        assert!(bvh.nodes.len() > 1);
+        assert_eq!(bvh.indices.len(), world.len());
+        assert!(bvh.unbounded.is_empty());
+    }
+
+    #[test]
+    fn matches_linear_search() {
+        let world = random_world(200);
+        let bvh = Bvh::new(&world);
+
+        for _ in 0..1000 {
+            let ray = Ray::new(Point::random(-12.0..12.0), Point::random(-1.0..1.0), 0.);
+            let expected = world.hit(&ray, 0.001, f64::INFINITY).map(|hit| hit.time);
+            let actual = bvh.hit(&world, &ray, 0.001, f64::INFINITY);
+            assert_eq!(expected, actual.as_ref().map(|(_, hit)| hit.time));
+            // The index points at the shape that was hit
+            if let Some((index, hit)) = actual {
+                let shape = world[index].hit(&ray, 0.001, f64::INFINITY);
+                assert_eq!(shape.map(|hit| hit.time), Some(hit.time));
+            }
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use std::f64::consts::PI;
+
+use crate::{
+    materials::scatter::Material,
+    shapes::{
+        aabb::Aabb,
+        cylinder::{Cylinder, LocalHit},
+        hit::{Hit, Hittable},
+    },
+    utilities::{frame::Frame, point::Point, polynomial::solve_quadratic, ray::Ray},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// A cylinder of `radius` along `axis` from `base`, closed with a half sphere at each end
+#[derive(Serialize, Deserialize)]
+pub struct Capsule {
+    base: Point,
+    axis: Point,
+    radius: f64,
+    material: Material,
+}
+
+impl Capsule {
+    #[allow(dead_code)]
+    pub fn new(base: Point, axis: Point, radius: f64, material: Material) -> Self {
+        Self {
+            base,
+            axis,
+            radius,
+            material,
+        }
+    }
+
+    /// First hit of an upright ray on the half sphere centered at `height`, which bulges
+    /// upwards if `up` and downwards otherwise
+    fn end(
+        &self,
+        ray: &Ray,
+        height: f64,
+        up: bool,
+        time_min: f64,
+        time_max: f64,
+    ) -> Option<LocalHit> {
+        let center = Point::new(0., 0., height);
+        let offset = ray.origin - center;
+        let a = ray.direction.dot(ray.direction);
+        let b = 2. * offset.dot(ray.direction);
+        let c = offset.dot(offset) - self.radius.powi(2);
+        solve_quadratic(a, b, c)
+            .into_iter()
+            .filter(|time| (time_min..=time_max).contains(time))
+            .map(|time| (time, ray.at(time)))
+            .find(|(_, point)| (point.z >= height) == up)
+            .map(|(time, point)| {
+                let normal = (point - center) / self.radius;
+                (
+                    time,
+                    normal,
+                    (Cylinder::around(&point), self.v(&point, height, up)),
+                )
+            })
+    }
+
+    /// Texture coordinate for the distance along the outline from the bottom pole to the top
+    /// pole, passing through a point on the half sphere centered at `height`
+    fn v(&self, point: &Point, height: f64, up: bool) -> f64 {
+        let quarter = PI / 2. * self.radius;
+        let length = height + 2. * quarter;
+        let angle = ((point.z - height) / self.radius).clamp(-1., 1.).asin();
+        let distance = match up {
+            true => quarter + height + angle * self.radius,
+            false => quarter + angle * self.radius,
+        };
+        distance / length
+    }
+}
+
+#[typetag::serde]
+impl Hittable for Capsule {
+    fn hit(&self, ray: &Ray, time_min: f64, time_max: f64) -> Option<Hit<'_>> {
+        let frame = Frame::new(self.base, self.axis);
+        let local = frame.to_local_ray(ray);
+        let height = self.axis.len();
+
+        let side = Cylinder::side(&local, height, self.radius, time_min, time_max).map(
+            |(time, normal, (u, _))| {
+                let quarter = PI / 2. * self.radius;
+                let v = (quarter + local.at(time).z) / (height + 2. * quarter);
+                (time, normal, (u, v))
+            },
+        );
+        let ends = [
+            self.end(&local, 0., false, time_min, time_max),
+            self.end(&local, height, true, time_min, time_max),
+        ];
+        let (time, normal, uv) = [side]
+            .into_iter()
+            .chain(ends)
+            .flatten()
+            .min_by(|a, b| a.0.total_cmp(&b.0))?;
+
+        let mut hit = Hit::new(ray.at(time), Point::origin(), &self.material, time, false);
+        hit.set_face_normal(ray, frame.to_world_vector(&normal));
+        hit.uv = uv;
+        Some(hit)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let radius = Point::new(self.radius, self.radius, self.radius);
+        let top = self.base + self.axis;
+        let bottom = Aabb::new(self.base - radius, self.base + radius);
+        Some(bottom.union(&Aabb::new(top - radius, top + radius)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        materials::{diffuse::Lambertian, scatter::Scatter},
+        shapes::{capsule::Capsule, hit::Hittable},
+        utilities::{point::Point, ray::Ray},
+    };
+
+    /// Lying along x from the origin to x = 2, reaching from x = -1 to 3 with its ends
+    fn pill() -> Capsule {
+        Capsule::new(
+            Point::origin(),
+            Point::new(2., 0., 0.),
+            1.,
+            Box::new(Lambertian::random()),
+        )
+    }
+
+    #[test]
+    fn can_hit_side() {
+        let ray = Ray::new(Point::new(1., 5., 0.), Point::new(0., -1., 0.), 0.);
+        let capsule = pill();
+        let hit = capsule.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.time - 4.).abs() < 1e-9);
+        assert!((hit.normal - Point::new(0., 1., 0.)).len() < 1e-9);
+        assert!((hit.uv.1 - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn can_hit_ends() {
+        let capsule = pill();
+        let ray = Ray::new(Point::new(-5., 0., 0.), Point::new(1., 0., 0.), 0.);
+        let hit = capsule.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.time - 4.).abs() < 1e-9);
+        assert!((hit.normal - Point::new(-1., 0., 0.)).len() < 1e-9);
+        assert!(hit.uv.1.abs() < 1e-9);
+
+        let ray = Ray::new(Point::new(10., 0., 0.), Point::new(-1., 0., 0.), 0.);
+        let hit = capsule.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.time - 7.).abs() < 1e-9);
+        assert!((hit.uv.1 - 1.).abs() < 1e-9);
+
+        // Past the end of the straight part, but inside the end's bounding box
+        let corner = Ray::new(Point::new(2.9, 5., 0.9), Point::new(0., -1., 0.), 0.);
+        assert!(capsule.hit(&corner, 0.001, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn can_bound() {
+        let bounds = pill().bounding_box().unwrap();
+        assert_eq!(bounds.min, Point::new(-1., -1., -1.));
+        assert_eq!(bounds.max, Point::new(3., 1., 1.));
+    }
+}
@@ -0,0 +1,171 @@
+use crate::{
+    materials::scatter::Material,
+    shapes::{
+        aabb::Aabb,
+        cylinder::{Cylinder, LocalHit},
+        hit::{Hit, Hittable},
+    },
+    utilities::{frame::Frame, point::Point, polynomial::solve_quadratic, ray::Ray},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// A cone standing on a circle of `radius` at `base`, narrowing along `axis` to `top_radius`,
+/// which is a point by default and a flat top otherwise; the ends are closed unless `caps` is off
+#[derive(Serialize, Deserialize)]
+pub struct Cone {
+    base: Point,
+    axis: Point,
+    radius: f64,
+    #[serde(default)]
+    top_radius: f64,
+    #[serde(default = "Cone::default_caps")]
+    caps: bool,
+    material: Material,
+}
+
+impl Cone {
+    #[allow(dead_code)]
+    pub fn new(
+        base: Point,
+        axis: Point,
+        radius: f64,
+        top_radius: f64,
+        caps: bool,
+        material: Material,
+    ) -> Self {
+        Self {
+            base,
+            axis,
+            radius,
+            top_radius,
+            caps,
+            material,
+        }
+    }
+
+    fn default_caps() -> bool {
+        true
+    }
+
+    /// First hit of an upright ray on the slanted side, where the radius at height `z` is
+    /// `radius + slope * z`
+    fn side(&self, ray: &Ray, height: f64, time_min: f64, time_max: f64) -> Option<LocalHit> {
+        let (origin, direction) = (ray.origin, ray.direction);
+        let slope = (self.top_radius - self.radius) / height;
+        let radius_at_origin = self.radius + slope * origin.z;
+        let a = direction.x.powi(2) + direction.y.powi(2) - (slope * direction.z).powi(2);
+        let b = 2.
+            * (origin.x * direction.x + origin.y * direction.y
+                - slope * direction.z * radius_at_origin);
+        let c = origin.x.powi(2) + origin.y.powi(2) - radius_at_origin.powi(2);
+        solve_quadratic(a, b, c)
+            .into_iter()
+            .filter(|time| (time_min..=time_max).contains(time))
+            .map(|time| (time, ray.at(time)))
+            .find(|(_, point)| (0. ..=height).contains(&point.z))
+            .map(|(time, point)| {
+                // Gradient of x² + y² - (radius + slope z)²
+                let radius = self.radius + slope * point.z;
+                let normal = Point::new(point.x, point.y, -radius * slope).normalized();
+                (time, normal, (Cylinder::around(&point), point.z / height))
+            })
+    }
+}
+
+#[typetag::serde]
+impl Hittable for Cone {
+    fn hit(&self, ray: &Ray, time_min: f64, time_max: f64) -> Option<Hit<'_>> {
+        let frame = Frame::new(self.base, self.axis);
+        let local = frame.to_local_ray(ray);
+        let height = self.axis.len();
+
+        let side = self.side(&local, height, time_min, time_max);
+        let caps = match self.caps {
+            true => [
+                Cylinder::cap(&local, 0., self.radius, false, time_min, time_max),
+                Cylinder::cap(&local, height, self.top_radius, true, time_min, time_max),
+            ],
+            false => [None, None],
+        };
+        let (time, normal, uv) = [side]
+            .into_iter()
+            .chain(caps)
+            .flatten()
+            .min_by(|a, b| a.0.total_cmp(&b.0))?;
+
+        let mut hit = Hit::new(ray.at(time), Point::origin(), &self.material, time, false);
+        hit.set_face_normal(ray, frame.to_world_vector(&normal));
+        hit.uv = uv;
+        Some(hit)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let bottom = Aabb::around_disk(self.base, self.axis, self.radius);
+        let top = Aabb::around_disk(self.base + self.axis, self.axis, self.top_radius);
+        Some(bottom.union(&top).pad(1e-4))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        materials::{diffuse::Lambertian, scatter::Scatter},
+        shapes::{cone::Cone, hit::Hittable},
+        utilities::{point::Point, ray::Ray},
+    };
+
+    /// Standing on the origin with its tip at y = 2
+    fn spike(top_radius: f64) -> Cone {
+        Cone::new(
+            Point::origin(),
+            Point::new(0., 2., 0.),
+            1.,
+            top_radius,
+            true,
+            Box::new(Lambertian::random()),
+        )
+    }
+
+    #[test]
+    fn can_hit_side() {
+        let cone = spike(0.);
+        // Halfway up, the cone is half as wide
+        let ray = Ray::new(Point::new(0., 1., 5.), Point::new(0., 0., -1.), 0.);
+        let hit = cone.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.time - 4.5).abs() < 1e-9);
+        // The side leans back by the cone's slope of one across for two up
+        let expected = Point::new(0., 1., 2.).normalized();
+        assert!((hit.normal - expected).len() < 1e-9);
+        assert!((hit.uv.1 - 0.5).abs() < 1e-9);
+
+        let beside = Ray::new(Point::new(0.6, 1., 5.), Point::new(0., 0., -1.), 0.);
+        assert!(cone.hit(&beside, 0.001, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn can_hit_caps() {
+        let up = Ray::new(Point::new(0.2, -5., 0.), Point::new(0., 1., 0.), 0.);
+        let cone = spike(0.);
+        let hit = cone.hit(&up, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.time - 5.).abs() < 1e-9);
+        assert!((hit.normal - Point::new(0., -1., 0.)).len() < 1e-9);
+
+        let down = Ray::new(Point::new(0.2, 5., 0.), Point::new(0., -1., 0.), 0.);
+        let frustum = spike(0.5);
+        let hit = frustum.hit(&down, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.time - 3.).abs() < 1e-9);
+        assert!((hit.normal - Point::new(0., 1., 0.)).len() < 1e-9);
+    }
+
+    #[test]
+    fn can_deserialize() {
+        let yaml = "type: Cone\nbase: {x: 0.0, y: 0.0, z: 0.0}\naxis: {x: 0.0, y: 2.0, z: 0.0}\nradius: 1.0\ncaps: false\nmaterial:\n  type: Lambertian\n  albedo: {r: 0.5, g: 0.5, b: 0.5, a: 255}\n  probability: 1.0\n";
+        let shape: Box<dyn Hittable> = serde_yml::from_str(yaml).unwrap();
+        let bounds = shape.bounding_box().unwrap();
+        assert!((bounds.max.y - 2.).abs() < 1e-3);
+        // Without caps, the inside of the cone shows through the open bottom
+        let up = Ray::new(Point::new(0.2, -5., 0.), Point::new(0., 1., 0.), 0.);
+        assert!(!shape.hit(&up, 0.001, f64::INFINITY).unwrap().front_face);
+    }
+}
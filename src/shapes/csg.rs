@@ -0,0 +1,241 @@
+use crate::{
+    shapes::{
+        aabb::Aabb,
+        hit::{Hit, Hittable, Span},
+        instance::Library,
+    },
+    utilities::ray::Ray,
+};
+
+use serde::{Deserialize, Serialize};
+
+/// How `Csg` combines the space inside its two shapes
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Operation {
+    /// Inside either shape
+    Union,
+    /// Inside both shapes
+    Intersection,
+    /// Inside `left` but not `right`, which cuts `right` out of `left`
+    Difference,
+}
+
+impl Operation {
+    fn contains(&self, left: bool, right: bool) -> bool {
+        match self {
+            Operation::Union => left || right,
+            Operation::Intersection => left && right,
+            Operation::Difference => left && !right,
+        }
+    }
+}
+
+/// A solid made by combining two closed shapes, such as a box with a sphere cut out of it or a
+/// lens where two spheres overlap
+///
+/// https://en.wikipedia.org/wiki/Constructive_solid_geometry
+#[derive(Serialize, Deserialize)]
+pub struct Csg {
+    operation: Operation,
+    left: Box<dyn Hittable>,
+    right: Box<dyn Hittable>,
+}
+
+impl Csg {
+    #[allow(dead_code)]
+    pub fn new(operation: Operation, left: Box<dyn Hittable>, right: Box<dyn Hittable>) -> Self {
+        Self {
+            operation,
+            left,
+            right,
+        }
+    }
+}
+
+#[typetag::serde]
+impl Hittable for Csg {
+    /// The first place the ray goes in or out of the combined solid
+    fn hit(&self, ray: &Ray, time_min: f64, time_max: f64) -> Option<Hit<'_>> {
+        let span = self.spans(ray, time_min, time_max).into_iter().next()?;
+        span.enter.or(span.exit)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let (left, right) = (self.left.bounding_box(), self.right.bounding_box());
+        match self.operation {
+            Operation::Union => Some(left?.union(&right?)),
+            Operation::Intersection => match (left, right) {
+                (Some(left), Some(right)) => Some(left.intersection(&right)),
+                (left, right) => left.or(right),
+            },
+            Operation::Difference => left,
+        }
+    }
+
+    /// Walks through where the ray crosses either shape in order, keeping the crossings that
+    /// change whether it is inside the combined solid
+    fn spans(&self, ray: &Ray, time_min: f64, time_max: f64) -> Vec<Span<'_>> {
+        let mut inside = [false; 2];
+        let mut crossings = Vec::new();
+        [&self.left, &self.right]
+            .into_iter()
+            .enumerate()
+            .for_each(|(side, shape)| {
+                shape
+                    .spans(ray, time_min, time_max)
+                    .into_iter()
+                    .for_each(|span| {
+                        match span.enter {
+                            Some(hit) => crossings.push((side, true, hit)),
+                            None => inside[side] = true,
+                        }
+                        if let Some(hit) = span.exit {
+                            crossings.push((side, false, hit));
+                        }
+                    })
+            });
+        crossings.sort_by(|a, b| a.2.time.total_cmp(&b.2.time));
+
+        let mut spans = Vec::new();
+        let mut enter = None;
+        let mut was_inside = self.operation.contains(inside[0], inside[1]);
+        for (side, entering, mut hit) in crossings {
+            inside[side] = entering;
+            let is_inside = self.operation.contains(inside[0], inside[1]);
+            if is_inside == was_inside {
+                continue;
+            }
+            was_inside = is_inside;
+            // A surface cut out by `right` faces into it, so going into `right` comes out of
+            // the solid; normals already face the ray, so only the side needs fixing
+            hit.front_face = is_inside;
+            match is_inside {
+                true => enter = Some(hit),
+                false => spans.push(Span {
+                    enter: enter.take(),
+                    exit: Some(hit),
+                }),
+            }
+        }
+        if was_inside {
+            spans.push(Span { enter, exit: None });
+        }
+        spans
+    }
+
+    fn link(&mut self, shapes: &Library) {
+        self.left.link(shapes);
+        self.right.link(shapes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        materials::{diffuse::Lambertian, scatter::Scatter},
+        shapes::{
+            csg::{Csg, Operation},
+            cuboid::Cuboid,
+            hit::Hittable,
+            sphere::Sphere,
+        },
+        utilities::{point::Point, ray::Ray},
+    };
+
+    fn ball(x: f64, radius: f64) -> Box<dyn Hittable> {
+        let center = Point::new(x, 0., 0.);
+        let material = Box::new(Lambertian::random());
+        Box::new(Sphere::new(center, center, 0., 1., radius, material))
+    }
+
+    fn cube() -> Box<dyn Hittable> {
+        Box::new(Cuboid::new(
+            Point::new(-1., -1., -1.),
+            Point::new(1., 1., 1.),
+            Box::new(Lambertian::random()),
+        ))
+    }
+
+    #[test]
+    fn can_cut_difference() {
+        let csg = Csg::new(Operation::Difference, cube(), ball(0., 0.5));
+        let ray = Ray::new(Point::new(0., 0., 5.), Point::new(0., 0., -1.), 0.);
+        let spans = csg.spans(&ray, 0.001, f64::INFINITY);
+        let times: Vec<_> = spans
+            .iter()
+            .map(|span| {
+                (
+                    span.enter.as_ref().unwrap().time,
+                    span.exit.as_ref().unwrap().time,
+                )
+            })
+            .collect();
+        assert_eq!(times, vec![(4., 4.5), (5.5, 6.)]);
+
+        // From inside the hole, the ray goes into the solid through the sphere's inner face
+        let inside = Ray::new(Point::origin(), Point::new(0., 0., 1.), 0.);
+        let hit = csg.hit(&inside, 0.001, f64::INFINITY).unwrap();
+        assert_eq!(hit.time, 0.5);
+        assert!(hit.front_face);
+        assert_eq!(hit.normal, Point::new(0., 0., -1.));
+
+        // Going out of the solid into the hole
+        let hit = csg.hit(&ray, 4.1, f64::INFINITY).unwrap();
+        assert_eq!(hit.time, 4.5);
+        assert!(!hit.front_face);
+    }
+
+    #[test]
+    fn can_make_lens() {
+        let lens = Csg::new(Operation::Intersection, ball(-0.5, 1.), ball(0.5, 1.));
+        let ray = Ray::new(Point::new(-5., 0., 0.), Point::new(1., 0., 0.), 0.);
+        let hit = lens.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert_eq!(hit.time, 4.5);
+        assert!(hit.front_face);
+
+        // Through the middle of the lens, then out the far side
+        let exit = lens.hit(&ray, 4.6, f64::INFINITY).unwrap();
+        assert_eq!(exit.time, 5.5);
+        assert!(!exit.front_face);
+
+        // Inside the first sphere only
+        let beside = Ray::new(Point::new(-1.2, 5., 0.), Point::new(0., -1., 0.), 0.);
+        assert!(lens.hit(&beside, 0.001, f64::INFINITY).is_none());
+
+        let bounds = lens.bounding_box().unwrap();
+        assert_eq!(bounds.min.x, -0.5);
+        assert_eq!(bounds.max.x, 0.5);
+    }
+
+    #[test]
+    fn can_join_union() {
+        let union = Csg::new(Operation::Union, ball(-0.5, 1.), ball(0.5, 1.));
+        let ray = Ray::new(Point::new(-5., 0., 0.), Point::new(1., 0., 0.), 0.);
+        let spans = union.spans(&ray, 0.001, f64::INFINITY);
+        // The overlapping surfaces inside are skipped
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].enter.as_ref().unwrap().time, 3.5);
+        assert_eq!(spans[0].exit.as_ref().unwrap().time, 6.5);
+    }
+
+    #[test]
+    fn can_nest() {
+        let lens = Csg::new(Operation::Intersection, ball(-0.5, 1.), ball(0.5, 1.));
+        let drilled = Csg::new(Operation::Difference, Box::new(lens), ball(0., 0.25));
+        let ray = Ray::new(Point::new(-5., 0., 0.), Point::new(1., 0., 0.), 0.);
+        let spans = drilled.spans(&ray, 0.001, f64::INFINITY);
+        assert_eq!(spans.len(), 2);
+        assert_eq!(spans[1].enter.as_ref().unwrap().time, 5.25);
+    }
+
+    #[test]
+    fn can_deserialize() {
+        let sphere = "type: Sphere\n  center_t_0: {x: 0.0, y: 0.0, z: 0.0}\n  center_t_1: {x: 0.0, y: 0.0, z: 0.0}\n  t_0: 0.0\n  t_1: 1.0\n  radius: 1.5\n  material:\n    type: Dielectric\n    albedo: {r: 1.0, g: 1.0, b: 1.0, a: 255}\n    refraction_index: 1.5\n";
+        let cuboid = "type: Cuboid\n  min: {x: -1.0, y: -1.0, z: -1.0}\n  max: {x: 1.0, y: 1.0, z: 1.0}\n  material:\n    type: Dielectric\n    albedo: {r: 1.0, g: 1.0, b: 1.0, a: 255}\n    refraction_index: 1.5\n";
+        let yaml =
+            format!("type: Csg\noperation: Intersection\nleft:\n  {cuboid}right:\n  {sphere}");
+        let shape: Box<dyn Hittable> = serde_yml::from_str(&yaml).unwrap();
+        let ray = Ray::new(Point::new(0., 0., 5.), Point::new(0., 0., -1.), 0.);
+        assert_eq!(shape.hit(&ray, 0.001, f64::INFINITY).unwrap().time, 4.);
+    }
+}
@@ -0,0 +1,337 @@
+use std::collections::BTreeMap;
+
+use rand::Rng;
+
+use crate::{
+    materials::scatter::Material,
+    shapes::{
+        aabb::Aabb,
+        hit::{area_pdf, Hit, Hittable},
+    },
+    utilities::{point::Point, ray::Ray},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// The six sides of a `Cuboid`, named for the direction they face
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Face {
+    /// Facing `-x`
+    Left,
+    /// Facing `+x`
+    Right,
+    /// Facing `-y`
+    Bottom,
+    /// Facing `+y`
+    Top,
+    /// Facing `-z`
+    Back,
+    /// Facing `+z`
+    Front,
+}
+
+impl Face {
+    const ALL: [Face; 6] = [
+        Face::Left,
+        Face::Right,
+        Face::Bottom,
+        Face::Top,
+        Face::Back,
+        Face::Front,
+    ];
+
+    fn new(axis: usize, positive: bool) -> Self {
+        Self::ALL[2 * axis + positive as usize]
+    }
+
+    fn axis(&self) -> usize {
+        *self as usize / 2
+    }
+
+    fn is_positive(&self) -> bool {
+        *self as usize % 2 == 1
+    }
+}
+
+#[derive(Deserialize)]
+struct CuboidFile {
+    min: Point,
+    max: Point,
+    material: Material,
+    #[serde(default)]
+    faces: BTreeMap<Face, Material>,
+}
+
+impl From<CuboidFile> for Cuboid {
+    fn from(file: CuboidFile) -> Self {
+        Cuboid::new(file.min, file.max, file.material).with_faces(file.faces)
+    }
+}
+
+/// A box with sides parallel to the axes; wrap it in a `Transform` to turn it
+#[derive(Serialize, Deserialize)]
+#[serde(from = "CuboidFile")]
+pub struct Cuboid {
+    min: Point,
+    max: Point,
+    material: Material,
+    /// Materials for single faces, used instead of `material` on those faces
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    faces: BTreeMap<Face, Material>,
+}
+
+impl Cuboid {
+    /// Any two opposite corners define the box
+    pub fn new(a: Point, b: Point, material: Material) -> Self {
+        Self {
+            min: a.min(b),
+            max: a.max(b),
+            material,
+            faces: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_faces(mut self, faces: BTreeMap<Face, Material>) -> Self {
+        self.faces = faces;
+        self
+    }
+
+    fn material(&self, face: Face) -> &Material {
+        self.faces.get(&face).unwrap_or(&self.material)
+    }
+
+    /// Distances along `ray` to where it enters and leaves the box, with the faces it crosses
+    ///
+    /// https://tavianator.com/2011/ray_box.html
+    fn intersect(&self, ray: &Ray) -> Option<[(f64, Face); 2]> {
+        let mut enter = (f64::NEG_INFINITY, Face::Left);
+        let mut leave = (f64::INFINITY, Face::Left);
+        for axis in 0..3 {
+            let inverse = 1. / ray.direction.axis(axis);
+            let t_0 = (self.min.axis(axis) - ray.origin.axis(axis)) * inverse;
+            let t_1 = (self.max.axis(axis) - ray.origin.axis(axis)) * inverse;
+            // Moving towards +x enters through the face looking at -x, and leaves through +x
+            let (near, far) = match inverse < 0. {
+                true => ((t_1, Face::new(axis, true)), (t_0, Face::new(axis, false))),
+                false => ((t_0, Face::new(axis, false)), (t_1, Face::new(axis, true))),
+            };
+            if near.0 > enter.0 {
+                enter = near;
+            }
+            if far.0 < leave.0 {
+                leave = far;
+            }
+        }
+        (enter.0 <= leave.0).then_some([enter, leave])
+    }
+
+    fn normal(face: Face) -> Point {
+        let sign = if face.is_positive() { 1. } else { -1. };
+        match face.axis() {
+            0 => Point::new(sign, 0., 0.),
+            1 => Point::new(0., sign, 0.),
+            _ => Point::new(0., 0., sign),
+        }
+    }
+
+    /// Texture coordinates on `face`, laid out upright as seen from outside the box, with the
+    /// top and bottom faces turned so their `v` points away from the front
+    fn uv(&self, point: &Point, face: Face) -> (f64, f64) {
+        let size = self.max - self.min;
+        let [x, y, z] = [0, 1, 2].map(|axis| {
+            let extent = size.axis(axis);
+            match extent > 0. {
+                true => ((point.axis(axis) - self.min.axis(axis)) / extent).clamp(0., 1.),
+                false => 0.,
+            }
+        });
+        match face {
+            Face::Left => (z, y),
+            Face::Right => (1. - z, y),
+            Face::Bottom => (x, z),
+            Face::Top => (x, 1. - z),
+            Face::Back => (1. - x, y),
+            Face::Front => (x, y),
+        }
+    }
+
+    fn area(&self, face: Face) -> f64 {
+        let size = self.max - self.min;
+        let axis = face.axis();
+        size.axis((axis + 1) % 3) * size.axis((axis + 2) % 3)
+    }
+
+    /// Faces whose material gives off light, which are the ones sampled as lights
+    fn emitting(&self) -> impl Iterator<Item = Face> + '_ {
+        Face::ALL
+            .into_iter()
+            .filter(|face| !self.material(*face).emit().is_black())
+    }
+}
+
+#[typetag::serde]
+impl Hittable for Cuboid {
+    fn hit(&self, ray: &Ray, time_min: f64, time_max: f64) -> Option<Hit<'_>> {
+        let [enter, leave] = self.intersect(ray)?;
+        // From inside the box the ray is already past the face it entered through
+        let (time, face) = [enter, leave]
+            .into_iter()
+            .find(|(time, _)| (time_min..=time_max).contains(time))?;
+
+        let mut hit = Hit::new(
+            ray.at(time),
+            Point::origin(),
+            self.material(face),
+            time,
+            false,
+        );
+        hit.set_face_normal(ray, Self::normal(face));
+        hit.uv = self.uv(&hit.point, face);
+        Some(hit)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(Aabb::new(self.min, self.max))
+    }
+
+    fn is_emitter(&self) -> bool {
+        self.emitting().next().is_some()
+    }
+
+    /// Picks a point uniformly over the area of the faces that give off light
+    fn sample(&self, origin: &Point, _time: f64) -> Option<Point> {
+        let total: f64 = self.emitting().map(|face| self.area(face)).sum();
+        let mut rng = rand::thread_rng();
+        let mut target = rng.gen::<f64>() * total;
+        let face = self
+            .emitting()
+            .find(|face| {
+                target -= self.area(*face);
+                target <= 0.
+            })
+            .or_else(|| self.emitting().last())?;
+
+        let axis = face.axis();
+        let point = Point::new(rng.gen(), rng.gen(), rng.gen());
+        let [x, y, z] = [0, 1, 2].map(|index| match index == axis {
+            true if face.is_positive() => self.max.axis(index),
+            true => self.min.axis(index),
+            false => self.min.axis(index) + point.axis(index) * (self.max - self.min).axis(index),
+        });
+        Some((Point::new(x, y, z) - *origin).normalized())
+    }
+
+    /// Summed over both sides of the box the direction passes through
+    fn pdf(&self, origin: &Point, direction: &Point, time: f64) -> f64 {
+        let direction = direction.normalized();
+        let total: f64 = self.emitting().map(|face| self.area(face)).sum();
+        let Some(crossings) = self.intersect(&Ray::new(*origin, direction, time)) else {
+            return 0.;
+        };
+        crossings
+            .into_iter()
+            .filter(|(distance, face)| *distance > 0.001 && !self.material(*face).emit().is_black())
+            .map(|(distance, face)| area_pdf(distance, direction.axis(face.axis()).abs(), total))
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::{
+        materials::{diffuse::Lambertian, light::Light, scatter::Scatter},
+        shapes::{
+            cuboid::{Cuboid, Face},
+            hit::Hittable,
+        },
+        utilities::{color::Color, point::Point, ray::Ray},
+    };
+
+    fn unit_cube() -> Cuboid {
+        Cuboid::new(
+            Point::new(1., 1., 1.),
+            Point::new(-1., -1., -1.),
+            Box::new(Lambertian::random()),
+        )
+    }
+
+    #[test]
+    fn can_hit_faces() {
+        let cube = unit_cube();
+        let ray = Ray::new(Point::new(0.5, 0.5, 5.), Point::new(0., 0., -1.), 0.);
+        let hit = cube.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert_eq!(hit.time, 4.);
+        assert_eq!(hit.normal, Point::new(0., 0., 1.));
+        assert!(hit.front_face);
+        assert_eq!(hit.uv, (0.75, 0.75));
+
+        let ray = Ray::new(Point::new(-5., 0., 0.), Point::new(1., 0., 0.), 0.);
+        let hit = cube.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert_eq!(hit.normal, Point::new(-1., 0., 0.));
+
+        let miss = Ray::new(Point::new(0., 1.5, 5.), Point::new(0., 0., -1.), 0.);
+        assert!(cube.hit(&miss, 0.001, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn can_hit_from_inside() {
+        let cube = unit_cube();
+        let ray = Ray::new(Point::origin(), Point::new(0., -1., 0.), 0.);
+        let hit = cube.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert_eq!(hit.time, 1.);
+        assert_eq!(hit.point, Point::new(0., -1., 0.));
+        // Normals face back against the ray, into the box
+        assert_eq!(hit.normal, Point::new(0., 1., 0.));
+        assert!(!hit.front_face);
+    }
+
+    #[test]
+    fn can_use_face_materials() {
+        let faces = BTreeMap::from([(
+            Face::Top,
+            Box::new(Light::new(Color::new(1., 1., 1., 255), 4.)) as Box<dyn Scatter>,
+        )]);
+        let cube = unit_cube().with_faces(faces);
+        assert!(cube.is_emitter());
+        assert!(!unit_cube().is_emitter());
+
+        let down = Ray::new(Point::new(0., 5., 0.), Point::new(0., -1., 0.), 0.);
+        let hit = cube.hit(&down, 0.001, f64::INFINITY).unwrap();
+        assert!(!hit.material.emit().is_black());
+        let side = Ray::new(Point::new(5., 0., 0.), Point::new(-1., 0., 0.), 0.);
+        let hit = cube.hit(&side, 0.001, f64::INFINITY).unwrap();
+        assert!(hit.material.emit().is_black());
+    }
+
+    #[test]
+    fn can_sample_lit_faces() {
+        let light = Box::new(Light::new(Color::new(1., 1., 1., 255), 4.));
+        let cube = Cuboid::new(Point::new(-1., -1., -1.), Point::new(1., 1., 1.), light);
+        let origin = Point::new(0., 0., 10.);
+        // Only the front face is visible, seen almost straight on
+        (0..100).for_each(|_| {
+            let direction = cube.sample(&origin, 0.).unwrap();
+            let ray = Ray::new(origin, direction, 0.);
+            assert!(cube.hit(&ray, 0.001, f64::INFINITY).is_some());
+        });
+        // Each direction reaches the front and back faces, so the density integrates to one
+        let pdf = cube.pdf(&origin, &Point::new(0., 0., -1.), 0.);
+        let expected = (9f64.powi(2) + 11f64.powi(2)) / 24.;
+        assert!((pdf - expected).abs() < 1e-9);
+        assert_eq!(cube.pdf(&origin, &Point::new(0., 1., 0.), 0.), 0.);
+    }
+
+    #[test]
+    fn can_deserialize() {
+        let yaml = "type: Cuboid\nmin: {x: 1.0, y: 2.0, z: 3.0}\nmax: {x: 0.0, y: 0.0, z: 0.0}\nmaterial:\n  type: Lambertian\n  albedo: {r: 0.5, g: 0.5, b: 0.5, a: 255}\n  probability: 1.0\nfaces:\n  Top:\n    type: Light\n    albedo: {r: 1.0, g: 1.0, b: 1.0, a: 255}\n    intensity: 2.0\n";
+        let shape: Box<dyn Hittable> = serde_yml::from_str(yaml).unwrap();
+        let bounds = shape.bounding_box().unwrap();
+        assert_eq!(bounds.min, Point::origin());
+        assert_eq!(bounds.max, Point::new(1., 2., 3.));
+        assert!(shape.is_emitter());
+        let saved = serde_yml::to_string(&shape).unwrap();
+        assert!(saved.contains("Top"));
+    }
+}
@@ -0,0 +1,194 @@
+use std::f64::consts::PI;
+
+use crate::{
+    materials::scatter::Material,
+    shapes::{
+        aabb::Aabb,
+        hit::{Hit, Hittable},
+        plane::Plane,
+    },
+    utilities::{frame::Frame, point::Point, polynomial::solve_quadratic, ray::Ray},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Distance along a ray, the outward normal and the texture coordinates of a hit, all in the
+/// upright space of a `Frame`
+pub type LocalHit = (f64, Point, (f64, f64));
+
+/// A tube of `radius` around `axis`, which runs from the middle of the bottom at `base` to the
+/// middle of the top, closed at both ends unless `caps` is off
+#[derive(Serialize, Deserialize)]
+pub struct Cylinder {
+    base: Point,
+    axis: Point,
+    radius: f64,
+    #[serde(default = "Cylinder::default_caps")]
+    caps: bool,
+    material: Material,
+}
+
+impl Cylinder {
+    #[allow(dead_code)]
+    pub fn new(base: Point, axis: Point, radius: f64, caps: bool, material: Material) -> Self {
+        Self {
+            base,
+            axis,
+            radius,
+            caps,
+            material,
+        }
+    }
+
+    fn default_caps() -> bool {
+        true
+    }
+
+    /// Texture coordinate for the angle around the z axis of a point in upright space
+    pub fn around(point: &Point) -> f64 {
+        (point.y.atan2(point.x) + PI) / (2. * PI)
+    }
+
+    /// First hit of an upright ray on the open tube of `radius` from z = 0 to `height`
+    pub fn side(
+        ray: &Ray,
+        height: f64,
+        radius: f64,
+        time_min: f64,
+        time_max: f64,
+    ) -> Option<LocalHit> {
+        let (origin, direction) = (ray.origin, ray.direction);
+        let a = direction.x.powi(2) + direction.y.powi(2);
+        let b = 2. * (origin.x * direction.x + origin.y * direction.y);
+        let c = origin.x.powi(2) + origin.y.powi(2) - radius.powi(2);
+        solve_quadratic(a, b, c)
+            .into_iter()
+            .filter(|time| (time_min..=time_max).contains(time))
+            .map(|time| (time, ray.at(time)))
+            .find(|(_, point)| (0. ..=height).contains(&point.z))
+            .map(|(time, point)| {
+                let normal = Point::new(point.x, point.y, 0.) / radius;
+                (time, normal, (Self::around(&point), point.z / height))
+            })
+    }
+
+    /// Hit of an upright ray on the flat end at `height` that faces `up` or down, mapped onto
+    /// the texture like a `Disk`
+    pub fn cap(
+        ray: &Ray,
+        height: f64,
+        radius: f64,
+        up: bool,
+        time_min: f64,
+        time_max: f64,
+    ) -> Option<LocalHit> {
+        let normal = Point::new(0., 0., if up { 1. } else { -1. });
+        let center = Point::new(0., 0., height);
+        let time = Plane::intersect(center, normal, ray, time_min, time_max)?;
+        let point = ray.at(time);
+        (point.x.powi(2) + point.y.powi(2) <= radius.powi(2)).then(|| {
+            let uv = ((point.x / radius + 1.) / 2., (point.y / radius + 1.) / 2.);
+            (time, normal, uv)
+        })
+    }
+}
+
+#[typetag::serde]
+impl Hittable for Cylinder {
+    fn hit(&self, ray: &Ray, time_min: f64, time_max: f64) -> Option<Hit<'_>> {
+        let frame = Frame::new(self.base, self.axis);
+        let local = frame.to_local_ray(ray);
+        let height = self.axis.len();
+
+        let side = Self::side(&local, height, self.radius, time_min, time_max);
+        let caps = match self.caps {
+            true => [
+                Self::cap(&local, 0., self.radius, false, time_min, time_max),
+                Self::cap(&local, height, self.radius, true, time_min, time_max),
+            ],
+            false => [None, None],
+        };
+        let (time, normal, uv) = [side]
+            .into_iter()
+            .chain(caps)
+            .flatten()
+            .min_by(|a, b| a.0.total_cmp(&b.0))?;
+
+        let mut hit = Hit::new(ray.at(time), Point::origin(), &self.material, time, false);
+        hit.set_face_normal(ray, frame.to_world_vector(&normal));
+        hit.uv = uv;
+        Some(hit)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let bottom = Aabb::around_disk(self.base, self.axis, self.radius);
+        let top = Aabb::around_disk(self.base + self.axis, self.axis, self.radius);
+        Some(bottom.union(&top).pad(1e-4))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        materials::{diffuse::Lambertian, scatter::Scatter},
+        shapes::{cylinder::Cylinder, hit::Hittable},
+        utilities::{point::Point, ray::Ray},
+    };
+
+    /// Standing on the origin, reaching up to y = 2
+    fn pillar(caps: bool) -> Cylinder {
+        Cylinder::new(
+            Point::origin(),
+            Point::new(0., 2., 0.),
+            1.,
+            caps,
+            Box::new(Lambertian::random()),
+        )
+    }
+
+    #[test]
+    fn can_hit_side() {
+        let cylinder = pillar(true);
+        let ray = Ray::new(Point::new(0., 1.5, 5.), Point::new(0., 0., -1.), 0.);
+        let hit = cylinder.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.time - 4.).abs() < 1e-9);
+        assert!((hit.normal - Point::new(0., 0., 1.)).len() < 1e-9);
+        assert!((hit.uv.1 - 0.75).abs() < 1e-9);
+
+        let above = Ray::new(Point::new(0., 2.5, 5.), Point::new(0., 0., -1.), 0.);
+        assert!(cylinder.hit(&above, 0.001, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn can_hit_caps() {
+        let down = Ray::new(Point::new(0.5, 5., 0.), Point::new(0., -1., 0.), 0.);
+        let closed = pillar(true);
+        let hit = closed.hit(&down, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.time - 3.).abs() < 1e-9);
+        assert!((hit.normal - Point::new(0., 1., 0.)).len() < 1e-9);
+
+        // Without caps, the ray falls through the top and out of the bottom
+        let open = pillar(false);
+        assert!(open.hit(&down, 0.001, f64::INFINITY).is_none());
+        // But still sees the inside of the tube at an angle
+        let slanted = Ray::new(Point::new(0., 2.5, 0.), Point::new(0.5, -1., 0.), 0.);
+        let hit = open.hit(&slanted, 0.001, f64::INFINITY).unwrap();
+        assert!(!hit.front_face);
+        assert!((hit.point.x - 1.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn can_bound() {
+        let bounds = pillar(true).bounding_box().unwrap();
+        assert!((bounds.min - Point::new(-1., 0., -1.)).len() < 1e-3);
+        assert!((bounds.max - Point::new(1., 2., 1.)).len() < 1e-3);
+    }
+
+    #[test]
+    fn can_deserialize() {
+        let yaml = "type: Cylinder\nbase: {x: 0.0, y: 0.0, z: 0.0}\naxis: {x: 0.0, y: 2.0, z: 0.0}\nradius: 1.0\nmaterial:\n  type: Lambertian\n  albedo: {r: 0.5, g: 0.5, b: 0.5, a: 255}\n  probability: 1.0\n";
+        let shape: Box<dyn Hittable> = serde_yml::from_str(yaml).unwrap();
+        let down = Ray::new(Point::new(0., 5., 0.), Point::new(0., -1., 0.), 0.);
+        assert!(shape.hit(&down, 0.001, f64::INFINITY).is_some());
+    }
+}
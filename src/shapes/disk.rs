@@ -0,0 +1,143 @@
+use std::f64::consts::PI;
+
+use rand::Rng;
+
+use crate::{
+    materials::scatter::Material,
+    shapes::{
+        aabb::Aabb,
+        hit::{area_pdf, Hit, Hittable},
+        plane::Plane,
+    },
+    utilities::{point::Point, ray::Ray},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// A flat circle of `radius` around `center`, facing `normal`
+#[derive(Serialize, Deserialize)]
+pub struct Disk {
+    center: Point,
+    normal: Point,
+    radius: f64,
+    material: Material,
+}
+
+impl Disk {
+    #[allow(dead_code)]
+    pub fn new(center: Point, normal: Point, radius: f64, material: Material) -> Self {
+        Self {
+            center,
+            normal,
+            radius,
+            material,
+        }
+    }
+
+    fn area(&self) -> f64 {
+        PI * self.radius.powi(2)
+    }
+}
+
+#[typetag::serde]
+impl Hittable for Disk {
+    fn hit(&self, ray: &Ray, time_min: f64, time_max: f64) -> Option<Hit<'_>> {
+        let normal = self.normal.normalized();
+        let time = Plane::intersect(self.center, normal, ray, time_min, time_max)?;
+        let point = ray.at(time);
+        let offset = point - self.center;
+        if offset.len() > self.radius {
+            return None;
+        }
+
+        let mut hit = Hit::new(point, Point::origin(), &self.material, time, false);
+        hit.set_face_normal(ray, normal);
+        // Map the square around the disk onto the texture, like a label
+        let (u, v) = normal.orthonormal_basis();
+        hit.uv = (
+            (offset.dot(u) / self.radius + 1.) / 2.,
+            (offset.dot(v) / self.radius + 1.) / 2.,
+        );
+        Some(hit)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(Aabb::around_disk(self.center, self.normal, self.radius).pad(1e-4))
+    }
+
+    fn is_emitter(&self) -> bool {
+        !self.material.emit().is_black()
+    }
+
+    /// Picks a point uniformly over the disk's area
+    ///
+    /// https://pbr-book.org/3ed-2018/Monte_Carlo_Integration/2D_Sampling_with_Multidimensional_Transformations#SamplingaUnitDisk
+    fn sample(&self, origin: &Point, _time: f64) -> Option<Point> {
+        let mut rng = rand::thread_rng();
+        let distance = self.radius * rng.gen::<f64>().sqrt();
+        let angle = 2. * PI * rng.gen::<f64>();
+        let (u, v) = self.normal.normalized().orthonormal_basis();
+        let point = self.center + distance * (angle.cos() * u + angle.sin() * v);
+        Some((point - *origin).normalized())
+    }
+
+    fn pdf(&self, origin: &Point, direction: &Point, time: f64) -> f64 {
+        let direction = direction.normalized();
+        let Some(hit) = self.hit(&Ray::new(*origin, direction, time), 0.001, f64::INFINITY) else {
+            return 0.;
+        };
+        area_pdf(hit.time, hit.normal.dot(direction).abs(), self.area())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use crate::{
+        materials::{diffuse::Lambertian, light::Light, scatter::Scatter},
+        shapes::{disk::Disk, hit::Hittable},
+        utilities::{color::Color, point::Point, ray::Ray},
+    };
+
+    fn lamp(material: Box<dyn Scatter>) -> Disk {
+        Disk::new(
+            Point::new(0., 4., 0.),
+            Point::new(0., -1., 0.),
+            2.,
+            material,
+        )
+    }
+
+    #[test]
+    fn can_hit() {
+        let disk = lamp(Box::new(Lambertian::random()));
+        let ray = Ray::new(Point::origin(), Point::new(0., 1., 0.), 0.);
+        let hit = disk.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert_eq!(hit.time, 4.);
+        assert_eq!(hit.normal, Point::new(0., -1., 0.));
+        assert_eq!(hit.uv, (0.5, 0.5));
+
+        // Inside the square around the disk, but outside the circle
+        let corner = Ray::new(Point::new(1.6, 0., 1.6), Point::new(0., 1., 0.), 0.);
+        assert!(disk.hit(&corner, 0.001, f64::INFINITY).is_none());
+
+        let bounds = disk.bounding_box().unwrap();
+        assert!((bounds.max.x - 2.).abs() < 1e-3);
+        assert!(bounds.max.y - bounds.min.y < 1e-3);
+    }
+
+    #[test]
+    fn can_sample_light() {
+        let disk = lamp(Box::new(Light::new(Color::new(1., 1., 1., 255), 1.)));
+        assert!(disk.is_emitter());
+        let origin = Point::origin();
+        (0..100).for_each(|_| {
+            let direction = disk.sample(&origin, 0.).unwrap();
+            assert!(disk.pdf(&origin, &direction, 0.) > 0.);
+        });
+        let pdf = disk.pdf(&origin, &Point::new(0., 1., 0.), 0.);
+        assert!((pdf - 16. / (4. * PI)).abs() < 1e-9);
+        assert_eq!(disk.pdf(&origin, &Point::new(1., 0., 0.), 0.), 0.);
+    }
+}
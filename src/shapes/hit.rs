@@ -1,5 +1,6 @@
 use crate::{
     materials::scatter::Material,
+    shapes::{aabb::Aabb, instance::Library},
     utilities::{point::Point, ray::Ray},
 };
 
@@ -9,6 +10,11 @@ pub struct Hit<'a> {
     pub material: &'a Material,
     pub time: f64,
     pub front_face: bool,
+    /// Surface coordinates of `point`, each in 0..1, used to look up textures
+    pub uv: (f64, f64),
+    /// `point` in the coordinates of the shape that was hit, so that solid textures stay
+    /// attached to the shape however it is placed in the scene
+    pub local: Point,
 }
 
 impl<'a> Hit<'a> {
@@ -25,6 +31,8 @@ impl<'a> Hit<'a> {
             material,
             time,
             front_face,
+            uv: (0., 0.),
+            local: point,
         }
     }
 
@@ -37,7 +45,69 @@ impl<'a> Hit<'a> {
     }
 }
 
+/// A stretch of a ray inside a solid shape, from the hit where it goes in to the hit where it
+/// comes out
+///
+/// `enter` is `None` if the ray starts inside, and `exit` is `None` if it is still inside at the
+/// end of the range that was searched.
+pub struct Span<'a> {
+    pub enter: Option<Hit<'a>>,
+    pub exit: Option<Hit<'a>>,
+}
+
 #[typetag::serde(tag = "type")]
 pub trait Hittable: Send + Sync {
-    fn hit(&self, ray: &Ray, time_min: f64, time_max: f64) -> Option<Hit>;
+    fn hit(&self, ray: &Ray, time_min: f64, time_max: f64) -> Option<Hit<'_>>;
+    /// Box enclosing the shape over the whole shutter interval, or `None` if it is unbounded
+    fn bounding_box(&self) -> Option<Aabb>;
+    /// Whether the shape emits light and can be sampled directly with `sample`
+    fn is_emitter(&self) -> bool {
+        false
+    }
+    /// Pick a unit direction from `origin` towards a random point on the shape
+    fn sample(&self, _origin: &Point, _time: f64) -> Option<Point> {
+        None
+    }
+    /// Solid angle density of `sample` picking `direction` from `origin`
+    fn pdf(&self, _origin: &Point, _direction: &Point, _time: f64) -> f64 {
+        0.
+    }
+    /// Every stretch of `ray` inside the shape, in order, for combining solids with `Csg`
+    ///
+    /// By default, found by following the ray from hit to hit, where a hit on the front of a
+    /// face goes in and a hit on the back comes out, which holds for any closed shape.
+    fn spans(&self, ray: &Ray, time_min: f64, time_max: f64) -> Vec<Span<'_>> {
+        let mut spans = Vec::new();
+        let mut enter = None;
+        let mut time = time_min;
+        while let Some(hit) = self.hit(ray, time, time_max) {
+            // Step past the surface so the same hit is not found again, by a step that grows
+            // with the distance so it is not rounded away far along grazing rays
+            let next = hit.time + (hit.time.abs() * 1e-9).max(1e-6);
+            assert!(
+                next > hit.time,
+                "Spans must move forward along the ray, stuck at {next}"
+            );
+            time = next;
+            match hit.front_face {
+                true => enter = Some(hit),
+                false => spans.push(Span {
+                    enter: enter.take(),
+                    exit: Some(hit),
+                }),
+            }
+        }
+        if enter.is_some() {
+            spans.push(Span { enter, exit: None });
+        }
+        spans
+    }
+    /// Resolve references to the scene's shared `shapes`, before the scene is rendered
+    fn link(&mut self, _shapes: &Library) {}
+}
+
+/// Convert a density spread uniformly over `area` to solid angle, for a point `distance` away
+/// whose normal is at `cosine` to the direction towards it
+pub fn area_pdf(distance: f64, cosine: f64, area: f64) -> f64 {
+    distance.powi(2) / (cosine * area)
 }
@@ -0,0 +1,122 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use crate::{
+    shapes::{
+        aabb::Aabb,
+        hit::{Hit, Hittable},
+        transform::Placement,
+    },
+    utilities::{matrix::Matrix, point::Point, ray::Ray},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Shapes defined once in the scene file and shared by every `Instance` that names them
+pub type Library = BTreeMap<String, Arc<dyn Hittable>>;
+
+/// A copy of a shape from the scene's `shapes`, placed without duplicating its geometry
+///
+/// Instances are resolved by name when the scene is loaded, and do not hit anything before.
+#[derive(Serialize, Deserialize)]
+pub struct Instance {
+    /// Name of the shared shape
+    shape: String,
+    #[serde(flatten)]
+    placement: Placement,
+    #[serde(skip)]
+    target: Option<Arc<dyn Hittable>>,
+}
+
+impl Instance {
+    #[allow(dead_code)]
+    pub fn new(shape: &str, matrix: Matrix) -> Self {
+        Self {
+            shape: shape.to_string(),
+            placement: Placement::new(matrix),
+            target: None,
+        }
+    }
+}
+
+#[typetag::serde]
+impl Hittable for Instance {
+    fn hit(&self, ray: &Ray, time_min: f64, time_max: f64) -> Option<Hit<'_>> {
+        let target = self.target.as_deref()?;
+        self.placement.hit(target, ray, time_min, time_max)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.placement.bounding_box(self.target.as_deref()?)
+    }
+
+    fn is_emitter(&self) -> bool {
+        self.target
+            .as_ref()
+            .is_some_and(|target| target.is_emitter())
+    }
+
+    fn sample(&self, origin: &Point, time: f64) -> Option<Point> {
+        self.placement.sample(self.target.as_deref()?, origin, time)
+    }
+
+    fn pdf(&self, origin: &Point, direction: &Point, time: f64) -> f64 {
+        match self.target.as_deref() {
+            Some(target) => self.placement.pdf(target, origin, direction, time),
+            None => 0.,
+        }
+    }
+
+    fn link(&mut self, shapes: &Library) {
+        let target = shapes
+            .get(&self.shape)
+            .unwrap_or_else(|| panic!("Unable to find shape `{}` for instance", self.shape));
+        self.target = Some(Arc::clone(target));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        materials::{diffuse::Lambertian, scatter::Scatter},
+        shapes::{
+            hit::Hittable,
+            instance::{Instance, Library},
+            mesh::Mesh,
+        },
+        utilities::{matrix::Matrix, point::Point, ray::Ray},
+    };
+
+    fn library() -> Library {
+        let pyramid = Mesh::new("scenes/models/pyramid.obj", Box::new(Lambertian::random()));
+        Library::from([(
+            "pyramid".to_string(),
+            Arc::new(pyramid) as Arc<dyn Hittable>,
+        )])
+    }
+
+    #[test]
+    fn can_share_geometry() {
+        let library = library();
+        let mut left = Instance::new("pyramid", Matrix::translation(Point::new(-5., 0., 0.)));
+        let mut right = Instance::new("pyramid", Matrix::translation(Point::new(5., 0., 0.)));
+        assert!(left.bounding_box().is_none());
+        left.link(&library);
+        right.link(&library);
+        // Both instances and the library hold the same mesh
+        assert_eq!(Arc::strong_count(&library["pyramid"]), 3);
+
+        let ray = Ray::new(Point::new(5., 0.5, 5.), Point::new(0., 0., -1.), 0.);
+        assert!(right.hit(&ray, 0.001, f64::INFINITY).is_some());
+        assert!(left.hit(&ray, 0.001, f64::INFINITY).is_none());
+        let bounds = left.bounding_box().unwrap();
+        assert!(bounds.max.x <= -3.9 && bounds.min.x >= -6.1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unable to find shape `cube`")]
+    fn can_reject_unknown_shape() {
+        Instance::new("cube", Matrix::identity()).link(&library());
+    }
+}
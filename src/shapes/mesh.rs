@@ -0,0 +1,355 @@
+use std::{collections::BTreeMap, path::Path};
+
+use crate::{
+    materials::{
+        diffuse::Lambertian, glass::Dielectric, light::Light, metal::Metal, scatter::Material,
+    },
+    shapes::{
+        aabb::Aabb,
+        bvh::Bvh,
+        hit::{Hit, Hittable},
+        triangle::Triangle,
+    },
+    textures::{image::ImageTexture, texture::Texture},
+    utilities::{color::Color, point::Point, ray::Ray},
+};
+
+use serde::{Deserialize, Serialize, Serializer};
+
+/// Where a face gets its material from
+#[derive(Clone, Copy)]
+enum Surface {
+    /// The mesh's default material
+    Default,
+    /// A material from the scene file that replaces an `.mtl` material
+    Override(usize),
+    /// A material converted from the `.mtl` file
+    Loaded(usize),
+}
+
+/// A triangle that references the vertices and material of its mesh
+struct Face {
+    vertices: [usize; 3],
+    surface: Surface,
+}
+
+/// Scene file representation of a mesh; the geometry is loaded from `path`
+#[derive(Deserialize)]
+struct MeshFile {
+    path: String,
+    material: Material,
+    #[serde(default)]
+    overrides: BTreeMap<String, Material>,
+}
+
+/// A triangle mesh loaded from a Wavefront `.obj` file
+///
+/// Faces share materials instead of each owning a copy. Materials come from the `.mtl` file
+/// referenced by the model, unless the scene overrides them by name; faces without a material
+/// use `material`.
+#[derive(Serialize, Deserialize)]
+#[serde(from = "MeshFile")]
+pub struct Mesh {
+    /// Path to the `.obj` file, relative to the working directory
+    path: String,
+    /// Material for faces that do not have one in the `.mtl` file
+    material: Material,
+    /// Materials used instead of the `.mtl` materials with the same name
+    #[serde(
+        serialize_with = "serialize_overrides",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    overrides: Vec<(String, Material)>,
+    #[serde(skip_serializing)]
+    positions: Vec<Point>,
+    /// Per-vertex shading normals, empty if the model does not define them
+    #[serde(skip_serializing)]
+    normals: Vec<Point>,
+    /// Per-vertex texture coordinates, empty if the model does not define them
+    #[serde(skip_serializing)]
+    texcoords: Vec<(f64, f64)>,
+    #[serde(skip_serializing)]
+    faces: Vec<Face>,
+    /// Materials converted from the `.mtl` file
+    #[serde(skip_serializing)]
+    loaded: Vec<Material>,
+    #[serde(skip_serializing)]
+    bvh: Bvh,
+}
+
+fn serialize_overrides<S>(
+    overrides: &[(String, Material)],
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.collect_map(overrides.iter().map(|(name, material)| (name, material)))
+}
+
+impl From<MeshFile> for Mesh {
+    fn from(file: MeshFile) -> Self {
+        Mesh::load(&file.path, file.material, file.overrides)
+    }
+}
+
+impl Mesh {
+    #[allow(dead_code)]
+    pub fn new(path: &str, material: Material) -> Self {
+        Mesh::load(path, material, BTreeMap::new())
+    }
+
+    /// Read the `.obj` file at `path` and its materials, building a hierarchy over the faces
+    pub fn load(path: &str, material: Material, overrides: BTreeMap<String, Material>) -> Self {
+        let options = tobj::LoadOptions {
+            single_index: true,
+            triangulate: true,
+            ..Default::default()
+        };
+        let (models, mtl_materials) = tobj::load_obj(path, &options)
+            .unwrap_or_else(|error| panic!("Unable to load mesh {path}: {error}"));
+        // A missing or broken `.mtl` file leaves faces with the default material
+        let mtl_materials = mtl_materials.unwrap_or_default();
+
+        let overrides: Vec<(String, Material)> = overrides.into_iter().collect();
+        let surfaces: Vec<Surface> = mtl_materials
+            .iter()
+            .enumerate()
+            .map(|(index, mtl)| {
+                overrides
+                    .iter()
+                    .position(|(name, _)| *name == mtl.name)
+                    .map_or(Surface::Loaded(index), Surface::Override)
+            })
+            .collect();
+        // Texture images are found relative to the model
+        let directory = Path::new(path).parent().unwrap_or(Path::new(""));
+        let loaded = mtl_materials
+            .iter()
+            .map(|mtl| Self::convert_material(mtl, directory))
+            .collect();
+
+        let mut positions = vec![];
+        let mut normals = vec![];
+        let mut texcoords = vec![];
+        let mut faces = vec![];
+        models.iter().for_each(|model| {
+            let mesh = &model.mesh;
+            let offset = positions.len();
+            let surface = mesh
+                .material_id
+                .and_then(|id| surfaces.get(id).copied())
+                .unwrap_or(Surface::Default);
+
+            positions.extend(mesh.positions.chunks_exact(3).map(to_point));
+            // Shading normals are only usable if every vertex has one
+            if mesh.normals.len() == mesh.positions.len() {
+                normals.extend(mesh.normals.chunks_exact(3).map(to_point));
+            }
+            if mesh.texcoords.len() / 2 == mesh.positions.len() / 3 {
+                texcoords.extend(
+                    mesh.texcoords
+                        .chunks_exact(2)
+                        .map(|uv| (uv[0] as f64, uv[1] as f64)),
+                );
+            }
+            faces.extend(mesh.indices.chunks_exact(3).map(|face| Face {
+                vertices: [
+                    offset + face[0] as usize,
+                    offset + face[1] as usize,
+                    offset + face[2] as usize,
+                ],
+                surface,
+            }));
+        });
+        if normals.len() != positions.len() {
+            normals.clear();
+        }
+        if texcoords.len() != positions.len() {
+            texcoords.clear();
+        }
+
+        let mut mesh = Mesh {
+            path: path.to_string(),
+            material,
+            overrides,
+            positions,
+            normals,
+            texcoords,
+            faces,
+            loaded,
+            bvh: Bvh::default(),
+        };
+        mesh.bvh =
+            Bvh::from_bounds((0..mesh.faces.len()).map(|index| Some(mesh.face_bounds(index))));
+        mesh
+    }
+
+    /// Convert an `.mtl` material to the closest built-in material
+    ///
+    /// - Emissive (`Ke`) materials become `Light`
+    /// - Transparent (`d < 1`) or refractive (`illum` 4, 6, 7, 9) materials become `Dielectric`
+    /// - Reflective (`illum` 3, 5, 8) materials become `Metal`, with `Ns` controlling the matte
+    /// - Anything else becomes `Lambertian` using the diffuse color, or the diffuse texture
+    ///   (`map_Kd`) found relative to `directory`
+    fn convert_material(mtl: &tobj::Material, directory: &Path) -> Material {
+        let color = |rgb: [f32; 3]| Color::rgb(rgb[0] as f64, rgb[1] as f64, rgb[2] as f64);
+        let diffuse = mtl.diffuse.map_or(Color::gray(0.8), color);
+
+        let emissive = mtl.emissive.map(color).filter(|emit| !emit.is_black());
+        if let Some(emit) = emissive {
+            let intensity = emit.max_channel();
+            return Box::new(Light::new(emit * (1. / intensity), intensity));
+        }
+
+        let illumination = mtl.illumination_model.unwrap_or(2);
+        if mtl.dissolve.is_some_and(|dissolve| dissolve < 1.)
+            || matches!(illumination, 4 | 6 | 7 | 9)
+        {
+            let refraction_index = mtl.optical_density.map_or(1.5, |index| index as f64);
+            return Box::new(Dielectric::new(Color::gray(1.).into(), refraction_index));
+        }
+
+        if matches!(illumination, 3 | 5 | 8) {
+            let albedo = mtl.specular.map_or(diffuse, color);
+            // Phong exponents range from 0 to 1000; map them to a roughness-like matte
+            let shininess = mtl.shininess.map_or(0., |shininess| shininess as f64);
+            let matte = (2. / (shininess + 2.)).sqrt();
+            return Box::new(Metal::new(albedo.into(), matte));
+        }
+
+        let albedo: Box<dyn Texture> = match &mtl.diffuse_texture {
+            Some(texture) => {
+                let path = directory.join(texture);
+                Box::new(ImageTexture::new(path.to_str().unwrap(), (1., 1.)))
+            }
+            None => diffuse.into(),
+        };
+        Box::new(Lambertian::new(albedo, 1.))
+    }
+
+    fn face_bounds(&self, index: usize) -> Aabb {
+        let [a, b, c] = self.faces[index].vertices;
+        Aabb::new(self.positions[a], self.positions[b])
+            .grow(self.positions[c])
+            .pad(1e-4)
+    }
+
+    fn surface_material(&self, surface: Surface) -> &Material {
+        match surface {
+            Surface::Default => &self.material,
+            Surface::Override(index) => &self.overrides[index].1,
+            Surface::Loaded(index) => &self.loaded[index],
+        }
+    }
+
+    fn hit_face(&self, index: usize, ray: &Ray, time_min: f64, time_max: f64) -> Option<Hit<'_>> {
+        let face = &self.faces[index];
+        let [a, b, c] = face.vertices.map(|vertex| self.positions[vertex]);
+        let (time, u, v) = Triangle::intersect(a, b, c, ray, time_min, time_max)?;
+
+        let outward_normal = Triangle::normal(a, b, c);
+        let mut hit = Hit::new(
+            ray.at(time),
+            outward_normal,
+            self.surface_material(face.surface),
+            time,
+            false,
+        );
+        hit.set_face_normal(ray, outward_normal);
+        hit.uv = match self.texcoords.is_empty() {
+            true => (u, v),
+            false => {
+                Triangle::interpolate_uv(face.vertices.map(|vertex| self.texcoords[vertex]), u, v)
+            }
+        };
+
+        // Interpolate vertex normals for smooth shading, keeping the side chosen above
+        if !self.normals.is_empty() {
+            let [n_a, n_b, n_c] = face.vertices.map(|vertex| self.normals[vertex]);
+            let shading_normal = ((1. - u - v) * n_a + u * n_b + v * n_c).normalized();
+            hit.normal = match hit.front_face {
+                true => shading_normal,
+                false => shading_normal * -1.,
+            };
+        }
+
+        Some(hit)
+    }
+}
+
+fn to_point(xyz: &[f32]) -> Point {
+    Point::new(xyz[0] as f64, xyz[1] as f64, xyz[2] as f64)
+}
+
+#[typetag::serde]
+impl Hittable for Mesh {
+    fn hit(&self, ray: &Ray, time_min: f64, time_max: f64) -> Option<Hit<'_>> {
+        self.bvh
+            .traverse(ray, time_min, time_max, |index, closest_so_far| {
+                self.hit_face(index, ray, time_min, closest_so_far)
+            })
+            .map(|(_, hit)| hit)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some((0..self.faces.len()).fold(Aabb::empty(), |bounds, index| {
+            bounds.union(&self.face_bounds(index))
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        materials::{diffuse::Lambertian, scatter::Scatter},
+        shapes::{hit::Hittable, mesh::Mesh},
+        utilities::{point::Point, ray::Ray},
+    };
+
+    const PYRAMID: &str = "scenes/models/pyramid.obj";
+
+    #[test]
+    fn can_load() {
+        let mesh = Mesh::new(PYRAMID, Box::new(Lambertian::random()));
+        assert_eq!(mesh.faces.len(), 6);
+        assert_eq!(mesh.normals.len(), 0);
+        assert_eq!(mesh.loaded.len(), 2);
+    }
+
+    #[test]
+    fn can_get_bounding_box() {
+        let mesh = Mesh::new(PYRAMID, Box::new(Lambertian::random()));
+        let bounds = mesh.bounding_box().unwrap();
+        assert!(bounds.min.x <= -1. && bounds.min.y <= 0. && bounds.min.z <= -1.);
+        assert!(bounds.max.x >= 1. && bounds.max.y >= 1.5 && bounds.max.z >= 1.);
+    }
+
+    #[test]
+    fn can_hit() {
+        let mesh = Mesh::new(PYRAMID, Box::new(Lambertian::random()));
+        let ray = Ray::new(Point::new(0., 0.5, 5.), Point::new(0., 0., -1.), 0.);
+        let hit = mesh.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!(hit.front_face);
+        assert!(hit.point.z > 0. && hit.point.z < 1.);
+    }
+
+    #[test]
+    fn can_miss() {
+        let mesh = Mesh::new(PYRAMID, Box::new(Lambertian::random()));
+        let ray = Ray::new(Point::new(0., 2., 5.), Point::new(0., 0., -1.), 0.);
+        assert!(mesh.hit(&ray, 0.001, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn can_round_trip() {
+        let scene = format!(
+            "type: Mesh\npath: {PYRAMID}\nmaterial:\n  type: Mirror\n  albedo: {{r: 1.0, g: 1.0, b: 1.0, a: 255}}\noverrides:\n  Stone:\n    type: Mirror\n    albedo: {{r: 0.5, g: 0.5, b: 0.5, a: 255}}\n"
+        );
+        let shape: Box<dyn Hittable> = serde_yml::from_str(&scene).unwrap();
+        let saved = serde_yml::to_string(&shape).unwrap();
+        assert!(saved.contains("path: scenes/models/pyramid.obj"));
+        assert!(saved.contains("Stone:"));
+        assert!(!saved.contains("positions"));
+    }
+}
@@ -1,5 +1,20 @@
+pub mod aabb;
+pub mod bvh;
+pub mod capsule;
+pub mod cone;
+pub mod csg;
+pub mod cuboid;
+pub mod cylinder;
+pub mod disk;
 pub mod hit;
+pub mod instance;
+pub mod mesh;
+pub mod plane;
+pub mod quad;
 pub mod sphere;
+pub mod torus;
+pub mod transform;
+pub mod volume;
 pub mod world;
 pub mod triangle;
 
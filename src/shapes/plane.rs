@@ -0,0 +1,159 @@
+use std::f64::consts::PI;
+
+use rand::Rng;
+
+use crate::{
+    materials::scatter::Material,
+    shapes::{
+        aabb::Aabb,
+        hit::{Hit, Hittable},
+    },
+    utilities::{point::Point, ray::Ray},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// A flat surface through `point` facing `normal`, stretching forever in every direction
+#[derive(Serialize, Deserialize)]
+pub struct Plane {
+    point: Point,
+    normal: Point,
+    material: Material,
+}
+
+impl Plane {
+    #[allow(dead_code)]
+    pub fn new(point: Point, normal: Point, material: Material) -> Self {
+        Self {
+            point,
+            normal,
+            material,
+        }
+    }
+
+    /// Distance along `ray` to the plane through `point` facing `normal`, or `None` if the ray
+    /// runs parallel to the plane or meets it outside of `time_min..time_max`
+    pub fn intersect(
+        point: Point,
+        normal: Point,
+        ray: &Ray,
+        time_min: f64,
+        time_max: f64,
+    ) -> Option<f64> {
+        let denominator = normal.dot(ray.direction);
+        if denominator.abs() < 1e-12 {
+            return None;
+        }
+        let time = normal.dot(point - ray.origin) / denominator;
+        (time_min..=time_max).contains(&time).then_some(time)
+    }
+}
+
+#[typetag::serde]
+impl Hittable for Plane {
+    fn hit(&self, ray: &Ray, time_min: f64, time_max: f64) -> Option<Hit<'_>> {
+        let normal = self.normal.normalized();
+        let time = Self::intersect(self.point, normal, ray, time_min, time_max)?;
+
+        let mut hit = Hit::new(ray.at(time), Point::origin(), &self.material, time, false);
+        hit.set_face_normal(ray, normal);
+        // Textures repeat across squares one unit wide
+        let (u, v) = normal.orthonormal_basis();
+        let offset = hit.point - self.point;
+        hit.uv = (offset.dot(u).rem_euclid(1.), offset.dot(v).rem_euclid(1.));
+        Some(hit)
+    }
+
+    /// No box can hold an infinite plane
+    fn bounding_box(&self) -> Option<Aabb> {
+        None
+    }
+
+    fn is_emitter(&self) -> bool {
+        !self.material.emit().is_black()
+    }
+
+    /// Picks a direction towards the plane, favoring those close to straight on
+    ///
+    /// Every direction on the plane's side of `origin` reaches it, so a cosine weighted
+    /// hemisphere covers the plane exactly.
+    fn sample(&self, origin: &Point, _time: f64) -> Option<Point> {
+        let normal = self.normal.normalized();
+        let height = normal.dot(self.point - *origin);
+        if height.abs() < 1e-12 {
+            return None;
+        }
+        let axis = normal * height.signum();
+
+        let mut rng = rand::thread_rng();
+        let phi = 2. * PI * rng.gen::<f64>();
+        let sin_squared = rng.gen::<f64>();
+        let (sin_theta, cos_theta) = (sin_squared.sqrt(), (1. - sin_squared).sqrt());
+        let (u, v) = axis.orthonormal_basis();
+        Some(sin_theta * phi.cos() * u + sin_theta * phi.sin() * v + cos_theta * axis)
+    }
+
+    fn pdf(&self, origin: &Point, direction: &Point, time: f64) -> f64 {
+        let normal = self.normal.normalized();
+        let direction = direction.normalized();
+        let ray = Ray::new(*origin, direction, time);
+        match Self::intersect(self.point, normal, &ray, 0.001, f64::INFINITY) {
+            Some(_) => normal.dot(direction).abs() / PI,
+            None => 0.,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        materials::{diffuse::Lambertian, light::Light, scatter::Scatter},
+        shapes::{hit::Hittable, plane::Plane},
+        utilities::{color::Color, point::Point, ray::Ray},
+    };
+
+    fn floor(material: Box<dyn Scatter>) -> Plane {
+        Plane::new(Point::origin(), Point::new(0., 2., 0.), material)
+    }
+
+    #[test]
+    fn can_hit() {
+        let plane = floor(Box::new(Lambertian::random()));
+        let ray = Ray::new(Point::new(100., 3., -40.), Point::new(0., -1., 0.), 0.);
+        let hit = plane.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert_eq!(hit.time, 3.);
+        assert_eq!(hit.normal, Point::new(0., 1., 0.));
+        assert!((0. ..1.).contains(&hit.uv.0) && (0. ..1.).contains(&hit.uv.1));
+
+        let parallel = Ray::new(Point::new(0., 1., 0.), Point::new(1., 0., 0.), 0.);
+        assert!(plane.hit(&parallel, 0.001, f64::INFINITY).is_none());
+        let away = Ray::new(Point::new(0., 1., 0.), Point::new(0., 1., 0.), 0.);
+        assert!(plane.hit(&away, 0.001, f64::INFINITY).is_none());
+        assert!(plane.bounding_box().is_none());
+    }
+
+    #[test]
+    fn can_sample_light() {
+        let plane = floor(Box::new(Light::new(Color::new(1., 1., 1., 255), 1.)));
+        assert!(plane.is_emitter());
+        let origin = Point::new(0., 5., 0.);
+        (0..100).for_each(|_| {
+            let direction = plane.sample(&origin, 0.).unwrap();
+            assert!(direction.y < 0.);
+            let pdf = plane.pdf(&origin, &direction, 0.);
+            assert!((pdf - -direction.y / std::f64::consts::PI).abs() < 1e-9);
+        });
+        assert_eq!(plane.pdf(&origin, &Point::new(0., 1., 0.), 0.), 0.);
+    }
+
+    #[test]
+    fn can_span_grazing_ray() {
+        let plane = floor(Box::new(Lambertian::random()));
+        // Meets the plane so far away that a small fixed step past the hit rounds to nothing
+        let ray = Ray::new(Point::new(0., 1., 0.), Point::new(1., -1e-11, 0.), 0.);
+        let spans = plane.spans(&ray, 0.001, f64::INFINITY);
+        assert_eq!(spans.len(), 1);
+        assert!(spans[0].enter.as_ref().unwrap().time > 1e10);
+        assert!(spans[0].exit.is_none());
+    }
+}
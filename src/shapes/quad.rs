@@ -0,0 +1,140 @@
+use rand::Rng;
+
+use crate::{
+    materials::scatter::Material,
+    shapes::{
+        aabb::Aabb,
+        hit::{area_pdf, Hit, Hittable},
+        plane::Plane,
+    },
+    utilities::{point::Point, ray::Ray},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// A parallelogram with a corner at `origin` and sides `u` and `v`, facing `u × v`
+#[derive(Serialize, Deserialize)]
+pub struct Quad {
+    origin: Point,
+    u: Point,
+    v: Point,
+    material: Material,
+}
+
+impl Quad {
+    #[allow(dead_code)]
+    pub fn new(origin: Point, u: Point, v: Point, material: Material) -> Self {
+        Self {
+            origin,
+            u,
+            v,
+            material,
+        }
+    }
+
+    fn area(&self) -> f64 {
+        self.u.cross(self.v).len()
+    }
+}
+
+#[typetag::serde]
+impl Hittable for Quad {
+    /// Hits the plane the quad lies in, then checks the hit's position along each side
+    ///
+    /// https://raytracing.github.io/books/RayTracingTheNextWeek.html#quadrilaterals
+    fn hit(&self, ray: &Ray, time_min: f64, time_max: f64) -> Option<Hit<'_>> {
+        let n = self.u.cross(self.v);
+        let time = Plane::intersect(self.origin, n, ray, time_min, time_max)?;
+        let point = ray.at(time);
+
+        let w = n / n.dot(n);
+        let offset = point - self.origin;
+        let alpha = w.dot(offset.cross(self.v));
+        let beta = w.dot(self.u.cross(offset));
+        if !(0. ..=1.).contains(&alpha) || !(0. ..=1.).contains(&beta) {
+            return None;
+        }
+
+        let mut hit = Hit::new(point, Point::origin(), &self.material, time, false);
+        hit.set_face_normal(ray, n.normalized());
+        hit.uv = (alpha, beta);
+        Some(hit)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let far = self.origin + self.u + self.v;
+        Some(
+            Aabb::new(self.origin, far)
+                .grow(self.origin + self.u)
+                .grow(self.origin + self.v)
+                .pad(1e-4),
+        )
+    }
+
+    fn is_emitter(&self) -> bool {
+        !self.material.emit().is_black()
+    }
+
+    /// Picks a point uniformly over the quad's area
+    fn sample(&self, origin: &Point, _time: f64) -> Option<Point> {
+        let mut rng = rand::thread_rng();
+        let point = self.origin + rng.gen::<f64>() * self.u + rng.gen::<f64>() * self.v;
+        Some((point - *origin).normalized())
+    }
+
+    fn pdf(&self, origin: &Point, direction: &Point, time: f64) -> f64 {
+        let direction = direction.normalized();
+        let Some(hit) = self.hit(&Ray::new(*origin, direction, time), 0.001, f64::INFINITY) else {
+            return 0.;
+        };
+        area_pdf(hit.time, hit.normal.dot(direction).abs(), self.area())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        materials::{diffuse::Lambertian, light::Light, scatter::Scatter},
+        shapes::{hit::Hittable, quad::Quad},
+        utilities::{color::Color, point::Point, ray::Ray},
+    };
+
+    fn wall(material: Box<dyn Scatter>) -> Quad {
+        Quad::new(
+            Point::new(-1., 0., -2.),
+            Point::new(4., 0., 0.),
+            Point::new(0., 2., 0.),
+            material,
+        )
+    }
+
+    #[test]
+    fn can_hit() {
+        let quad = wall(Box::new(Lambertian::random()));
+        let ray = Ray::new(Point::new(0., 1.5, 0.), Point::new(0., 0., -1.), 0.);
+        let hit = quad.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert_eq!(hit.time, 2.);
+        assert_eq!(hit.normal, Point::new(0., 0., 1.));
+        assert_eq!(hit.uv, (0.25, 0.75));
+
+        let beside = Ray::new(Point::new(3.5, 1., 0.), Point::new(0., 0., -1.), 0.);
+        assert!(quad.hit(&beside, 0.001, f64::INFINITY).is_none());
+        let bounds = quad.bounding_box().unwrap();
+        assert!(bounds.min.z < -2. && bounds.max.z > -2.);
+    }
+
+    #[test]
+    fn can_sample_light() {
+        let quad = wall(Box::new(Light::new(Color::new(1., 1., 1., 255), 1.)));
+        assert!(quad.is_emitter());
+        let origin = Point::new(0., 1., 0.);
+        (0..100).for_each(|_| {
+            let direction = quad.sample(&origin, 0.).unwrap();
+            assert!(quad.pdf(&origin, &direction, 0.) > 0.);
+        });
+        // Straight on, the density is the squared distance over the area
+        let pdf = quad.pdf(&origin, &Point::new(0., 0., -1.), 0.);
+        assert!((pdf - 4. / 8.).abs() < 1e-9);
+        assert_eq!(quad.pdf(&origin, &Point::new(0., 0., 1.), 0.), 0.);
+    }
+}
@@ -1,6 +1,13 @@
+use std::f64::consts::PI;
+
+use rand::Rng;
+
 use crate::{
     materials::scatter::Material,
-    shapes::hit::{Hit, Hittable},
+    shapes::{
+        aabb::Aabb,
+        hit::{area_pdf, Hit, Hittable},
+    },
     utilities::{point::Point, ray::Ray},
 };
 
@@ -42,11 +49,27 @@ impl Sphere {
         self.center_t_0
             + ((time - self.t_0) / (self.t_1 - self.t_0) * (self.center_t_1 - self.center_t_0))
     }
+
+    /// Texture coordinates of a point on the unit sphere, with `u` going around the y axis
+    /// starting from `-x` and `v` going from the bottom to the top
+    fn uv(point: &Point) -> (f64, f64) {
+        let theta = (-point.y).clamp(-1., 1.).acos();
+        let phi = (-point.z).atan2(point.x) + PI;
+        (phi / (2. * PI), theta / PI)
+    }
+
+    /// Cosine of the half angle of the cone the sphere covers as seen from `origin`, or
+    /// `None` if `origin` is inside the sphere
+    fn cos_theta_max(&self, origin: &Point, time: f64) -> Option<f64> {
+        let distance_squared = (self.center(time) - *origin).len().powi(2);
+        let sin_theta_max_squared = self.radius.powi(2) / distance_squared;
+        (sin_theta_max_squared < 1.).then(|| (1. - sin_theta_max_squared).sqrt())
+    }
 }
 
 #[typetag::serde]
 impl Hittable for Sphere {
-    fn hit(&self, ray: &Ray, time_min: f64, time_max: f64) -> Option<Hit> {
+    fn hit(&self, ray: &Ray, time_min: f64, time_max: f64) -> Option<Hit<'_>> {
         let oc = ray.origin - self.center(ray.time);
         let a = ray.direction.len().powi(2);
         let half_b = oc.dot(ray.direction);
@@ -70,21 +93,71 @@ impl Hittable for Sphere {
         let mut hit = Hit::new(ray.at(root), Point::origin(), &self.material, root, false);
         let outward_normal = (hit.point - self.center(ray.time)) / self.radius;
         hit.set_face_normal(ray, outward_normal);
+        hit.uv = Self::uv(&((hit.point - self.center(ray.time)) / self.radius.abs()));
         Some(hit)
     }
+
+    /// Encloses the sphere at both ends of its motion, which covers every position in between
+    fn bounding_box(&self) -> Option<Aabb> {
+        let radius = Point::new(self.radius, self.radius, self.radius);
+        let box_t_0 = Aabb::new(self.center_t_0 - radius, self.center_t_0 + radius);
+        let box_t_1 = Aabb::new(self.center_t_1 - radius, self.center_t_1 + radius);
+        Some(box_t_0.union(&box_t_1))
+    }
+
+    fn is_emitter(&self) -> bool {
+        !self.material.emit().is_black()
+    }
+
+    /// Samples the cone of directions the sphere covers, or its whole surface from inside
+    ///
+    /// https://pbr-book.org/3ed-2018/Light_Transport_I_Surface_Reflection/Sampling_Light_Sources#SamplingSpheres
+    fn sample(&self, origin: &Point, time: f64) -> Option<Point> {
+        let center = self.center(time);
+        let Some(cos_theta_max) = self.cos_theta_max(origin, time) else {
+            let point = center + self.radius * Point::random_in_sphere().normalized();
+            return Some((point - *origin).normalized());
+        };
+
+        let mut rng = rand::thread_rng();
+        let cos_theta = 1. + rng.gen::<f64>() * (cos_theta_max - 1.);
+        let sin_theta = (1. - cos_theta.powi(2)).max(0.).sqrt();
+        let phi = 2. * PI * rng.gen::<f64>();
+
+        let axis = (center - *origin).normalized();
+        let (u, v) = axis.orthonormal_basis();
+        Some(sin_theta * phi.cos() * u + sin_theta * phi.sin() * v + cos_theta * axis)
+    }
+
+    fn pdf(&self, origin: &Point, direction: &Point, time: f64) -> f64 {
+        let direction = direction.normalized();
+        let Some(hit) = self.hit(&Ray::new(*origin, direction, time), 0.001, f64::INFINITY) else {
+            return 0.;
+        };
+        match self.cos_theta_max(origin, time) {
+            Some(cos_theta_max) => 1. / (2. * PI * (1. - cos_theta_max)),
+            // Convert the uniform area density to solid angle at the point that was hit
+            None => {
+                let area = 4. * PI * self.radius.powi(2);
+                area_pdf(hit.time, hit.normal.dot(direction).abs(), area)
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod stationary_tests {
+    use std::f64::consts::PI;
+
     use crate::{
-        materials::diffuse::Lambertian,
+        materials::{diffuse::Lambertian, light::Light},
         shapes::{hit::Hittable, sphere::Sphere},
         utilities::{color::Color, point::Point, ray::Ray},
     };
 
     #[test]
     fn can_create() {
-        let mat = Lambertian::new(Color::random(), 1.0);
+        let mat = Lambertian::new(Color::random().into(), 1.0);
         let s = Sphere::new(Point::origin(), Point::origin(), 0., 0., 1.0, Box::new(mat));
         assert_eq!(s.radius, 1.0);
         assert_eq!(s.center_t_0.x, Point::default().x);
@@ -94,7 +167,7 @@ mod stationary_tests {
 
     #[test]
     fn can_hit() {
-        let mat = Lambertian::new(Color::random(), 1.0);
+        let mat = Lambertian::new(Color::random().into(), 1.0);
         let s = Sphere::new(Point::origin(), Point::origin(), 0., 0., 1.0, Box::new(mat));
         assert!(s
             .hit(
@@ -107,7 +180,7 @@ mod stationary_tests {
 
     #[test]
     fn can_miss() {
-        let mat = Lambertian::new(Color::random(), 1.0);
+        let mat = Lambertian::new(Color::random().into(), 1.0);
         let s = Sphere::new(Point::origin(), Point::origin(), 0., 0., 1.0, Box::new(mat));
         assert!(s
             .hit(
@@ -117,4 +190,112 @@ mod stationary_tests {
             )
             .is_none());
     }
+
+    #[test]
+    fn can_get_uv() {
+        assert_eq!(Sphere::uv(&Point::new(1., 0., 0.)), (0.5, 0.5));
+        assert_eq!(Sphere::uv(&Point::new(0., 1., 0.)).1, 1.);
+        assert_eq!(Sphere::uv(&Point::new(0., -1., 0.)).1, 0.);
+        assert_eq!(Sphere::uv(&Point::new(0., 0., 1.)), (0.25, 0.5));
+
+        let s = Sphere::new(
+            Point::new(0., 0., -3.),
+            Point::new(0., 0., -3.),
+            0.,
+            1.,
+            -2.,
+            Box::new(Lambertian::new(Color::random().into(), 1.0)),
+        );
+        let ray = Ray::new(Point::origin(), Point::new(0., 0., -1.), 0.);
+        let hit = s.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        // Hollow spheres map their surface the same way as solid ones
+        assert_eq!(hit.uv, (0.25, 0.5));
+    }
+
+    #[test]
+    fn can_get_bounding_box() {
+        let mat = Lambertian::new(Color::random().into(), 1.0);
+        let s = Sphere::new(
+            Point::new(1., 2., 3.),
+            Point::new(1., 2., 3.),
+            0.,
+            1.,
+            2.0,
+            Box::new(mat),
+        );
+        let bounds = s.bounding_box().unwrap();
+        assert_eq!(bounds.min, Point::new(-1., 0., 1.));
+        assert_eq!(bounds.max, Point::new(3., 4., 5.));
+    }
+
+    #[test]
+    fn can_find_emitter() {
+        let light = Light::new(Color::gray(1.), 4.);
+        let s = Sphere::new(
+            Point::origin(),
+            Point::origin(),
+            0.,
+            0.,
+            1.0,
+            Box::new(light),
+        );
+        assert!(s.is_emitter());
+        let mat = Lambertian::new(Color::random().into(), 1.0);
+        let s = Sphere::new(Point::origin(), Point::origin(), 0., 0., 1.0, Box::new(mat));
+        assert!(!s.is_emitter());
+    }
+
+    #[test]
+    fn can_sample_from_outside() {
+        let mat = Lambertian::new(Color::random().into(), 1.0);
+        let s = Sphere::new(Point::origin(), Point::origin(), 0., 0., 1.0, Box::new(mat));
+        let origin = Point::new(0., 0., 2.);
+        // The sphere covers a cone with a half angle of 30 degrees
+        let expected = 1. / (2. * PI * (1. - 0.75f64.sqrt()));
+        for _ in 0..1000 {
+            let direction = s.sample(&origin, 0.).unwrap();
+            assert!((direction.len() - 1.).abs() < 1e-9);
+            assert!((s.pdf(&origin, &direction, 0.) - expected).abs() < 1e-9);
+        }
+        assert_eq!(s.pdf(&origin, &Point::new(0., 0., 1.), 0.), 0.);
+    }
+
+    #[test]
+    fn can_sample_from_inside() {
+        let mat = Lambertian::new(Color::random().into(), 1.0);
+        let s = Sphere::new(Point::origin(), Point::origin(), 0., 0., 2.0, Box::new(mat));
+        let origin = Point::new(0.5, 0., 0.);
+        // Every direction reaches the sphere, so the densities cover the whole sphere of directions
+        let samples = 20000;
+        let solid_angle: f64 = (0..samples)
+            .map(|_| 1. / s.pdf(&origin, &s.sample(&origin, 0.).unwrap(), 0.))
+            .sum::<f64>()
+            / samples as f64;
+        assert!((solid_angle - 4. * PI).abs() < 0.05 * 4. * PI);
+    }
+}
+
+#[cfg(test)]
+mod moving_tests {
+    use crate::{
+        materials::diffuse::Lambertian,
+        shapes::{hit::Hittable, sphere::Sphere},
+        utilities::{color::Color, point::Point},
+    };
+
+    #[test]
+    fn can_get_bounding_box() {
+        let mat = Lambertian::new(Color::random().into(), 1.0);
+        let s = Sphere::new(
+            Point::origin(),
+            Point::new(4., 0., 0.),
+            0.,
+            1.,
+            1.0,
+            Box::new(mat),
+        );
+        let bounds = s.bounding_box().unwrap();
+        assert_eq!(bounds.min, Point::new(-1., -1., -1.));
+        assert_eq!(bounds.max, Point::new(5., 1., 1.));
+    }
 }
@@ -0,0 +1,179 @@
+use std::f64::consts::PI;
+
+use crate::{
+    materials::scatter::Material,
+    shapes::{
+        aabb::Aabb,
+        cylinder::Cylinder,
+        hit::{Hit, Hittable},
+    },
+    utilities::{
+        frame::Frame,
+        point::Point,
+        polynomial::{solve_quadratic, solve_quartic},
+        ray::Ray,
+    },
+};
+
+use serde::{Deserialize, Serialize};
+
+/// A ring around `center`, made by sweeping a circle of `minor_radius` along a circle of
+/// `major_radius` that lies flat across `axis`
+#[derive(Serialize, Deserialize)]
+pub struct Torus {
+    center: Point,
+    axis: Point,
+    major_radius: f64,
+    minor_radius: f64,
+    material: Material,
+}
+
+impl Torus {
+    #[allow(dead_code)]
+    pub fn new(
+        center: Point,
+        axis: Point,
+        major_radius: f64,
+        minor_radius: f64,
+        material: Material,
+    ) -> Self {
+        Self {
+            center,
+            axis,
+            major_radius,
+            minor_radius,
+            material,
+        }
+    }
+
+    /// Distances along an upright ray with a unit `direction` to where it crosses the surface
+    /// `(x² + y² + z² + R² - r²)² = 4 R² (x² + y²)`
+    ///
+    /// https://www.cl.cam.ac.uk/teaching/1999/AGraphHCI/SMAG/node2.html
+    fn intersect(&self, origin: &Point, direction: &Point) -> Vec<f64> {
+        let (major, minor) = (self.major_radius.powi(2), self.minor_radius.powi(2));
+        let along = origin.dot(*direction);
+        let offset = origin.dot(*origin) + major - minor;
+        let flat_direction = direction.x.powi(2) + direction.y.powi(2);
+        let flat_along = origin.x * direction.x + origin.y * direction.y;
+        let flat_origin = origin.x.powi(2) + origin.y.powi(2);
+        solve_quartic(
+            1.,
+            4. * along,
+            4. * along.powi(2) + 2. * offset - 4. * major * flat_direction,
+            4. * along * offset - 8. * major * flat_along,
+            offset.powi(2) - 4. * major * flat_origin,
+        )
+    }
+
+    /// Gradient of the implicit surface at an upright `point`
+    fn normal(&self, point: &Point) -> Point {
+        let major = self.major_radius.powi(2);
+        let sum = point.dot(*point) - major - self.minor_radius.powi(2);
+        Point::new(point.x * sum, point.y * sum, point.z * (sum + 2. * major)).normalized()
+    }
+
+    /// `u` goes around the ring and `v` around the tube, starting from the inside edge
+    fn uv(&self, point: &Point) -> (f64, f64) {
+        let ring = (point.x.powi(2) + point.y.powi(2)).sqrt();
+        let tube = point.z.atan2(ring - self.major_radius);
+        (Cylinder::around(point), (tube + PI) / (2. * PI))
+    }
+}
+
+#[typetag::serde]
+impl Hittable for Torus {
+    fn hit(&self, ray: &Ray, time_min: f64, time_max: f64) -> Option<Hit<'_>> {
+        let frame = Frame::new(self.center, self.axis);
+        let local = frame.to_local_ray(ray);
+        let speed = local.direction.len();
+        let direction = local.direction / speed;
+
+        // Solving from far away loses precision, so start the ray at the sphere around the torus
+        let bound = self.major_radius + self.minor_radius;
+        let along = local.origin.dot(direction);
+        let sphere = solve_quadratic(
+            1.,
+            2. * along,
+            local.origin.dot(local.origin) - bound.powi(2),
+        );
+        let (&enter, &leave) = (sphere.first()?, sphere.last()?);
+        if leave < time_min * speed || enter > time_max * speed {
+            return None;
+        }
+        let start = enter.max(0.);
+        let origin = local.origin + start * direction;
+
+        let time = self
+            .intersect(&origin, &direction)
+            .into_iter()
+            .map(|distance| (start + distance) / speed)
+            .find(|time| (time_min..=time_max).contains(time))?;
+
+        let point = local.at(time);
+        let mut hit = Hit::new(ray.at(time), Point::origin(), &self.material, time, false);
+        hit.set_face_normal(ray, frame.to_world_vector(&self.normal(&point)));
+        hit.uv = self.uv(&point);
+        Some(hit)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let ring = Aabb::around_disk(self.center, self.axis, self.major_radius);
+        Some(ring.pad(self.minor_radius))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        materials::{diffuse::Lambertian, scatter::Scatter},
+        shapes::{hit::Hittable, torus::Torus},
+        utilities::{point::Point, ray::Ray},
+    };
+
+    /// Lying flat on the ground, with the hole along y
+    fn ring() -> Torus {
+        Torus::new(
+            Point::origin(),
+            Point::new(0., 1., 0.),
+            2.,
+            0.5,
+            Box::new(Lambertian::random()),
+        )
+    }
+
+    #[test]
+    fn can_hit() {
+        let torus = ring();
+        let ray = Ray::new(Point::new(-10., 0., 0.), Point::new(1., 0., 0.), 0.);
+        let hit = torus.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.time - 7.5).abs() < 1e-9);
+        assert!((hit.normal - Point::new(-1., 0., 0.)).len() < 1e-9);
+
+        let down = Ray::new(Point::new(2., 5., 0.), Point::new(0., -2., 0.), 0.);
+        let hit = torus.hit(&down, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.time - 2.25).abs() < 1e-9);
+        assert!((hit.normal - Point::new(0., 1., 0.)).len() < 1e-9);
+        assert!((hit.uv.1 - 0.75).abs() < 1e-9);
+    }
+
+    #[test]
+    fn can_miss_through_hole() {
+        let torus = ring();
+        let down = Ray::new(Point::new(0., 5., 0.), Point::new(0., -1., 0.), 0.);
+        assert!(torus.hit(&down, 0.001, f64::INFINITY).is_none());
+
+        // From inside the tube, the ray leaves through the far wall
+        let inside = Ray::new(Point::new(2., 0., 0.), Point::new(1., 0., 0.), 0.);
+        let hit = torus.hit(&inside, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.time - 0.5).abs() < 1e-9);
+        assert!(!hit.front_face);
+    }
+
+    #[test]
+    fn can_bound() {
+        let bounds = ring().bounding_box().unwrap();
+        assert!((bounds.min - Point::new(-2.5, -0.5, -2.5)).len() < 1e-9);
+        assert!((bounds.max - Point::new(2.5, 0.5, 2.5)).len() < 1e-9);
+    }
+}
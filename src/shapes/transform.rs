@@ -0,0 +1,272 @@
+use crate::{
+    shapes::{
+        aabb::Aabb,
+        hit::{Hit, Hittable},
+        instance::Library,
+    },
+    utilities::{matrix::Matrix, point::Point, ray::Ray},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Scene file representation of a placement; every part is optional and they are applied in
+/// the order `matrix`, `scale`, `rotate`, then `translate`
+#[derive(Clone, Default, Serialize, Deserialize)]
+struct PlacementFile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    matrix: Option<Matrix>,
+    /// Factor along each axis
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scale: Option<Point>,
+    /// Degrees around the x, y, then z axis
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rotate: Option<Point>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    translate: Option<Point>,
+}
+
+/// Where a shape defined in its own object space sits in the scene
+///
+/// Rays are moved into object space to hit the shape, and hits are moved back out, with
+/// normals transformed by the inverse transpose so they stay perpendicular to the surface.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(from = "PlacementFile", into = "PlacementFile")]
+pub struct Placement {
+    file: PlacementFile,
+    to_world: Matrix,
+    to_object: Matrix,
+    /// Inverse transpose of `to_world`, for normals
+    normal: Matrix,
+}
+
+impl From<PlacementFile> for Placement {
+    fn from(file: PlacementFile) -> Self {
+        let matrix = file
+            .translate
+            .map_or(Matrix::identity(), Matrix::translation)
+            * file.rotate.map_or(Matrix::identity(), Matrix::rotation)
+            * file.scale.map_or(Matrix::identity(), Matrix::scaling)
+            * file.matrix.unwrap_or_default();
+        let to_object = matrix
+            .inverse()
+            .unwrap_or_else(|| panic!("Unable to place shape: {matrix:?} is not invertible"));
+        Self {
+            file,
+            to_world: matrix,
+            to_object,
+            normal: to_object.transpose(),
+        }
+    }
+}
+
+impl From<Placement> for PlacementFile {
+    fn from(placement: Placement) -> Self {
+        placement.file
+    }
+}
+
+impl Placement {
+    #[allow(dead_code)]
+    pub fn new(matrix: Matrix) -> Self {
+        Placement::from(PlacementFile {
+            matrix: Some(matrix),
+            ..Default::default()
+        })
+    }
+
+    pub fn hit<'a>(
+        &self,
+        shape: &'a dyn Hittable,
+        ray: &Ray,
+        time_min: f64,
+        time_max: f64,
+    ) -> Option<Hit<'a>> {
+        // The direction is not normalized, so hit times are the same in both spaces
+        let local_ray = Ray::new(
+            self.to_object.transform_point(&ray.origin),
+            self.to_object.transform_vector(&ray.direction),
+            ray.time,
+        );
+        let mut hit = shape.hit(&local_ray, time_min, time_max)?;
+        hit.point = ray.at(hit.time);
+        hit.normal = self.normal.transform_vector(&hit.normal).normalized();
+        Some(hit)
+    }
+
+    /// Box around the eight corners of the shape's box, moved into the scene
+    pub fn bounding_box(&self, shape: &dyn Hittable) -> Option<Aabb> {
+        let bounds = shape.bounding_box()?;
+        let corners = (0..8).map(|corner| {
+            Point::new(
+                if corner & 1 == 0 {
+                    bounds.min.x
+                } else {
+                    bounds.max.x
+                },
+                if corner & 2 == 0 {
+                    bounds.min.y
+                } else {
+                    bounds.max.y
+                },
+                if corner & 4 == 0 {
+                    bounds.min.z
+                } else {
+                    bounds.max.z
+                },
+            )
+        });
+        Some(corners.fold(Aabb::empty(), |bounds, corner| {
+            bounds.grow(self.to_world.transform_point(&corner))
+        }))
+    }
+
+    pub fn sample(&self, shape: &dyn Hittable, origin: &Point, time: f64) -> Option<Point> {
+        let direction = shape.sample(&self.to_object.transform_point(origin), time)?;
+        Some(self.to_world.transform_vector(&direction).normalized())
+    }
+
+    /// The shape's density in object space, scaled by how much the transformation stretches
+    /// solid angle around `direction`
+    pub fn pdf(&self, shape: &dyn Hittable, origin: &Point, direction: &Point, time: f64) -> f64 {
+        let local = self.to_object.transform_vector(&direction.normalized());
+        let length = local.len();
+        let pdf = shape.pdf(
+            &self.to_object.transform_point(origin),
+            &(local / length),
+            time,
+        );
+        pdf * self.to_object.determinant().abs() / length.powi(3)
+    }
+}
+
+/// A shape moved, rotated or scaled away from the coordinates it was defined with
+#[derive(Serialize, Deserialize)]
+pub struct Transform {
+    shape: Box<dyn Hittable>,
+    #[serde(flatten)]
+    placement: Placement,
+}
+
+impl Transform {
+    #[allow(dead_code)]
+    pub fn new(shape: Box<dyn Hittable>, matrix: Matrix) -> Self {
+        Self {
+            shape,
+            placement: Placement::new(matrix),
+        }
+    }
+}
+
+#[typetag::serde]
+impl Hittable for Transform {
+    fn hit(&self, ray: &Ray, time_min: f64, time_max: f64) -> Option<Hit<'_>> {
+        self.placement
+            .hit(self.shape.as_ref(), ray, time_min, time_max)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.placement.bounding_box(self.shape.as_ref())
+    }
+
+    fn is_emitter(&self) -> bool {
+        self.shape.is_emitter()
+    }
+
+    fn sample(&self, origin: &Point, time: f64) -> Option<Point> {
+        self.placement.sample(self.shape.as_ref(), origin, time)
+    }
+
+    fn pdf(&self, origin: &Point, direction: &Point, time: f64) -> f64 {
+        self.placement
+            .pdf(self.shape.as_ref(), origin, direction, time)
+    }
+
+    fn link(&mut self, shapes: &Library) {
+        self.shape.link(shapes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use crate::{
+        materials::{diffuse::Lambertian, light::Light, scatter::Scatter},
+        shapes::{hit::Hittable, sphere::Sphere, transform::Transform},
+        utilities::{color::Color, matrix::Matrix, point::Point, ray::Ray},
+    };
+
+    fn unit_sphere(material: Box<dyn Scatter>) -> Box<dyn Hittable> {
+        Box::new(Sphere::new(
+            Point::origin(),
+            Point::origin(),
+            0.,
+            1.,
+            1.,
+            material,
+        ))
+    }
+
+    #[test]
+    fn can_hit_moved_shape() {
+        let matrix =
+            Matrix::translation(Point::new(0., 0., -5.)) * Matrix::scaling(Point::new(1., 1., 2.));
+        let shape = Transform::new(unit_sphere(Box::new(Lambertian::random())), matrix);
+        let ray = Ray::new(Point::origin(), Point::new(0., 0., -1.), 0.);
+        let hit = shape.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        // Stretched to reach from z = -7 to -3
+        assert!((hit.time - 3.).abs() < 1e-12);
+        assert!((hit.point - Point::new(0., 0., -3.)).len() < 1e-12);
+        assert!((hit.normal - Point::new(0., 0., 1.)).len() < 1e-12);
+        assert!((hit.local - Point::new(0., 0., 1.)).len() < 1e-12);
+
+        let bounds = shape.bounding_box().unwrap();
+        assert_eq!(bounds.min, Point::new(-1., -1., -7.));
+        assert_eq!(bounds.max, Point::new(1., 1., -3.));
+    }
+
+    #[test]
+    fn can_transform_normals() {
+        // Squashing a sphere into a disc tilts normals towards the squashed axis
+        let matrix = Matrix::scaling(Point::new(4., 1., 1.));
+        let shape = Transform::new(unit_sphere(Box::new(Lambertian::random())), matrix);
+        let target = Point::new(4. * 0.5f64.sqrt(), 0.5f64.sqrt(), 0.);
+        let ray = Ray::new(target * 2., target * -1., 0.);
+        let hit = shape.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        let tangent = Point::new(-4. * 0.5f64.sqrt(), 0.5f64.sqrt(), 0.);
+        assert!(hit.normal.dot(tangent).abs() < 1e-12);
+        assert!((hit.normal.len() - 1.).abs() < 1e-12);
+    }
+
+    #[test]
+    fn can_sample_scaled_light() {
+        // A unit sphere scaled by 2 looks like a sphere of radius 2
+        let light = Box::new(Light::new(Color::gray(1.), 1.));
+        let matrix =
+            Matrix::translation(Point::new(0., 0., -6.)) * Matrix::scaling(Point::new(2., 2., 2.));
+        let shape = Transform::new(unit_sphere(light), matrix);
+        assert!(shape.is_emitter());
+
+        let origin = Point::origin();
+        let expected = 2. * PI * (1. - (1. - (2f64 / 6.).powi(2)).sqrt());
+        let samples = 5000;
+        let solid_angle: f64 = (0..samples)
+            .map(|_| 1. / shape.pdf(&origin, &shape.sample(&origin, 0.).unwrap(), 0.))
+            .sum::<f64>()
+            / samples as f64;
+        assert!((solid_angle - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn can_round_trip() {
+        let yaml = "type: Transform\nshape:\n  type: Sphere\n  center_t_0: {x: 0.0, y: 0.0, z: 0.0}\n  center_t_1: {x: 0.0, y: 0.0, z: 0.0}\n  t_0: 0.0\n  t_1: 1.0\n  radius: 1.0\n  material:\n    type: Mirror\n    albedo: {r: 1.0, g: 1.0, b: 1.0, a: 255}\ntranslate: {x: 1.0, y: 2.0, z: 3.0}\nrotate: {x: 0.0, y: 45.0, z: 0.0}\n";
+        let shape: Box<dyn Hittable> = serde_yml::from_str(yaml).unwrap();
+        let bounds = shape.bounding_box().unwrap();
+        assert!((bounds.centroid() - Point::new(1., 2., 3.)).len() < 1e-12);
+
+        let saved = serde_yml::to_string(&shape).unwrap();
+        assert!(saved.contains("translate:"));
+        assert!(saved.contains("rotate:"));
+        assert!(!saved.contains("to_world"));
+    }
+}
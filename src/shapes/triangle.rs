@@ -1,6 +1,11 @@
+use rand::Rng;
+
 use crate::{
     materials::scatter::Material,
-    shapes::hit::{Hit, Hittable},
+    shapes::{
+        aabb::Aabb,
+        hit::{area_pdf, Hit, Hittable},
+    },
     utilities::{point::Point, ray::Ray},
 };
 
@@ -13,6 +18,9 @@ pub struct Triangle {
     b: Point,
     c: Point,
     material: Material,
+    /// Texture coordinates of `a`, `b` and `c`, defaulting to `(0, 0)`, `(1, 0)` and `(0, 1)`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    uv: Option<[(f64, f64); 3]>,
 }
 
 impl Triangle {
@@ -21,21 +29,34 @@ impl Triangle {
     /// - `b` is part of the base
     /// - `c` is the zenith
     pub fn new(a: Point, b: Point, c: Point, material: Material) -> Self {
-        Self { a, b, c, material }
+        Self {
+            a,
+            b,
+            c,
+            material,
+            uv: None,
+        }
     }
-}
 
-#[typetag::serde]
-impl Hittable for Triangle {
+    /// Intersect a ray with the triangle `(a, b, c)`, returning the distance along the ray and
+    /// the barycentric coordinates `(u, v)` of the hit
+    ///
     /// Implemented using the Möller–Trumbore intersection algorithm using the two-sided approach
     ///
     /// https://cadxfem.org/inf/Fast%20MinimumStorage%20RayTriangle%20Intersection.pdf
     ///
     /// The order of the points matters for the math here:
     /// https://courses.cs.washington.edu/courses/cse457/04sp/lectures/triangle_intersection.pdf
-    fn hit(&self, ray: &Ray, time_min: f64, time_max: f64) -> Option<Hit> {
-        let edge_1 = self.b - self.a;
-        let edge_2 = -1. * (self.c - self.a);
+    pub fn intersect(
+        a: Point,
+        b: Point,
+        c: Point,
+        ray: &Ray,
+        time_min: f64,
+        time_max: f64,
+    ) -> Option<(f64, f64, f64)> {
+        let edge_1 = b - a;
+        let edge_2 = -1. * (c - a);
 
         // p_vec is the direction vector perpendicular to both the ray direction and edge_2
         // It's used to compute the barycentric coordinate u and helps determine if the ray
@@ -54,7 +75,7 @@ impl Hittable for Triangle {
         let inverse_determinant = 1. / determinant;
 
         // Distance from point a to ray origin
-        let t_vec = ray.origin - self.a;
+        let t_vec = ray.origin - a;
 
         /*
         (u, v) are the coordinates inside the triangle
@@ -90,21 +111,85 @@ impl Hittable for Triangle {
             return None;
         }
 
+        Some((time, u, v))
+    }
+
+    /// Outward surface normal of the triangle `(a, b, c)`
+    pub fn normal(a: Point, b: Point, c: Point) -> Point {
+        (b - a).cross(c - a).normalized()
+    }
+
+    /// Blend per-vertex texture coordinates with the barycentric coordinates `(u, v)` from
+    /// `intersect`
+    pub fn interpolate_uv(uv: [(f64, f64); 3], u: f64, v: f64) -> (f64, f64) {
+        let w = 1. - u - v;
+        (
+            w * uv[0].0 + u * uv[1].0 + v * uv[2].0,
+            w * uv[0].1 + u * uv[1].1 + v * uv[2].1,
+        )
+    }
+
+    fn area(&self) -> f64 {
+        0.5 * (self.b - self.a).cross(self.c - self.a).len()
+    }
+}
+
+#[typetag::serde]
+impl Hittable for Triangle {
+    fn hit(&self, ray: &Ray, time_min: f64, time_max: f64) -> Option<Hit<'_>> {
+        let (time, u, v) = Self::intersect(self.a, self.b, self.c, ray, time_min, time_max)?;
+
         // Calculate the outward surface normal
-        let outward_normal = edge_2.cross(edge_1).normalized();
+        let outward_normal = Self::normal(self.a, self.b, self.c);
         let mut hit = Hit::new(ray.at(time), outward_normal, &self.material, time, false);
         hit.set_face_normal(ray, outward_normal);
+        hit.uv = match self.uv {
+            Some(uv) => Self::interpolate_uv(uv, u, v),
+            None => (u, v),
+        };
 
         Some(hit)
     }
+
+    /// Padded so that axis-aligned triangles do not produce a flat box
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(Aabb::new(self.a, self.b).grow(self.c).pad(1e-4))
+    }
+
+    fn is_emitter(&self) -> bool {
+        !self.material.emit().is_black()
+    }
+
+    /// Picks a point uniformly over the triangle's area
+    ///
+    /// https://pbr-book.org/3ed-2018/Monte_Carlo_Integration/2D_Sampling_with_Multidimensional_Transformations#SamplingaTriangle
+    fn sample(&self, origin: &Point, _time: f64) -> Option<Point> {
+        let mut rng = rand::thread_rng();
+        let root = rng.gen::<f64>().sqrt();
+        let v = rng.gen::<f64>();
+        let point = (1. - root) * self.a + root * (1. - v) * self.b + root * v * self.c;
+        Some((point - *origin).normalized())
+    }
+
+    fn pdf(&self, origin: &Point, direction: &Point, time: f64) -> f64 {
+        let direction = direction.normalized();
+        let ray = Ray::new(*origin, direction, time);
+        let Some((distance, _, _)) =
+            Self::intersect(self.a, self.b, self.c, &ray, 0.001, f64::INFINITY)
+        else {
+            return 0.;
+        };
+        let cosine = Self::normal(self.a, self.b, self.c).dot(direction).abs();
+        area_pdf(distance, cosine, self.area())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
-        materials::{diffuse::Lambertian, scatter::Scatter},
+        materials::{diffuse::Lambertian, light::Light, scatter::Scatter},
         shapes::{hit::Hittable, triangle::Triangle},
-        utilities::{point::Point, ray::Ray},
+        utilities::{color::Color, point::Point, ray::Ray},
     };
 
     #[test]
@@ -137,4 +222,79 @@ mod tests {
             )
             .is_some());
     }
+
+    #[test]
+    fn can_get_uv() {
+        let (a, b, c) = (
+            Point::new(-2., 0., -1.),
+            Point::new(2., 0., -1.),
+            Point::new(0., 3., -1.),
+        );
+        let mut t = Triangle::new(a, b, c, Box::new(Lambertian::random()));
+        let ray = Ray::new(Point::new(0.5, 1., 0.), Point::new(0., 0., -1.), 0.);
+
+        // Without texture coordinates, `u` runs towards `b` and `v` towards `c`
+        let hit = t.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        let (u, v) = hit.uv;
+        assert!(((1. - u - v) * a + u * b + v * c - hit.point).len() < 1e-12);
+
+        t.uv = Some([(0., 0.), (1., 0.), (0.5, 1.)]);
+        let hit = t.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!((hit.uv.0 - (hit.point.x + 2.) / 4.).abs() < 1e-12);
+        assert!((hit.uv.1 - hit.point.y / 3.).abs() < 1e-12);
+    }
+
+    #[test]
+    fn can_get_bounding_box() {
+        let mat = Lambertian::random();
+        let t = Triangle::new(
+            Point::new(-2., 0., -1.),
+            Point::new(2., 0., -1.),
+            Point::new(0., 3., -1.),
+            Box::new(mat),
+        );
+        let bounds = t.bounding_box().unwrap();
+        assert!(bounds.min.x <= -2. && bounds.min.y <= 0. && bounds.min.z < -1.);
+        assert!(bounds.max.x >= 2. && bounds.max.y >= 3. && bounds.max.z > -1.);
+    }
+
+    #[test]
+    fn can_sample() {
+        let a = Point::new(-2., 0., -1.);
+        let b = Point::new(2., 0., -1.);
+        let c = Point::new(0., 3., -1.);
+        let t = Triangle::new(a, b, c, Box::new(Light::new(Color::gray(1.), 1.)));
+        assert!(t.is_emitter());
+
+        // Solid angle of the triangle as seen from the origin
+        // https://en.wikipedia.org/wiki/Solid_angle#Tetrahedron
+        let (la, lb, lc) = (a.len(), b.len(), c.len());
+        let expected = 2.
+            * a.dot(b.cross(c))
+                .abs()
+                .atan2(la * lb * lc + a.dot(b) * lc + a.dot(c) * lb + b.dot(c) * la);
+
+        // Averaging the inverse density of uniform samples estimates the solid angle
+        let samples = 20000;
+        let solid_angle: f64 = (0..samples)
+            .map(|_| {
+                let direction = t.sample(&Point::origin(), 0.).unwrap();
+                1. / t.pdf(&Point::origin(), &direction, 0.)
+            })
+            .sum::<f64>()
+            / samples as f64;
+        assert!((solid_angle - expected).abs() < 0.05 * expected);
+    }
+
+    #[test]
+    fn can_get_pdf_on_miss() {
+        let t = Triangle::new(
+            Point::new(-2., 0., -1.),
+            Point::new(2., 0., -1.),
+            Point::new(0., 3., -1.),
+            Box::new(Lambertian::random()),
+        );
+        let pdf = t.pdf(&Point::origin(), &Point::new(0., 0., 1.), 0.);
+        assert_eq!(pdf, 0.);
+    }
 }
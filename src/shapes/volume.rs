@@ -0,0 +1,141 @@
+use rand::Rng;
+
+use crate::{
+    materials::scatter::Material,
+    shapes::{
+        aabb::Aabb,
+        hit::{Hit, Hittable},
+        instance::Library,
+    },
+    utilities::ray::Ray,
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Fog, smoke or the inside of a waxy solid: particles spread evenly through the space inside
+/// a closed `boundary`, which scatter light with `material`, usually a `Phase`
+///
+/// Rays pass through until they hit a particle, after a distance picked at random so that
+/// thicker volumes, with a higher `density`, stop more of them.
+///
+/// https://raytracing.github.io/books/RayTracingTheNextWeek.html#volumes
+#[derive(Serialize, Deserialize)]
+pub struct Volume {
+    boundary: Box<dyn Hittable>,
+    /// Chance of hitting a particle per unit of distance
+    density: f64,
+    material: Material,
+}
+
+impl Volume {
+    #[allow(dead_code)]
+    pub fn new(boundary: Box<dyn Hittable>, density: f64, material: Material) -> Self {
+        Self {
+            boundary,
+            density,
+            material,
+        }
+    }
+
+    /// Distance a ray travels through a medium of `density` before hitting a particle,
+    /// following an exponential distribution
+    pub fn free_path(density: f64) -> f64 {
+        -(1. - rand::thread_rng().gen::<f64>()).ln() / density
+    }
+
+    /// A hit on a particle at `time` along `ray`, facing back along the ray
+    pub fn particle<'a>(material: &'a Material, ray: &Ray, time: f64) -> Hit<'a> {
+        let normal = -ray.direction.normalized();
+        let mut hit = Hit::new(ray.at(time), normal, material, time, true);
+        hit.uv = (0.5, 0.5);
+        hit
+    }
+}
+
+#[typetag::serde]
+impl Hittable for Volume {
+    fn hit(&self, ray: &Ray, time_min: f64, time_max: f64) -> Option<Hit<'_>> {
+        let speed = ray.direction.len();
+        // Each stretch inside the boundary gets a fresh distance, since particles have no
+        // memory of the ones the ray passed before
+        self.boundary
+            .spans(ray, time_min, time_max)
+            .into_iter()
+            .find_map(|span| {
+                let enter = span.enter.map_or(time_min, |hit| hit.time);
+                let exit = span.exit.map_or(time_max, |hit| hit.time);
+                let time = enter + Self::free_path(self.density) / speed;
+                (time < exit).then(|| Self::particle(&self.material, ray, time))
+            })
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.boundary.bounding_box()
+    }
+
+    fn link(&mut self, shapes: &Library) {
+        self.boundary.link(shapes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        materials::{diffuse::Lambertian, phase::Phase, scatter::Scatter},
+        shapes::{hit::Hittable, sphere::Sphere, volume::Volume},
+        utilities::{point::Point, ray::Ray},
+    };
+
+    fn cloud(density: f64) -> Volume {
+        let boundary = Box::new(Sphere::new(
+            Point::origin(),
+            Point::origin(),
+            0.,
+            1.,
+            1.,
+            Box::new(Lambertian::random()),
+        ));
+        Volume::new(boundary, density, Box::new(Phase::random()))
+    }
+
+    #[test]
+    fn can_scatter_inside() {
+        let volume = cloud(2.);
+        let ray = Ray::new(Point::new(0., 0., 5.), Point::new(0., 0., -1.), 0.);
+        (0..100).for_each(|_| {
+            if let Some(hit) = volume.hit(&ray, 0.001, f64::INFINITY) {
+                assert!((4. ..6.).contains(&hit.time));
+                assert!(hit.front_face);
+            }
+        });
+        let miss = Ray::new(Point::new(0., 2., 5.), Point::new(0., 0., -1.), 0.);
+        assert!(volume.hit(&miss, 0.001, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn can_transmit() {
+        // Across the middle, the ray crosses 2 units of the volume
+        let volume = cloud(0.5);
+        let ray = Ray::new(Point::new(0., 0., 5.), Point::new(0., 0., -2.), 0.);
+        let samples = 20000;
+        let passed = (0..samples)
+            .filter(|_| volume.hit(&ray, 0.001, f64::INFINITY).is_none())
+            .count();
+        let transmittance = passed as f64 / samples as f64;
+        assert!(
+            (transmittance - (-1f64).exp()).abs() < 0.02,
+            "{transmittance}"
+        );
+
+        // Starting inside, only the remaining unit counts
+        let inside = Ray::new(Point::origin(), Point::new(1., 0., 0.), 0.);
+        let passed = (0..samples)
+            .filter(|_| volume.hit(&inside, 0.001, f64::INFINITY).is_none())
+            .count();
+        let transmittance = passed as f64 / samples as f64;
+        assert!(
+            (transmittance - (-0.5f64).exp()).abs() < 0.02,
+            "{transmittance}"
+        );
+    }
+}
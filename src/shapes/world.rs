@@ -1,5 +1,9 @@
 use crate::{
-    shapes::hit::{Hit, Hittable},
+    shapes::{
+        aabb::Aabb,
+        hit::{Hit, Hittable},
+        instance::Library,
+    },
     utilities::ray::Ray,
 };
 
@@ -10,7 +14,7 @@ pub type World = Vec<Box<dyn Hittable>>;
 #[typetag::serde]
 impl Hittable for World {
     /// Iterate through each item in the world, returning the closest hit
-    fn hit(&self, ray: &Ray, time_min: f64, time_max: f64) -> Option<Hit> {
+    fn hit(&self, ray: &Ray, time_min: f64, time_max: f64) -> Option<Hit<'_>> {
         let mut hit: Option<Hit> = None;
         // Stores the time it takes to hit the closest object to the camera
         // This ensures that we respect the z-axis, that is, closer objects
@@ -28,4 +32,15 @@ impl Hittable for World {
 
         hit
     }
+
+    /// Union of every shape's box, or `None` if any shape is unbounded
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.iter().try_fold(Aabb::empty(), |bounds, shape| {
+            Some(bounds.union(&shape.bounding_box()?))
+        })
+    }
+
+    fn link(&mut self, shapes: &Library) {
+        self.iter_mut().for_each(|shape| shape.link(shapes))
+    }
 }
@@ -0,0 +1,63 @@
+use crate::{
+    textures::texture::Texture,
+    utilities::{color::Color, noise::Worley, point::Point},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Cells from Worley noise, `center` at each feature point fading to `edge` away from it, like
+/// scales, stones or foam
+#[derive(Serialize, Deserialize)]
+pub struct Cellular {
+    center: Color,
+    edge: Color,
+    /// Number of cells per unit
+    scale: f64,
+    /// Number of layers of smaller cells
+    octaves: u32,
+    #[serde(rename = "seed", default)]
+    noise: Worley,
+}
+
+impl Cellular {
+    #[allow(dead_code)]
+    pub fn new(center: Color, edge: Color, scale: f64, octaves: u32, seed: u64) -> Self {
+        Self {
+            center,
+            edge,
+            scale,
+            octaves,
+            noise: Worley::new(seed),
+        }
+    }
+}
+
+#[typetag::serde]
+impl Texture for Cellular {
+    fn value(&self, _: (f64, f64), point: &Point, _: f64) -> Color {
+        let t = self
+            .noise
+            .fractal(&(self.scale * *point), self.octaves)
+            .clamp(0., 1.);
+        (1. - t) * self.center + t * self.edge
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        textures::{cellular::Cellular, texture::Texture},
+        utilities::{color::Color, point::Point},
+    };
+
+    #[test]
+    fn can_repeat_with_seed() {
+        let point = Point::new(0.3, 1.7, -2.2);
+        let value = |seed| {
+            Cellular::new(Color::gray(0.), Color::gray(1.), 3., 2, seed).value((0., 0.), &point, 0.)
+        };
+        assert_eq!(value(4), value(4));
+        assert_ne!(value(4), value(5));
+        assert!((0. ..=1.).contains(&value(4).r));
+    }
+}
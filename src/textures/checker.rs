@@ -0,0 +1,58 @@
+use crate::{
+    textures::texture::{self, Texture},
+    utilities::{color::Color, point::Point},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Alternating cubes of two textures filling space, so the pattern does not depend on how a
+/// shape maps its surface coordinates
+#[derive(Serialize, Deserialize)]
+pub struct Checker {
+    #[serde(deserialize_with = "texture::deserialize")]
+    even: Box<dyn Texture>,
+    #[serde(deserialize_with = "texture::deserialize")]
+    odd: Box<dyn Texture>,
+    /// Edge length of each cube
+    scale: f64,
+}
+
+impl Checker {
+    #[allow(dead_code)]
+    pub fn new(even: Box<dyn Texture>, odd: Box<dyn Texture>, scale: f64) -> Self {
+        Self { even, odd, scale }
+    }
+}
+
+#[typetag::serde]
+impl Texture for Checker {
+    fn value(&self, uv: (f64, f64), point: &Point, time: f64) -> Color {
+        let cell = [point.x, point.y, point.z]
+            .iter()
+            .map(|coordinate| (coordinate / self.scale).floor() as i64)
+            .sum::<i64>();
+        match cell.rem_euclid(2) == 0 {
+            true => self.even.value(uv, point, time),
+            false => self.odd.value(uv, point, time),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        textures::{checker::Checker, texture::Texture},
+        utilities::{color::Color, point::Point},
+    };
+
+    #[test]
+    fn can_alternate() {
+        let checker = Checker::new(Color::gray(1.).into(), Color::gray(0.).into(), 0.5);
+        let value = |x, y, z| checker.value((0., 0.), &Point::new(x, y, z), 0.);
+        assert_eq!(value(0.1, 0.1, 0.1), Color::gray(1.));
+        assert_eq!(value(0.6, 0.1, 0.1), Color::gray(0.));
+        assert_eq!(value(0.6, 0.6, 0.1), Color::gray(1.));
+        // Negative coordinates continue the pattern instead of mirroring it
+        assert_eq!(value(-0.1, 0.1, 0.1), Color::gray(0.));
+    }
+}
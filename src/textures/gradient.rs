@@ -0,0 +1,66 @@
+use crate::{
+    textures::texture::Texture,
+    utilities::{color::Color, point::Point},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Surface coordinate that a `Gradient` blends along
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Axis {
+    /// Around a sphere, or from `a` to `b` on a triangle
+    U,
+    /// From the bottom to the top of a sphere, or from `a` to `c` on a triangle
+    #[default]
+    V,
+}
+
+/// A linear blend between two colors across a shape's surface coordinates
+#[derive(Serialize, Deserialize)]
+pub struct Gradient {
+    start: Color,
+    end: Color,
+    #[serde(default)]
+    axis: Axis,
+}
+
+impl Gradient {
+    #[allow(dead_code)]
+    pub fn new(start: Color, end: Color, axis: Axis) -> Self {
+        Self { start, end, axis }
+    }
+}
+
+#[typetag::serde]
+impl Texture for Gradient {
+    fn value(&self, (u, v): (f64, f64), _: &Point, _: f64) -> Color {
+        let t = match self.axis {
+            Axis::U => u,
+            Axis::V => v,
+        }
+        .clamp(0., 1.);
+        (1. - t) * self.start + t * self.end
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        textures::{
+            gradient::{Axis, Gradient},
+            texture::Texture,
+        },
+        utilities::{color::Color, point::Point},
+    };
+
+    #[test]
+    fn can_blend() {
+        let gradient = Gradient::new(Color::gray(0.), Color::gray(1.), Axis::U);
+        let origin = Point::origin();
+        assert_eq!(gradient.value((0.25, 0.9), &origin, 0.), Color::gray(0.25));
+        assert_eq!(gradient.value((2., 0.), &origin, 0.), Color::gray(1.));
+
+        let gradient = Gradient::new(Color::gray(0.), Color::gray(1.), Axis::V);
+        assert_eq!(gradient.value((0.25, 0.5), &origin, 0.), Color::gray(0.5));
+    }
+}
@@ -0,0 +1,113 @@
+use crate::{
+    textures::texture::Texture,
+    utilities::{color::Color, image::Image, point::Point},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Scene file representation of an image texture; the image is loaded from `path`
+#[derive(Deserialize)]
+struct ImageTextureFile {
+    path: String,
+    #[serde(default = "ImageTexture::default_scale")]
+    scale: (f64, f64),
+}
+
+/// Colors read from a `.png`, `.ppm` or high dynamic range image, with `(0, 0)` at the bottom
+/// left corner and the image repeating outside of 0..1
+#[derive(Serialize, Deserialize)]
+#[serde(from = "ImageTextureFile")]
+pub struct ImageTexture {
+    /// Path to the image, relative to the working directory
+    path: String,
+    /// Number of times the image repeats along `u` and `v`
+    scale: (f64, f64),
+    #[serde(skip_serializing)]
+    image: Image,
+}
+
+impl From<ImageTextureFile> for ImageTexture {
+    fn from(file: ImageTextureFile) -> Self {
+        ImageTexture::new(&file.path, file.scale)
+    }
+}
+
+impl ImageTexture {
+    fn default_scale() -> (f64, f64) {
+        (1., 1.)
+    }
+
+    pub fn new(path: &str, scale: (f64, f64)) -> Self {
+        Self::from_image(path, Image::load(path), scale)
+    }
+
+    fn from_image(path: &str, image: Image, scale: (f64, f64)) -> Self {
+        Self {
+            path: path.to_string(),
+            scale,
+            image,
+        }
+    }
+}
+
+#[typetag::serde(name = "Image")]
+impl Texture for ImageTexture {
+    fn value(&self, (u, v): (f64, f64), _: &Point, _: f64) -> Color {
+        let u = (u * self.scale.0).rem_euclid(1.);
+        // Rows are stored from the top of the image
+        let v = 1. - (v * self.scale.1).rem_euclid(1.);
+        let col = ((u * self.image.width as f64) as u64).min(self.image.width - 1);
+        let row = ((v * self.image.height as f64) as u64).min(self.image.height - 1);
+        self.image.buffer[(row * self.image.width + col) as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        textures::{image::ImageTexture, texture::Texture},
+        utilities::{color::Color, image::Image, point::Point},
+    };
+
+    fn quadrants() -> ImageTexture {
+        let mut image = Image::from_dimensions(2, 2);
+        // Red at the top left and blue at the bottom right
+        image.buffer[0] = Color::rgb(1., 0., 0.);
+        image.buffer[3] = Color::rgb(0., 0., 1.);
+        ImageTexture::from_image("quadrants.png", image, (1., 1.))
+    }
+
+    #[test]
+    fn can_look_up() {
+        let texture = quadrants();
+        let origin = Point::origin();
+        // `v` runs from the bottom row of the image to the top
+        assert_eq!(
+            texture.value((0.25, 0.75), &origin, 0.),
+            Color::rgb(1., 0., 0.)
+        );
+        assert_eq!(
+            texture.value((0.75, 0.25), &origin, 0.),
+            Color::rgb(0., 0., 1.)
+        );
+        assert_eq!(texture.value((0.75, 0.75), &origin, 0.), Color::default());
+    }
+
+    #[test]
+    fn can_repeat() {
+        let texture = quadrants();
+        let origin = Point::origin();
+        assert_eq!(
+            texture.value((1.25, -0.25), &origin, 0.),
+            Color::rgb(1., 0., 0.)
+        );
+        // Repeating twice along each axis tiles four copies of the image
+        let mut image = Image::from_dimensions(2, 2);
+        image.buffer[0] = Color::rgb(1., 0., 0.);
+        let tiled = ImageTexture::from_image("quadrants.png", image, (2., 2.));
+        assert_eq!(
+            tiled.value((0.625, 0.375), &origin, 0.),
+            Color::rgb(1., 0., 0.)
+        );
+    }
+}
@@ -0,0 +1,89 @@
+use std::f64::consts::PI;
+
+use crate::{
+    textures::texture::Texture,
+    utilities::{color::Color, noise::Perlin, point::Point},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Veins of `vein` through `base`, running across the x axis and bent by Perlin turbulence
+#[derive(Serialize, Deserialize)]
+pub struct Marble {
+    base: Color,
+    vein: Color,
+    /// Number of veins per unit along x
+    scale: f64,
+    /// Number of layers of finer detail in the bends
+    octaves: u32,
+    /// How far turbulence pushes veins out of line, in vein spacings
+    #[serde(default = "Marble::default_distortion")]
+    distortion: f64,
+    #[serde(rename = "seed", default)]
+    noise: Perlin,
+}
+
+impl Marble {
+    fn default_distortion() -> f64 {
+        2.
+    }
+
+    #[allow(dead_code)]
+    pub fn new(
+        base: Color,
+        vein: Color,
+        scale: f64,
+        octaves: u32,
+        distortion: f64,
+        seed: u64,
+    ) -> Self {
+        Self {
+            base,
+            vein,
+            scale,
+            octaves,
+            distortion,
+            noise: Perlin::new(seed),
+        }
+    }
+}
+
+#[typetag::serde]
+impl Texture for Marble {
+    fn value(&self, _: (f64, f64), point: &Point, _: f64) -> Color {
+        let phase = self.scale * point.x
+            + self.distortion * self.noise.turbulence(&(self.scale * *point), self.octaves);
+        // Sharpen the sine so that veins are thin lines instead of half of the surface
+        let t = (0.5 + 0.5 * (2. * PI * phase).sin()).powi(6);
+        (1. - t) * self.base + t * self.vein
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        textures::{marble::Marble, texture::Texture},
+        utilities::{color::Color, point::Point},
+    };
+
+    #[test]
+    fn can_draw_veins() {
+        let texture = Marble::new(Color::gray(0.), Color::gray(1.), 1., 5, 0., 3);
+        // Without distortion the veins are straight, peaking a quarter of the way along
+        let value = |x| texture.value((0., 0.), &Point::new(x, 0.4, 0.2), 0.).r;
+        assert!((value(0.25) - 1.).abs() < 1e-12);
+        assert!(value(0.75) < 1e-12);
+        assert!((value(1.25) - 1.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn can_deserialize() {
+        let texture: Box<dyn Texture> = serde_yml::from_str(
+            "type: Marble\nbase: {r: 1.0, g: 1.0, b: 1.0, a: 255}\nvein: {r: 0.0, g: 0.0, b: 0.0, a: 255}\nscale: 2.0\noctaves: 7\nseed: 9\n",
+        )
+        .unwrap();
+        let saved = serde_yml::to_string(&texture).unwrap();
+        assert!(saved.contains("seed: 9"));
+        assert!(saved.contains("distortion: 2.0"));
+    }
+}
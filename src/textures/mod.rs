@@ -0,0 +1,9 @@
+pub mod cellular;
+pub mod checker;
+pub mod gradient;
+pub mod image;
+pub mod marble;
+pub mod solid;
+pub mod texture;
+pub mod turbulence;
+pub mod wood;
@@ -0,0 +1,31 @@
+use crate::{
+    textures::texture::Texture,
+    utilities::{color::Color, point::Point},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// The same color everywhere
+#[derive(Serialize, Deserialize)]
+pub struct Solid {
+    color: Color,
+}
+
+impl Solid {
+    pub fn new(color: Color) -> Self {
+        Self { color }
+    }
+}
+
+impl From<Color> for Box<dyn Texture> {
+    fn from(color: Color) -> Self {
+        Box::new(Solid::new(color))
+    }
+}
+
+#[typetag::serde]
+impl Texture for Solid {
+    fn value(&self, _: (f64, f64), _: &Point, _: f64) -> Color {
+        self.color
+    }
+}
@@ -0,0 +1,70 @@
+use crate::{
+    textures::solid::Solid,
+    utilities::{color::Color, point::Point},
+};
+
+use serde::{Deserialize, Deserializer};
+
+/// Color that varies over a surface, looked up wherever a ray hits it
+#[typetag::serde(tag = "type")]
+pub trait Texture: Send + Sync {
+    /// Color at surface coordinates `uv`, position `point` in the coordinates of the shape that
+    /// was hit, and ray `time`
+    fn value(&self, uv: (f64, f64), point: &Point, time: f64) -> Color;
+}
+
+/// Scene file representation of a texture, where a plain color is short for `Solid`
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TextureFile {
+    Color(Color),
+    Texture(Box<dyn Texture>),
+}
+
+/// Read a texture, or a plain color as a `Solid` texture, so that scene files written before
+/// textures existed still load
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Box<dyn Texture>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(match TextureFile::deserialize(deserializer)? {
+        TextureFile::Color(color) => Box::new(Solid::new(color)),
+        TextureFile::Texture(texture) => texture,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use crate::{
+        textures::texture::{self, Texture},
+        utilities::{color::Color, point::Point},
+    };
+
+    #[derive(Deserialize)]
+    struct Albedo {
+        #[serde(deserialize_with = "texture::deserialize")]
+        albedo: Box<dyn Texture>,
+    }
+
+    #[test]
+    fn can_deserialize_color() {
+        let file: Albedo =
+            serde_yml::from_str("albedo: {r: 0.5, g: 0.25, b: 1.0, a: 255}").unwrap();
+        let color = file.albedo.value((0., 0.), &Point::origin(), 0.);
+        assert_eq!(color, Color::rgb(0.5, 0.25, 1.));
+    }
+
+    #[test]
+    fn can_deserialize_texture() {
+        let file: Albedo = serde_yml::from_str(
+            "albedo:\n  type: Checker\n  even: {r: 1.0, g: 1.0, b: 1.0, a: 255}\n  odd:\n    type: Solid\n    color: {r: 0.0, g: 0.0, b: 0.0, a: 255}\n  scale: 1.0\n",
+        )
+        .unwrap();
+        let even = file.albedo.value((0., 0.), &Point::new(0.5, 0.5, 0.5), 0.);
+        let odd = file.albedo.value((0., 0.), &Point::new(1.5, 0.5, 0.5), 0.);
+        assert_eq!(even, Color::gray(1.));
+        assert_eq!(odd, Color::gray(0.));
+    }
+}
@@ -0,0 +1,63 @@
+use crate::{
+    textures::texture::Texture,
+    utilities::{color::Color, noise::Perlin, point::Point},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Smoky blend between two colors following Perlin turbulence
+#[derive(Serialize, Deserialize)]
+pub struct Turbulence {
+    low: Color,
+    high: Color,
+    /// Frequency of the noise; larger values give smaller features
+    scale: f64,
+    /// Number of layers of finer detail
+    octaves: u32,
+    #[serde(rename = "seed", default)]
+    noise: Perlin,
+}
+
+impl Turbulence {
+    #[allow(dead_code)]
+    pub fn new(low: Color, high: Color, scale: f64, octaves: u32, seed: u64) -> Self {
+        Self {
+            low,
+            high,
+            scale,
+            octaves,
+            noise: Perlin::new(seed),
+        }
+    }
+}
+
+#[typetag::serde]
+impl Texture for Turbulence {
+    fn value(&self, _: (f64, f64), point: &Point, _: f64) -> Color {
+        let t = self
+            .noise
+            .turbulence(&(self.scale * *point), self.octaves)
+            .clamp(0., 1.);
+        (1. - t) * self.low + t * self.high
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        textures::{texture::Texture, turbulence::Turbulence},
+        utilities::{color::Color, point::Point},
+    };
+
+    #[test]
+    fn can_blend() {
+        let texture = Turbulence::new(Color::gray(0.), Color::gray(1.), 4., 7, 1);
+        // Noise vanishes on the integer lattice
+        assert_eq!(
+            texture.value((0., 0.), &Point::origin(), 0.),
+            Color::gray(0.)
+        );
+        let color = texture.value((0., 0.), &Point::new(0.3, 0.1, 0.7), 0.);
+        assert!(color.r > 0. && color.r <= 1.);
+    }
+}
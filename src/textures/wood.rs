@@ -0,0 +1,78 @@
+use crate::{
+    textures::texture::Texture,
+    utilities::{color::Color, noise::Perlin, point::Point},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Growth rings around the y axis, alternating between `light` and `dark` wood, with Perlin
+/// noise warping the rings into grain
+#[derive(Serialize, Deserialize)]
+pub struct Wood {
+    light: Color,
+    dark: Color,
+    /// Number of rings per unit of distance from the y axis
+    scale: f64,
+    /// Number of layers of finer detail in the grain
+    octaves: u32,
+    /// How far the grain pushes rings out of round, in ring spacings
+    #[serde(default = "Wood::default_distortion")]
+    distortion: f64,
+    #[serde(rename = "seed", default)]
+    noise: Perlin,
+}
+
+impl Wood {
+    fn default_distortion() -> f64 {
+        0.5
+    }
+
+    #[allow(dead_code)]
+    pub fn new(
+        light: Color,
+        dark: Color,
+        scale: f64,
+        octaves: u32,
+        distortion: f64,
+        seed: u64,
+    ) -> Self {
+        Self {
+            light,
+            dark,
+            scale,
+            octaves,
+            distortion,
+            noise: Perlin::new(seed),
+        }
+    }
+}
+
+#[typetag::serde]
+impl Texture for Wood {
+    fn value(&self, _: (f64, f64), point: &Point, _: f64) -> Color {
+        let radius = point.x.hypot(point.z) * self.scale;
+        let rings =
+            radius + self.distortion * self.noise.turbulence(&(self.scale * *point), self.octaves);
+        // Each ring fades from light early wood to a dark band of late wood
+        let t = rings.rem_euclid(1.).powi(3);
+        (1. - t) * self.light + t * self.dark
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        textures::{texture::Texture, wood::Wood},
+        utilities::{color::Color, point::Point},
+    };
+
+    #[test]
+    fn can_draw_rings() {
+        let texture = Wood::new(Color::gray(0.), Color::gray(1.), 2., 4, 0., 1);
+        let value = |x, z| texture.value((0., 0.), &Point::new(x, 0.3, z), 0.).r;
+        // Rings are circles around the y axis, starting light and ending dark
+        assert!((value(0.2, 0.) - value(0., 0.2)).abs() < 1e-12);
+        assert!(value(0.01, 0.) < value(0.49, 0.));
+        assert!(value(0.51, 0.) < value(0.49, 0.));
+    }
+}
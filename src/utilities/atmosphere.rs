@@ -0,0 +1,89 @@
+use crate::{
+    materials::scatter::Material,
+    shapes::{hit::Hit, volume::Volume},
+    utilities::ray::Ray,
+};
+
+use serde::{Deserialize, Serialize};
+
+/// A medium such as haze filling all of the space between the scene's shapes
+///
+/// Rays that miss every shape travel through `extent` of the atmosphere before reaching the
+/// environment; without one, the atmosphere goes on forever and hides the environment.
+#[derive(Serialize, Deserialize)]
+pub struct Atmosphere {
+    /// Chance of hitting a particle per unit of distance
+    density: f64,
+    material: Material,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    extent: Option<f64>,
+}
+
+impl Atmosphere {
+    #[allow(dead_code)]
+    pub fn new(density: f64, material: Material, extent: Option<f64>) -> Self {
+        Self {
+            density,
+            material,
+            extent,
+        }
+    }
+
+    /// A particle the ray hits before reaching `surface`, the time of the closest hit on a
+    /// shape, or `None` if the ray makes it through
+    pub fn hit(&self, ray: &Ray, surface: Option<f64>) -> Option<Hit<'_>> {
+        let speed = ray.direction.len();
+        let limit = match (surface, self.extent) {
+            (Some(time), _) => time,
+            (None, Some(extent)) => extent / speed,
+            (None, None) => f64::INFINITY,
+        };
+        let time = Volume::free_path(self.density) / speed;
+        (time < limit).then(|| Volume::particle(&self.material, ray, time))
+    }
+
+    /// Fraction of light that crosses `distance` without hitting a particle, where `None` is
+    /// the distance to the environment
+    pub fn transmittance(&self, distance: Option<f64>) -> f64 {
+        match distance.or(self.extent) {
+            Some(distance) => (-self.density * distance).exp(),
+            None => 0.,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        materials::{phase::Phase, scatter::Scatter},
+        utilities::{atmosphere::Atmosphere, point::Point, ray::Ray},
+    };
+
+    #[test]
+    fn can_sample_distance() {
+        let atmosphere = Atmosphere::new(0.5, Box::new(Phase::random()), Some(4.));
+        let ray = Ray::new(Point::origin(), Point::new(0., 0., -2.), 0.);
+        let samples = 20000;
+        // A surface 1 unit of time away along a ray moving 2 units per unit of time
+        let passed = (0..samples)
+            .filter(|_| atmosphere.hit(&ray, Some(1.)).is_none())
+            .count();
+        let expected = atmosphere.transmittance(Some(2.));
+        assert!((passed as f64 / samples as f64 - expected).abs() < 0.02);
+        // Missing every shape, the ray crosses the whole extent
+        let passed = (0..samples)
+            .filter(|_| atmosphere.hit(&ray, None).is_none())
+            .count();
+        let expected = atmosphere.transmittance(None);
+        assert!((passed as f64 / samples as f64 - expected).abs() < 0.02);
+    }
+
+    #[test]
+    fn can_hide_environment() {
+        let atmosphere = Atmosphere::new(0.1, Box::new(Phase::random()), None);
+        assert_eq!(atmosphere.transmittance(None), 0.);
+        assert_eq!(atmosphere.transmittance(Some(0.)), 1.);
+        let ray = Ray::new(Point::origin(), Point::new(1., 0., 0.), 0.);
+        assert!(atmosphere.hit(&ray, None).is_some());
+    }
+}
@@ -20,6 +20,7 @@ pub struct CameraSettings {
 }
 
 impl CameraSettings {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         view_up: Point,
         position: Point,
@@ -91,6 +92,7 @@ impl Camera {
         }
     }
 
+    #[allow(dead_code)]
     pub fn default_from_image(image: &Image) -> Self {
         Self::new(
             &CameraSettings::new(
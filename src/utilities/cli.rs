@@ -0,0 +1,262 @@
+use std::path::Path;
+
+use clap::Parser;
+
+use crate::utilities::{colorspace::ColorSpace, image::ImageFormat, scene::Scene};
+
+/// Render a scene file with the path tracer
+#[derive(Parser)]
+#[command(about)]
+pub struct Args {
+    /// Scene file to render, with or without the `.scene` extension
+    #[arg(default_value = "scenes/test")]
+    pub scene: String,
+    /// Render the scene from `build_scene()` instead of a scene file
+    #[arg(long, conflicts_with = "scene")]
+    pub example: bool,
+    /// Where to write the render, defaults to `render/<scene name>`; the extension picks the format
+    #[arg(short, long)]
+    pub output: Option<String>,
+    /// Image format, used instead of the output file's extension
+    #[arg(short, long)]
+    pub format: Option<ImageFormat>,
+    /// Write the scene, including any overrides, to this scene file instead of rendering it
+    #[arg(long)]
+    pub save_scene: Option<String>,
+    /// Sample rays cast per pixel
+    #[arg(short = 's', long)]
+    pub msaa_samples: Option<f64>,
+    /// Maximum number of times a ray is allowed to bounce
+    #[arg(short = 'd', long)]
+    pub max_depth: Option<u64>,
+    /// Bounces before paths carrying little light may be ended early
+    #[arg(long)]
+    pub roulette_depth: Option<u64>,
+    /// Encoding for low dynamic range images: `linear`, `srgb`, or a gamma exponent
+    #[arg(short, long)]
+    pub color_space: Option<ColorSpace>,
+    /// Gamma exponent read like the older `gamma` scene setting: 1 means sRGB, and any other
+    /// value a plain gamma curve
+    #[arg(short, long, conflicts_with = "color_space", value_parser = parse_gamma)]
+    pub gamma: Option<ColorSpace>,
+    /// Exposure adjustment in stops, applied before tone mapping
+    #[arg(short, long, allow_negative_numbers = true)]
+    pub exposure: Option<f64>,
+    /// Trace a hero wavelength with each path, so that dispersive glass splits light
+    #[arg(long)]
+    pub spectral: bool,
+    /// Image width in pixels; if height is not given, the aspect ratio is kept
+    #[arg(long)]
+    pub width: Option<u64>,
+    /// Image height in pixels; if width is not given, the aspect ratio is kept
+    #[arg(long)]
+    pub height: Option<u64>,
+}
+
+impl Args {
+    /// Scene path without the `.scene` extension, as expected by `Scene::load`
+    pub fn scene_name(&self) -> &str {
+        self.scene.strip_suffix(".scene").unwrap_or(&self.scene)
+    }
+
+    /// Output path without an extension, and the format to write it in
+    pub fn output(&self) -> (String, ImageFormat) {
+        let output = self.output.clone().unwrap_or_else(|| {
+            let name = match self.example {
+                true => "example",
+                false => Path::new(self.scene_name())
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .unwrap_or("render"),
+            };
+            format!("render/{name}")
+        });
+
+        let path = Path::new(&output);
+        let extension_format = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(ImageFormat::from_extension);
+
+        // Only strip the extension if it names an image format, so `render/v1.2` stays intact
+        let filename = match extension_format {
+            Some(_) => path.with_extension("").to_str().unwrap().to_string(),
+            None => output.clone(),
+        };
+        let format = self.format.or(extension_format).unwrap_or(ImageFormat::Ppm);
+        (filename, format)
+    }
+
+    /// Replace scene settings with any values given on the command line
+    pub fn apply(&self, scene: &mut Scene) {
+        if let Some(msaa_samples) = self.msaa_samples {
+            scene.settings.render.msaa_samples = msaa_samples;
+        }
+        if let Some(max_depth) = self.max_depth {
+            scene.settings.render.max_depth = max_depth;
+        }
+        if let Some(roulette_depth) = self.roulette_depth {
+            scene.settings.render.roulette_depth = roulette_depth;
+        }
+        if let Some(color_space) = self.color_space {
+            scene.settings.render.color_space = color_space;
+        }
+        if let Some(color_space) = self.gamma {
+            scene.settings.render.color_space = color_space;
+        }
+        if let Some(exposure) = self.exposure {
+            scene.settings.render.exposure = exposure;
+        }
+        if self.spectral {
+            scene.settings.render.spectral = true;
+        }
+
+        let aspect_ratio = scene.image.aspect_ratio();
+        let (width, height) = match (self.width, self.height) {
+            (None, None) => return,
+            (Some(width), Some(height)) => (width, height),
+            (Some(width), None) => (width, (width as f64 / aspect_ratio).round() as u64),
+            (None, Some(height)) => ((height as f64 * aspect_ratio).round() as u64, height),
+        };
+        scene.resize(width, height);
+    }
+}
+
+/// Parse a positive `--gamma` exponent into the color space a scene file's `gamma` gives
+fn parse_gamma(value: &str) -> Result<ColorSpace, String> {
+    value
+        .parse::<f64>()
+        .ok()
+        .filter(|gamma| *gamma > 0.)
+        .map(ColorSpace::from_gamma)
+        .ok_or_else(|| format!("invalid gamma `{value}`, expected a positive number"))
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use crate::utilities::{
+        cli::Args,
+        colorspace::ColorSpace,
+        image::{Image, ImageFormat},
+        scenebuilder::build_scene,
+    };
+
+    #[test]
+    fn can_use_defaults() {
+        let args = Args::parse_from(["path-tracer"]);
+        assert_eq!(args.scene_name(), "scenes/test");
+        assert_eq!(
+            args.output(),
+            (String::from("render/test"), ImageFormat::Ppm)
+        );
+    }
+
+    #[test]
+    fn can_strip_scene_extension() {
+        let args = Args::parse_from(["path-tracer", "scenes/dof.scene"]);
+        assert_eq!(args.scene_name(), "scenes/dof");
+        assert_eq!(args.output().0, "render/dof");
+    }
+
+    #[test]
+    fn can_get_format_from_output() {
+        let args = Args::parse_from(["path-tracer", "-o", "out/dof.ppm"]);
+        assert_eq!(args.output(), (String::from("out/dof"), ImageFormat::Ppm));
+
+        let args = Args::parse_from(["path-tracer", "-o", "out/v1.2"]);
+        assert_eq!(args.output(), (String::from("out/v1.2"), ImageFormat::Ppm));
+
+        let args = Args::parse_from(["path-tracer", "-o", "out/dof.PNG"]);
+        assert_eq!(args.output(), (String::from("out/dof"), ImageFormat::Png));
+    }
+
+    #[test]
+    fn can_override_format() {
+        let args = Args::parse_from(["path-tracer", "-o", "out/dof.png", "-f", "png16"]);
+        assert_eq!(args.output(), (String::from("out/dof"), ImageFormat::Png16));
+    }
+
+    #[test]
+    fn can_reject_unknown_format() {
+        assert!(Args::try_parse_from(["path-tracer", "-f", "gif"]).is_err());
+    }
+
+    #[test]
+    fn can_override_settings() {
+        let args = Args::parse_from([
+            "path-tracer",
+            "--msaa-samples",
+            "3",
+            "-d",
+            "4",
+            "--roulette-depth",
+            "2",
+            "-c",
+            "2.2",
+            "-e",
+            "-1.5",
+            "--spectral",
+            "--width",
+            "200",
+            "--height",
+            "100",
+        ]);
+        let mut scene = build_scene();
+        args.apply(&mut scene);
+        assert_eq!(scene.settings.render.msaa_samples, 3.);
+        assert_eq!(scene.settings.render.max_depth, 4);
+        assert_eq!(scene.settings.render.roulette_depth, 2);
+        assert_eq!(
+            scene.settings.render.color_space,
+            ColorSpace::Gamma { gamma: 2.2 }
+        );
+        assert_eq!(scene.settings.render.exposure, -1.5);
+        assert!(scene.settings.render.spectral);
+        assert_eq!(scene.image.buffer.len(), 200 * 100);
+        assert_eq!(scene.settings.camera.aspect_ratio, 2.);
+    }
+
+    #[test]
+    fn can_override_color_space() {
+        let args = Args::parse_from(["path-tracer", "--color-space", "linear"]);
+        let mut scene = build_scene();
+        args.apply(&mut scene);
+        assert_eq!(scene.settings.render.color_space, ColorSpace::Linear);
+        assert!(Args::try_parse_from(["path-tracer", "-c", "cmyk"]).is_err());
+    }
+
+    #[test]
+    fn can_override_gamma() {
+        let args = Args::parse_from(["path-tracer", "--gamma", "1.8"]);
+        let mut scene = build_scene();
+        args.apply(&mut scene);
+        assert_eq!(
+            scene.settings.render.color_space,
+            ColorSpace::Gamma { gamma: 1.8 }
+        );
+        assert!(Args::try_parse_from(["path-tracer", "-g", "2.2", "-c", "srgb"]).is_err());
+        assert!(Args::try_parse_from(["path-tracer", "--gamma", "0"]).is_err());
+        assert!(Args::try_parse_from(["path-tracer", "--gamma", "-2"]).is_err());
+    }
+
+    #[test]
+    fn can_read_gamma_like_scene_files() {
+        // A gamma of 1 meant sRGB in older scene files, and means the same here
+        let args = Args::parse_from(["path-tracer", "-g", "1.0"]);
+        let mut scene = build_scene();
+        scene.settings.render.color_space = ColorSpace::Linear;
+        args.apply(&mut scene);
+        assert_eq!(scene.settings.render.color_space, ColorSpace::Srgb);
+    }
+
+    #[test]
+    fn can_keep_aspect_ratio() {
+        let args = Args::parse_from(["path-tracer", "--width", "400"]);
+        let mut scene = build_scene();
+        scene.image = Image::from_dimensions(200, 100);
+        args.apply(&mut scene);
+        assert_eq!((scene.image.width, scene.image.height), (400, 200));
+    }
+}
@@ -4,7 +4,10 @@ use rand::distributions::{Distribution, Uniform};
 
 use serde::{Deserialize, Serialize};
 
+use crate::utilities::colorspace::ColorSpace;
+
 const MAX_COLOR: f64 = 256.;
+const MAX_COLOR_16: f64 = 65536.;
 
 #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Color {
@@ -39,20 +42,78 @@ impl Color {
         }
     }
 
-    /// Format the color as a ppm triplet, applying gamma correction
-    pub fn as_string(&self, gamma: f64) -> String {
-        if gamma != 1. {
-            let ir = (MAX_COLOR * (self.r.powf(1.0 / gamma)).clamp(0.0, 1.0)) as u8;
-            let ig = (MAX_COLOR * (self.g.powf(1.0 / gamma)).clamp(0.0, 1.0)) as u8;
-            let ib = (MAX_COLOR * (self.b.powf(1.0 / gamma)).clamp(0.0, 1.0)) as u8;
-            return format!("{} {} {}\n", ir, ig, ib);
+    /// Value of the brightest channel
+    pub fn max_channel(&self) -> f64 {
+        self.r.max(self.g).max(self.b)
+    }
+
+    /// Whether every channel is zero or negative, meaning the color carries no light
+    pub fn is_black(&self) -> bool {
+        self.r <= 0. && self.g <= 0. && self.b <= 0.
+    }
+
+    /// Clamp each channel to 0..1 and encode it for display in `color_space`
+    pub fn encoded(&self, color_space: ColorSpace) -> [f64; 3] {
+        [self.r, self.g, self.b].map(|channel| color_space.encode(channel))
+    }
+
+    /// Quantize the encoded color to 8 bits per channel
+    pub fn as_rgb8(&self, color_space: ColorSpace) -> [u8; 3] {
+        self.encoded(color_space)
+            .map(|channel| (MAX_COLOR * channel) as u8)
+    }
+
+    /// Quantize the encoded color to 16 bits per channel
+    pub fn as_rgb16(&self, color_space: ColorSpace) -> [u16; 3] {
+        self.encoded(color_space)
+            .map(|channel| (MAX_COLOR_16 * channel) as u16)
+    }
+
+    /// Encode the linear color as Radiance RGBE: a shared exponent with an 8-bit mantissa per
+    /// channel. Negative channels are clamped to 0.
+    ///
+    /// https://www.graphics.cornell.edu/~bjw/rgbe.html
+    pub fn as_rgbe(&self) -> [u8; 4] {
+        let [r, g, b] = [self.r, self.g, self.b].map(|channel| channel.max(0.));
+        let max = r.max(g).max(b);
+        if max < 1e-32 || !max.is_finite() {
+            return [0, 0, 0, 0];
         }
-        return format!(
-            "{} {} {}\n",
-            (MAX_COLOR * self.r.clamp(0.0, 1.0)) as u8,
-            (MAX_COLOR * self.g.clamp(0.0, 1.0)) as u8,
-            (MAX_COLOR * self.b.clamp(0.0, 1.0)) as u8
-        );
+
+        // Split the brightest channel into a mantissa in 0.5..1 and a power of two
+        let exponent = max.log2().floor() + 1.;
+        let scale = MAX_COLOR / 2f64.powf(exponent);
+        [
+            (r * scale) as u8,
+            (g * scale) as u8,
+            (b * scale) as u8,
+            (exponent + 128.) as u8,
+        ]
+    }
+
+    /// Decode a Radiance RGBE pixel written by `as_rgbe`
+    pub fn from_rgbe(rgbe: &[u8; 4]) -> Color {
+        if rgbe[3] == 0 {
+            return Color::default();
+        }
+        // Take the middle of the range each mantissa covers
+        let scale = 2f64.powi(rgbe[3] as i32 - 128) / MAX_COLOR;
+        Color::rgb(
+            (rgbe[0] as f64 + 0.5) * scale,
+            (rgbe[1] as f64 + 0.5) * scale,
+            (rgbe[2] as f64 + 0.5) * scale,
+        )
+    }
+
+    /// Relative luminance of the linear color, using the Rec. 709 primaries
+    pub fn luminance(&self) -> f64 {
+        0.2126 * self.r + 0.7152 * self.g + 0.0722 * self.b
+    }
+
+    /// Format the color as a ppm triplet, encoded in `color_space`
+    pub fn as_string(&self, color_space: ColorSpace) -> String {
+        let [r, g, b] = self.as_rgb8(color_space);
+        format!("{} {} {}\n", r, g, b)
     }
 
     /// Generate a random color
@@ -114,6 +175,7 @@ impl Add for Color {
 #[cfg(test)]
 mod tests {
     use super::Color;
+    use crate::utilities::colorspace::ColorSpace;
 
     #[test]
     fn can_get_default() {
@@ -151,28 +213,107 @@ mod tests {
         assert_eq!(color.a, 255);
     }
 
+    #[test]
+    fn can_get_max_channel() {
+        assert_eq!(Color::rgb(0.2, 0.7, -1.).max_channel(), 0.7);
+    }
+
+    #[test]
+    fn can_check_black() {
+        assert!(Color::default().is_black());
+        assert!(Color::new(0., 0., 0., 10).is_black());
+        assert!(!Color::rgb(0., 0.1, 0.).is_black());
+    }
+
     #[test]
     fn can_get_string() {
         let color = Color::new(1., 0.8, 0.3, 255);
-        assert_eq!(color.as_string(1.), String::from("255 204 76\n"));
+        assert_eq!(
+            color.as_string(ColorSpace::Linear),
+            String::from("255 204 76\n")
+        );
     }
 
     #[test]
     fn can_get_string_out_of_bounds() {
         let color = Color::new(2., 0.8, -3.0, 255);
-        assert_eq!(color.as_string(1.), String::from("255 204 0\n"));
+        assert_eq!(
+            color.as_string(ColorSpace::Linear),
+            String::from("255 204 0\n")
+        );
     }
 
     #[test]
     fn can_get_string_gamma_brighter() {
         let color = Color::new(0.1, 0.8, 0.3, 255);
-        assert_eq!(color.as_string(1.1), String::from("31 208 85\n"));
+        assert_eq!(
+            color.as_string(ColorSpace::Gamma { gamma: 1.1 }),
+            String::from("31 208 85\n")
+        );
     }
 
     #[test]
     fn can_get_string_gamma_darker() {
         let color = Color::new(0.1, 0.8, 0.3, 255);
-        assert_eq!(color.as_string(0.9), String::from("19 199 67\n"));
+        assert_eq!(
+            color.as_string(ColorSpace::Gamma { gamma: 0.9 }),
+            String::from("19 199 67\n")
+        );
+    }
+
+    #[test]
+    fn can_get_rgb8() {
+        let color = Color::new(0.1, 0.8, 0.3, 255);
+        assert_eq!(
+            color.as_rgb8(ColorSpace::Gamma { gamma: 1.1 }),
+            [31, 208, 85]
+        );
+        assert_eq!(
+            Color::new(2., 0.8, -3.0, 255).as_rgb8(ColorSpace::Linear),
+            [255, 204, 0]
+        );
+    }
+
+    #[test]
+    fn can_get_rgb16() {
+        let color = Color::new(1., 0.5, 0., 255);
+        assert_eq!(color.as_rgb16(ColorSpace::Linear), [65535, 32768, 0]);
+        assert_eq!(
+            Color::new(-1., 2., 0.25, 255).as_rgb16(ColorSpace::Linear),
+            [0, 65535, 16384]
+        );
+    }
+
+    #[test]
+    fn can_get_rgb8_srgb() {
+        let color = Color::new(1., 0.5, 0.002, 255);
+        assert_eq!(color.as_rgb8(ColorSpace::Srgb), [255, 188, 6]);
+    }
+
+    #[test]
+    fn can_get_rgbe() {
+        assert_eq!(Color::rgb(1., 0.5, 0.).as_rgbe(), [128, 64, 0, 129]);
+        assert_eq!(Color::rgb(250., 0., -1.).as_rgbe(), [250, 0, 0, 136]);
+        assert_eq!(Color::default().as_rgbe(), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn can_read_rgbe() {
+        assert_eq!(
+            Color::from_rgbe(&[128, 64, 0, 129]),
+            Color::rgb(1.00390625, 0.50390625, 0.00390625)
+        );
+        assert_eq!(Color::from_rgbe(&[0, 0, 0, 0]), Color::default());
+        let color = Color::rgb(3.7, 0.2, 120.);
+        let decoded = Color::from_rgbe(&color.as_rgbe());
+        assert!((decoded.r - color.r).abs() < 0.01 * 120.);
+        assert!((decoded.b - color.b).abs() < 0.01 * 120.);
+    }
+
+    #[test]
+    fn can_get_luminance() {
+        assert!((Color::gray(1.).luminance() - 1.).abs() < 1e-12);
+        assert_eq!(Color::default().luminance(), 0.);
     }
 
     #[test]
@@ -224,6 +365,9 @@ mod tests {
         assert_eq!(color.g, 0.85);
         assert_eq!(color.b, 1.);
         assert_eq!(color.a, 255);
-        assert_eq!(color.as_string(1.), String::from("192 217 255\n"));
+        assert_eq!(
+            color.as_string(ColorSpace::Linear),
+            String::from("192 217 255\n")
+        );
     }
 }
@@ -0,0 +1,156 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+/// Transfer functions that encode linear radiance for display in low dynamic range images
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum ColorSpace {
+    /// Store linear values unchanged
+    Linear,
+    /// The piecewise sRGB curve expected by displays and image viewers
+    ///
+    /// https://en.wikipedia.org/wiki/SRGB#Transfer_function_(%22gamma%22)
+    #[default]
+    Srgb,
+    /// A plain power curve, `c^(1 / gamma)`
+    Gamma { gamma: f64 },
+}
+
+impl ColorSpace {
+    /// Color space for the older `gamma` setting: `gamma: 1.0` wrote linear values to files
+    /// viewers decode as sRGB, so only a deliberate exponent is kept as a plain gamma curve
+    pub fn from_gamma(gamma: f64) -> Self {
+        assert!(gamma > 0., "gamma must be positive, got {gamma}");
+        match gamma == 1. {
+            true => ColorSpace::Srgb,
+            false => ColorSpace::Gamma { gamma },
+        }
+    }
+
+    /// Encode a linear channel value, clamping the result to 0..1
+    pub fn encode(&self, channel: f64) -> f64 {
+        let channel = channel.clamp(0., 1.);
+        match *self {
+            ColorSpace::Linear => channel,
+            ColorSpace::Srgb => {
+                if channel <= 0.0031308 {
+                    12.92 * channel
+                } else {
+                    1.055 * channel.powf(1. / 2.4) - 0.055
+                }
+            }
+            ColorSpace::Gamma { gamma } => channel.powf(1. / gamma),
+        }
+    }
+
+    /// Undo `encode`, turning a stored 0..1 value back into linear light
+    pub fn decode(&self, channel: f64) -> f64 {
+        let channel = channel.clamp(0., 1.);
+        match *self {
+            ColorSpace::Linear => channel,
+            ColorSpace::Srgb => {
+                if channel <= 0.04045 {
+                    channel / 12.92
+                } else {
+                    ((channel + 0.055) / 1.055).powf(2.4)
+                }
+            }
+            ColorSpace::Gamma { gamma } => channel.powf(gamma),
+        }
+    }
+}
+
+impl FromStr for ColorSpace {
+    type Err = String;
+
+    /// Parse `linear`, `srgb`, or a number for a plain gamma curve
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_ascii_lowercase().as_str() {
+            "linear" => Ok(ColorSpace::Linear),
+            "srgb" => Ok(ColorSpace::Srgb),
+            other => other
+                .parse::<f64>()
+                .ok()
+                .filter(|gamma| *gamma > 0.)
+                .map(|gamma| ColorSpace::Gamma { gamma })
+                .ok_or_else(|| {
+                    format!("unknown color space `{name}`, expected linear, srgb or a gamma value")
+                }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utilities::colorspace::ColorSpace;
+
+    #[test]
+    fn can_encode_linear() {
+        assert_eq!(ColorSpace::Linear.encode(0.25), 0.25);
+        assert_eq!(ColorSpace::Linear.encode(2.), 1.);
+        assert_eq!(ColorSpace::Linear.encode(-1.), 0.);
+    }
+
+    #[test]
+    fn can_encode_srgb() {
+        let srgb = ColorSpace::Srgb;
+        assert_eq!(srgb.encode(0.), 0.);
+        assert!((srgb.encode(1.) - 1.).abs() < 1e-12);
+        // The linear toe
+        assert!((srgb.encode(0.002) - 0.02584).abs() < 1e-12);
+        // Middle gray is encoded close to half brightness
+        assert!((srgb.encode(0.214) - 0.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn can_join_srgb_segments() {
+        let srgb = ColorSpace::Srgb;
+        let below = srgb.encode(0.0031308 - 1e-9);
+        let above = srgb.encode(0.0031308 + 1e-9);
+        assert!((below - above).abs() < 1e-6);
+    }
+
+    #[test]
+    fn can_encode_gamma() {
+        let gamma = ColorSpace::Gamma { gamma: 2. };
+        assert_eq!(gamma.encode(0.25), 0.5);
+        assert_eq!(gamma.encode(4.), 1.);
+    }
+
+    #[test]
+    fn can_decode() {
+        [
+            ColorSpace::Linear,
+            ColorSpace::Srgb,
+            ColorSpace::Gamma { gamma: 2.2 },
+        ]
+        .iter()
+        .for_each(|color_space| {
+            [0., 0.002, 0.2, 0.5, 1.].iter().for_each(|channel| {
+                let round_trip = color_space.decode(color_space.encode(*channel));
+                assert!((round_trip - channel).abs() < 1e-12);
+            })
+        });
+    }
+
+    #[test]
+    fn can_parse() {
+        assert_eq!("linear".parse::<ColorSpace>(), Ok(ColorSpace::Linear));
+        assert_eq!("sRGB".parse::<ColorSpace>(), Ok(ColorSpace::Srgb));
+        assert_eq!(
+            "2.2".parse::<ColorSpace>(),
+            Ok(ColorSpace::Gamma { gamma: 2.2 })
+        );
+        assert!("-1".parse::<ColorSpace>().is_err());
+        assert!("rec709".parse::<ColorSpace>().is_err());
+    }
+
+    #[test]
+    fn can_deserialize() {
+        let color_space: ColorSpace = serde_yml::from_str("type: Gamma\ngamma: 1.8").unwrap();
+        assert_eq!(color_space, ColorSpace::Gamma { gamma: 1.8 });
+        let color_space: ColorSpace = serde_yml::from_str("type: Srgb").unwrap();
+        assert_eq!(color_space, ColorSpace::Srgb);
+    }
+}
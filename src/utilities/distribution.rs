@@ -0,0 +1,162 @@
+/// Piecewise constant distribution over 0..1 that picks each of its equally sized buckets in
+/// proportion to the bucket's weight
+///
+/// https://pbr-book.org/3ed-2018/Monte_Carlo_Integration/Sampling_Random_Variables#Example:Piecewise-Constant1DFunctions
+pub struct Distribution {
+    weights: Vec<f64>,
+    /// Running total of the normalized weights, starting at 0 and ending at 1
+    cdf: Vec<f64>,
+    /// Average weight, which normalizes the weights into densities
+    integral: f64,
+}
+
+impl Distribution {
+    /// Build a distribution from non-negative weights; if every weight is 0, buckets are
+    /// picked uniformly
+    pub fn new(weights: Vec<f64>) -> Self {
+        let count = weights.len() as f64;
+        let mut cdf = Vec::with_capacity(weights.len() + 1);
+        cdf.push(0.);
+        weights.iter().fold(0., |total, weight| {
+            let total = total + weight / count;
+            cdf.push(total);
+            total
+        });
+
+        let integral = cdf[weights.len()];
+        cdf.iter_mut().enumerate().for_each(|(index, value)| {
+            *value = match integral > 0. {
+                true => *value / integral,
+                false => index as f64 / count,
+            }
+        });
+
+        Self {
+            weights,
+            cdf,
+            integral,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.weights.len()
+    }
+
+    /// Average of the weights
+    pub fn integral(&self) -> f64 {
+        self.integral
+    }
+
+    /// Map a uniform random number to a position in 0..1, returning the position, its density,
+    /// and the bucket it falls in
+    pub fn sample(&self, random: f64) -> (f64, f64, usize) {
+        // Find the last bucket that starts at or before `random`
+        let index = self
+            .cdf
+            .partition_point(|value| *value <= random)
+            .clamp(1, self.len())
+            - 1;
+        let width = self.cdf[index + 1] - self.cdf[index];
+        let offset = match width > 0. {
+            true => (random - self.cdf[index]) / width,
+            false => 0.,
+        };
+        let position = (index as f64 + offset) / self.len() as f64;
+        (position, self.pdf(index), index)
+    }
+
+    /// Density of `sample` picking a position in bucket `index`
+    pub fn pdf(&self, index: usize) -> f64 {
+        match self.integral > 0. {
+            true => self.weights[index] / self.integral,
+            false => 1.,
+        }
+    }
+}
+
+/// Piecewise constant distribution over the unit square, picking a row from the total weight
+/// of each row and then a column within that row
+pub struct Distribution2D {
+    rows: Vec<Distribution>,
+    marginal: Distribution,
+}
+
+impl Distribution2D {
+    /// Build a distribution from `weights` stored row by row, `width` values per row
+    pub fn new(weights: &[f64], width: usize) -> Self {
+        let rows: Vec<Distribution> = weights
+            .chunks_exact(width)
+            .map(|row| Distribution::new(row.to_vec()))
+            .collect();
+        let marginal = Distribution::new(rows.iter().map(Distribution::integral).collect());
+        Self { rows, marginal }
+    }
+
+    /// Map two uniform random numbers to a position `(x, y)` in the unit square, returning the
+    /// position and its density
+    pub fn sample(&self, random_x: f64, random_y: f64) -> ((f64, f64), f64) {
+        let (y, pdf_y, row) = self.marginal.sample(random_y);
+        let (x, pdf_x, _) = self.rows[row].sample(random_x);
+        ((x, y), pdf_x * pdf_y)
+    }
+
+    /// Density of `sample` picking the position `(x, y)`
+    pub fn pdf(&self, x: f64, y: f64) -> f64 {
+        let row = ((y * self.rows.len() as f64) as usize).min(self.rows.len() - 1);
+        let columns = &self.rows[row];
+        let col = ((x * columns.len() as f64) as usize).min(columns.len() - 1);
+        match self.marginal.integral() > 0. {
+            true => columns.weights[col] / self.marginal.integral(),
+            false => 1.,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use crate::utilities::distribution::{Distribution, Distribution2D};
+
+    #[test]
+    fn can_sample_buckets() {
+        let distribution = Distribution::new(vec![1., 0., 3.]);
+        assert_eq!(distribution.integral(), 4. / 3.);
+        assert_eq!(distribution.sample(0.), (0., 0.75, 0));
+        assert_eq!(distribution.sample(0.125).2, 0);
+        let (position, pdf, index) = distribution.sample(0.625);
+        assert_eq!(index, 2);
+        assert!((position - 5. / 6.).abs() < 1e-12);
+        assert_eq!(pdf, 2.25);
+        assert_eq!(distribution.pdf(1), 0.);
+    }
+
+    #[test]
+    fn can_sample_uniformly_without_weight() {
+        let distribution = Distribution::new(vec![0., 0.]);
+        assert_eq!(distribution.sample(0.75), (0.75, 1., 1));
+        assert_eq!(distribution.pdf(0), 1.);
+    }
+
+    #[test]
+    fn can_sample_2d() {
+        let weights = [1., 0., 0., 2., 3., 2.];
+        let distribution = Distribution2D::new(&weights, 3);
+
+        // Densities integrate to 1 over the unit square
+        let total: f64 = (0..2)
+            .flat_map(|row| (0..3).map(move |col| (col, row)))
+            .map(|(col, row)| {
+                distribution.pdf((col as f64 + 0.5) / 3., (row as f64 + 0.5) / 2.) / 6.
+            })
+            .sum();
+        assert!((total - 1.).abs() < 1e-12);
+
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000 {
+            let ((x, y), pdf) = distribution.sample(rng.gen(), rng.gen());
+            assert!(pdf > 0.);
+            assert!((distribution.pdf(x, y) - pdf).abs() < 1e-9);
+        }
+    }
+}
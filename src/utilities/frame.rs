@@ -0,0 +1,59 @@
+use crate::utilities::{point::Point, ray::Ray};
+
+/// Axes placed at `origin` with `z` pointing along a chosen direction, so that shapes built
+/// around an axis can be solved as if they were standing upright at the origin
+///
+/// The axes are orthonormal, so distances along rays are the same in both spaces.
+pub struct Frame {
+    origin: Point,
+    x: Point,
+    y: Point,
+    z: Point,
+}
+
+impl Frame {
+    pub fn new(origin: Point, axis: Point) -> Self {
+        let z = axis.normalized();
+        let (x, y) = z.orthonormal_basis();
+        Self { origin, x, y, z }
+    }
+
+    pub fn to_local(&self, point: &Point) -> Point {
+        self.to_local_vector(&(*point - self.origin))
+    }
+
+    pub fn to_local_vector(&self, vector: &Point) -> Point {
+        Point::new(vector.dot(self.x), vector.dot(self.y), vector.dot(self.z))
+    }
+
+    pub fn to_local_ray(&self, ray: &Ray) -> Ray {
+        Ray::new(
+            self.to_local(&ray.origin),
+            self.to_local_vector(&ray.direction),
+            ray.time,
+        )
+    }
+
+    pub fn to_world_vector(&self, vector: &Point) -> Point {
+        vector.x * self.x + vector.y * self.y + vector.z * self.z
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utilities::{frame::Frame, point::Point};
+
+    #[test]
+    fn can_round_trip() {
+        let frame = Frame::new(Point::new(1., 2., 3.), Point::new(0., 3., 4.));
+        assert!(
+            (frame.to_local_vector(&Point::new(0., 0.6, 0.8)) - Point::new(0., 0., 1.)).len()
+                < 1e-12
+        );
+        assert!(frame.to_local(&Point::new(1., 2., 3.)).len() < 1e-12);
+
+        let vector = Point::new(-2., 0.5, 7.);
+        let back = frame.to_world_vector(&frame.to_local_vector(&vector));
+        assert!((back - vector).len() < 1e-12);
+    }
+}
@@ -1,22 +1,115 @@
 use std::{
-    f64::consts::PI,
-    fs::File,
-    io::{BufWriter, Write},
+    fmt::{self, Display},
+    fs::{create_dir_all, File},
+    io::{BufRead, BufReader, BufWriter, Read, Seek, Write},
     iter::repeat,
     path::Path,
+    str::FromStr,
     time::Instant,
 };
 
+use exr::prelude::{ReadChannels, ReadLayers, SpecificChannels, Vec2, WritableImage};
 use format_num::format_num;
+use png::{
+    BitDepth, ColorType, Decoder, Encoder, ScaledFloat, SrgbRenderingIntent, Transformations,
+};
 use serde::{Deserialize, Serialize};
 
-use crate::utilities::color::Color;
+use crate::utilities::{color::Color, colorspace::ColorSpace, tonemap::ToneMap};
 
+#[allow(dead_code)]
 pub enum Orientation {
     Landscape,
     Portrait,
 }
 
+/// File formats the image buffer can be written to
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ImageFormat {
+    /// ASCII `P3` portable pixmap
+    Ppm,
+    /// PNG with 8 bits per channel
+    Png,
+    /// PNG with 16 bits per channel
+    Png16,
+    /// Radiance RGBE, storing unclamped linear color
+    Hdr,
+    /// Portable float map, storing unclamped linear color as 32-bit floats
+    Pfm,
+    /// OpenEXR, storing unclamped linear color as 32-bit floats
+    Exr,
+}
+
+impl ImageFormat {
+    /// Every format, in the order they are matched against file extensions
+    pub const ALL: [ImageFormat; 6] = [
+        ImageFormat::Ppm,
+        ImageFormat::Png,
+        ImageFormat::Png16,
+        ImageFormat::Hdr,
+        ImageFormat::Pfm,
+        ImageFormat::Exr,
+    ];
+
+    /// Name used to select the format on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            ImageFormat::Ppm => "ppm",
+            ImageFormat::Png => "png",
+            ImageFormat::Png16 => "png16",
+            ImageFormat::Hdr => "hdr",
+            ImageFormat::Pfm => "pfm",
+            ImageFormat::Exr => "exr",
+        }
+    }
+
+    /// File extension used for the format, without the leading `.`
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Ppm => "ppm",
+            ImageFormat::Png | ImageFormat::Png16 => "png",
+            ImageFormat::Hdr => "hdr",
+            ImageFormat::Pfm => "pfm",
+            ImageFormat::Exr => "exr",
+        }
+    }
+
+    /// Whether the format stores the unclamped linear buffer rather than display values
+    pub fn is_high_dynamic_range(&self) -> bool {
+        matches!(self, ImageFormat::Hdr | ImageFormat::Pfm | ImageFormat::Exr)
+    }
+
+    /// Find the format for a file extension, ignoring case
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|format| format.extension().eq_ignore_ascii_case(extension))
+    }
+}
+
+impl FromStr for ImageFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|format| format.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                let supported: Vec<&str> = Self::ALL.iter().map(|format| format.name()).collect();
+                format!(
+                    "unsupported image format `{s}`, expected one of: {}",
+                    supported.join(", ")
+                )
+            })
+    }
+}
+
+impl Display for ImageFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
 #[derive(Deserialize, Serialize)]
 pub struct Image {
     pub width: u64,
@@ -64,6 +157,7 @@ impl Image {
     }
 
     /// 16:9 widescreen canvas of the specified height
+    #[allow(dead_code)]
     pub fn widescreen(height: u64, orientation: Orientation) -> Self {
         match orientation {
             Orientation::Landscape => Image::from_ratio(height, 16. / 9.),
@@ -77,6 +171,7 @@ impl Image {
     }
 
     /// 4K image size
+    #[allow(dead_code)]
     pub fn uhd(orientation: Orientation) -> Self {
         match orientation {
             Orientation::Landscape => Image::from_dimensions(3840, 2160),
@@ -85,6 +180,7 @@ impl Image {
     }
 
     /// 2K image size
+    #[allow(dead_code)]
     pub fn qhd(orientation: Orientation) -> Self {
         match orientation {
             Orientation::Landscape => Image::from_dimensions(2560, 1440),
@@ -93,6 +189,7 @@ impl Image {
     }
 
     /// HD image size
+    #[allow(dead_code)]
     pub fn hd(orientation: Orientation) -> Self {
         match orientation {
             Orientation::Landscape => Image::from_dimensions(1920, 1080),
@@ -103,6 +200,7 @@ impl Image {
     /// iPhone 13 Pro Max image size
     ///
     /// Scale reduces the size by that amount. A value of `scale = 4` renders at 1/4 scale.
+    #[allow(dead_code)]
     pub fn mobile(scale: u64, orientation: Orientation) -> Self {
         match orientation {
             Orientation::Landscape => Image::from_dimensions(2778 / scale, 1284 / scale),
@@ -121,34 +219,51 @@ impl Image {
 
     /// Returns an iterator that yields coordinate pairs, starting from
     /// (max_y, min_x), i.e. top left to bottom right, in the format of (row, col)
+    #[allow(dead_code)]
     pub fn walk(image: Self) -> impl Iterator<Item = (u64, u64)> {
         (0..image.height)
             .rev()
             .flat_map(move |row| repeat(row).zip(0..image.width))
     }
 
-    /// Write the image buffer to a `.pmm` file
-    pub fn save(&self, filepath: &str, filename: &str, gamma: f64) {
+    /// Write the image buffer to `filename` with the extension for `format`
+    ///
+    /// Exposure, tone mapping and color space encoding only apply to low dynamic range formats;
+    /// `hdr`, `pfm` and `exr` store the linear buffer as-is.
+    pub fn save(
+        &self,
+        filepath: &str,
+        filename: &str,
+        format: ImageFormat,
+        tone_map: ToneMap,
+        exposure: f64,
+        color_space: ColorSpace,
+    ) {
         // Generate filepath
-        let path = Path::new(filepath).join(format!("{filename}.ppm"));
+        let path = Path::new(filepath).join(format!("{filename}.{}", format.extension()));
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent).unwrap();
+        }
 
         // Create file
         let file = File::create(&path).unwrap();
         let mut buf_file = BufWriter::new(file);
 
-        // Add ppm metadata
-        writeln!(&mut buf_file, "P3").unwrap();
-        writeln!(&mut buf_file, "{} {}", self.width, self.height).unwrap();
-        writeln!(&mut buf_file, "255").unwrap();
-
-        // Write ppm colors
         println!("Writing file...");
         let now = Instant::now();
-        self.buffer.iter().for_each(|color| {
-            buf_file
-                .write_all(color.as_string(gamma).as_bytes())
-                .unwrap();
-        });
+        let display = match format.is_high_dynamic_range() {
+            true => None,
+            false => Some(self.tone_mapped(tone_map, exposure)),
+        };
+        let display = display.as_ref().unwrap_or(self);
+        match format {
+            ImageFormat::Ppm => display.write_ppm(&mut buf_file, color_space),
+            ImageFormat::Png => display.write_png(&mut buf_file, BitDepth::Eight, color_space),
+            ImageFormat::Png16 => display.write_png(&mut buf_file, BitDepth::Sixteen, color_space),
+            ImageFormat::Hdr => self.write_hdr(&mut buf_file),
+            ImageFormat::Pfm => self.write_pfm(&mut buf_file),
+            ImageFormat::Exr => self.write_exr(&mut buf_file),
+        }
         buf_file.flush().unwrap();
 
         // Print metrics
@@ -162,6 +277,427 @@ impl Image {
             );
         }
     }
+
+    /// Copy of the image with exposure and a tone mapping operator applied to every pixel
+    pub fn tone_mapped(&self, tone_map: ToneMap, exposure: f64) -> Image {
+        Image {
+            width: self.width,
+            height: self.height,
+            buffer: self
+                .buffer
+                .iter()
+                .map(|color| tone_map.apply(*color, exposure))
+                .collect(),
+        }
+    }
+
+    /// Write the image buffer as an ASCII `.ppm`
+    fn write_ppm(&self, writer: &mut impl Write, color_space: ColorSpace) {
+        // Add ppm metadata
+        writeln!(writer, "P3").unwrap();
+        writeln!(writer, "{} {}", self.width, self.height).unwrap();
+        writeln!(writer, "255").unwrap();
+
+        // Write ppm colors
+        self.buffer.iter().for_each(|color| {
+            writer
+                .write_all(color.as_string(color_space).as_bytes())
+                .unwrap();
+        });
+    }
+
+    /// Write the image buffer as an RGB `.png` with 8 or 16 bits per channel
+    ///
+    /// The file is tagged with the color space so viewers decode it with the matching curve
+    fn write_png(&self, writer: &mut impl Write, bit_depth: BitDepth, color_space: ColorSpace) {
+        let mut encoder = Encoder::new(writer, self.width as u32, self.height as u32);
+        encoder.set_color(ColorType::Rgb);
+        encoder.set_depth(bit_depth);
+        match color_space {
+            ColorSpace::Srgb => encoder.set_source_srgb(SrgbRenderingIntent::Perceptual),
+            ColorSpace::Linear => encoder.set_source_gamma(ScaledFloat::new(1.)),
+            ColorSpace::Gamma { gamma } => {
+                encoder.set_source_gamma(ScaledFloat::new(1. / gamma as f32))
+            }
+        }
+
+        // Samples are stored big-endian, row by row from the top of the image
+        let data: Vec<u8> = match bit_depth {
+            BitDepth::Sixteen => self
+                .buffer
+                .iter()
+                .flat_map(|color| color.as_rgb16(color_space))
+                .flat_map(u16::to_be_bytes)
+                .collect(),
+            _ => self
+                .buffer
+                .iter()
+                .flat_map(|color| color.as_rgb8(color_space))
+                .collect(),
+        };
+
+        let mut png_writer = encoder.write_header().unwrap();
+        png_writer.write_image_data(&data).unwrap();
+        png_writer.finish().unwrap();
+    }
+
+    /// Write the image buffer as a Radiance `.hdr`
+    ///
+    /// Scanlines use the run-length encoded layout, storing each channel as literal runs
+    /// so that no pixel can be mistaken for an old-style run marker
+    fn write_hdr(&self, writer: &mut impl Write) {
+        writeln!(writer, "#?RADIANCE").unwrap();
+        writeln!(writer, "FORMAT=32-bit_rle_rgbe").unwrap();
+        writeln!(writer).unwrap();
+        writeln!(writer, "-Y {} +X {}", self.height, self.width).unwrap();
+
+        // Run-length encoding is only defined for scanlines of this width
+        let encode = (8..0x8000).contains(&self.width);
+        self.buffer
+            .chunks(self.width as usize)
+            .for_each(|scanline| {
+                let pixels: Vec<[u8; 4]> = scanline.iter().map(Color::as_rgbe).collect();
+                if !encode {
+                    pixels
+                        .iter()
+                        .for_each(|pixel| writer.write_all(pixel).unwrap());
+                    return;
+                }
+
+                writer
+                    .write_all(&[2, 2, (self.width >> 8) as u8, self.width as u8])
+                    .unwrap();
+                (0..4).for_each(|channel| {
+                    let values: Vec<u8> = pixels.iter().map(|pixel| pixel[channel]).collect();
+                    values.chunks(128).for_each(|run| {
+                        writer.write_all(&[run.len() as u8]).unwrap();
+                        writer.write_all(run).unwrap();
+                    });
+                });
+            });
+    }
+
+    /// Write the image buffer as a little-endian `.pfm`, which stores rows bottom to top
+    fn write_pfm(&self, writer: &mut impl Write) {
+        writeln!(writer, "PF").unwrap();
+        writeln!(writer, "{} {}", self.width, self.height).unwrap();
+        // A negative scale marks little-endian data
+        writeln!(writer, "-1.0").unwrap();
+
+        self.buffer
+            .chunks(self.width as usize)
+            .rev()
+            .flatten()
+            .for_each(|color| {
+                [color.r, color.g, color.b].iter().for_each(|channel| {
+                    writer.write_all(&(*channel as f32).to_le_bytes()).unwrap()
+                });
+            });
+    }
+
+    /// Write the image buffer as an OpenEXR file with 32-bit float channels
+    fn write_exr(&self, writer: &mut (impl Write + Seek)) {
+        let width = self.width as usize;
+        let channels = SpecificChannels::rgb(|Vec2(x, y): Vec2<usize>| {
+            let color = self.buffer[y * width + x];
+            (color.r as f32, color.g as f32, color.b as f32)
+        });
+        exr::prelude::Image::from_channels((width, self.height as usize), channels)
+            .write()
+            .to_buffered(writer)
+            .unwrap();
+    }
+
+    /// Read a high dynamic range image, picking the format from the file extension
+    pub fn load(path: &str) -> Self {
+        let format = Path::new(path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(ImageFormat::from_extension);
+        let file =
+            File::open(path).unwrap_or_else(|error| panic!("Unable to open {path}: {error}"));
+        let mut reader = BufReader::new(file);
+        match format {
+            Some(ImageFormat::Ppm) => Self::read_ppm(&mut reader),
+            Some(ImageFormat::Png | ImageFormat::Png16) => Self::read_png(reader),
+            Some(ImageFormat::Hdr) => Self::read_hdr(&mut reader),
+            Some(ImageFormat::Pfm) => Self::read_pfm(&mut reader),
+            Some(ImageFormat::Exr) => Self::read_exr(reader),
+            None => {
+                panic!("Unable to load {path}: expected a .ppm, .png, .hdr, .pfm or .exr image")
+            }
+        }
+    }
+
+    /// Read an ASCII (`P3`) or binary (`P6`) `.ppm`, assuming sRGB encoded values
+    fn read_ppm(reader: &mut impl BufRead) -> Self {
+        let mut data = vec![];
+        reader.read_to_end(&mut data).unwrap();
+
+        // The header is four whitespace separated tokens, and comments run to the end of a line
+        let mut position = 0;
+        let mut read_token = || {
+            loop {
+                match data.get(position) {
+                    Some(b'#') => {
+                        while data.get(position).is_some_and(|byte| *byte != b'\n') {
+                            position += 1;
+                        }
+                    }
+                    Some(byte) if byte.is_ascii_whitespace() => position += 1,
+                    _ => break,
+                }
+            }
+            let start = position;
+            while data
+                .get(position)
+                .is_some_and(|byte| !byte.is_ascii_whitespace())
+            {
+                position += 1;
+            }
+            String::from_utf8_lossy(&data[start..position]).to_string()
+        };
+        let magic = read_token();
+        let width: u64 = read_token().parse().unwrap();
+        let height: u64 = read_token().parse().unwrap();
+        let max_value: f64 = read_token().parse().unwrap();
+
+        let samples: Vec<f64> = match magic.as_str() {
+            "P3" => (0..width * height * 3)
+                .map(|_| read_token().parse::<f64>().unwrap())
+                .collect(),
+            // A single whitespace byte separates the header from the samples
+            "P6" => {
+                let start = position + 1;
+                match max_value < 256. {
+                    true => data[start..].iter().map(|byte| *byte as f64).collect(),
+                    false => data[start..]
+                        .chunks_exact(2)
+                        .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]) as f64)
+                        .collect(),
+                }
+            }
+            other => panic!("Unsupported .ppm type {other}"),
+        };
+
+        let buffer = samples
+            .chunks_exact(3)
+            .take((width * height) as usize)
+            .map(|rgb| {
+                let [r, g, b] =
+                    [0, 1, 2].map(|index| ColorSpace::Srgb.decode(rgb[index] / max_value));
+                Color::rgb(r, g, b)
+            })
+            .collect();
+        Image {
+            width,
+            height,
+            buffer,
+        }
+    }
+
+    /// Read a `.png` of any color type and bit depth, decoding it to linear color with the
+    /// curve it is tagged with, or sRGB if it has none
+    fn read_png(reader: impl BufRead + Seek) -> Self {
+        let mut decoder = Decoder::new(reader);
+        decoder.set_transformations(Transformations::EXPAND);
+        let mut png_reader = decoder.read_info().unwrap();
+        let info = png_reader.info();
+        let color_space = match (info.srgb, info.gamma()) {
+            (None, Some(gamma)) if gamma.into_value() == 1. => ColorSpace::Linear,
+            (None, Some(gamma)) => ColorSpace::Gamma {
+                gamma: 1. / gamma.into_value() as f64,
+            },
+            _ => ColorSpace::Srgb,
+        };
+
+        let mut data = vec![0; png_reader.output_buffer_size().unwrap()];
+        let frame = png_reader.next_frame(&mut data).unwrap();
+        let samples: Vec<f64> = match frame.bit_depth {
+            BitDepth::Sixteen => data[..frame.buffer_size()]
+                .chunks_exact(2)
+                .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]) as f64 / 65535.)
+                .collect(),
+            _ => data[..frame.buffer_size()]
+                .iter()
+                .map(|byte| *byte as f64 / 255.)
+                .collect(),
+        };
+
+        // Alpha is dropped, and grayscale is copied to every channel
+        let channels = frame.color_type.samples();
+        let buffer = samples
+            .chunks_exact(channels)
+            .map(|pixel| {
+                let decode = |index: usize| color_space.decode(pixel[index]);
+                match channels {
+                    1 | 2 => Color::gray(decode(0)),
+                    _ => Color::rgb(decode(0), decode(1), decode(2)),
+                }
+            })
+            .collect();
+        Image {
+            width: frame.width as u64,
+            height: frame.height as u64,
+            buffer,
+        }
+    }
+
+    /// Read a Radiance `.hdr` with either flat or run-length encoded scanlines
+    fn read_hdr(reader: &mut impl BufRead) -> Self {
+        // The header ends with a blank line, followed by the resolution
+        let mut line = String::new();
+        loop {
+            line.clear();
+            reader.read_line(&mut line).unwrap();
+            let header = line.trim();
+            if header.is_empty() {
+                break;
+            }
+            if let Some(format) = header.strip_prefix("FORMAT=") {
+                assert_eq!(format, "32-bit_rle_rgbe", "Unsupported .hdr format");
+            }
+        }
+        line.clear();
+        reader.read_line(&mut line).unwrap();
+        let resolution: Vec<&str> = line.split_whitespace().collect();
+        let (height, width) = match resolution[..] {
+            ["-Y", height, "+X", width] => (height.parse().unwrap(), width.parse().unwrap()),
+            _ => panic!("Unsupported .hdr orientation {}", line.trim()),
+        };
+
+        let mut buffer = Vec::with_capacity((width * height) as usize);
+        let mut scanline = vec![[0u8; 4]; width as usize];
+        (0..height).for_each(|_| {
+            let mut start = [0u8; 4];
+            reader.read_exact(&mut start).unwrap();
+            let encoded = start[0] == 2
+                && start[1] == 2
+                && ((start[2] as u64) << 8 | start[3] as u64) == width;
+            if encoded {
+                // Each channel is stored separately as runs of one value or literal values
+                (0..4).for_each(|channel| {
+                    let mut col = 0;
+                    while col < scanline.len() {
+                        let mut count = [0u8; 1];
+                        reader.read_exact(&mut count).unwrap();
+                        let run = count[0] > 128;
+                        let length = match run {
+                            true => count[0] as usize - 128,
+                            false => count[0] as usize,
+                        };
+                        // Empty or overlong runs come from corrupt or truncated files
+                        let remaining = scanline.len() - col;
+                        assert!(
+                            length > 0 && length <= remaining,
+                            "Invalid .hdr run of {length} with {remaining} pixels left in the scanline"
+                        );
+                        let end = col + length;
+                        match run {
+                            true => {
+                                let mut value = [0u8; 1];
+                                reader.read_exact(&mut value).unwrap();
+                                scanline[col..end]
+                                    .iter_mut()
+                                    .for_each(|pixel| pixel[channel] = value[0]);
+                            }
+                            false => {
+                                let mut values = vec![0u8; length];
+                                reader.read_exact(&mut values).unwrap();
+                                scanline[col..end]
+                                    .iter_mut()
+                                    .zip(values)
+                                    .for_each(|(pixel, value)| pixel[channel] = value);
+                            }
+                        }
+                        col = end;
+                    }
+                });
+            } else {
+                scanline[0] = start;
+                scanline[1..]
+                    .iter_mut()
+                    .for_each(|pixel| reader.read_exact(pixel).unwrap());
+            }
+            buffer.extend(scanline.iter().map(Color::from_rgbe));
+        });
+
+        Image {
+            width,
+            height,
+            buffer,
+        }
+    }
+
+    /// Read a `.pfm` in either byte order, with color (`PF`) or grayscale (`Pf`) pixels
+    fn read_pfm(reader: &mut impl BufRead) -> Self {
+        let mut read_token = || {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            line
+        };
+        let channels = match read_token().trim() {
+            "PF" => 3,
+            "Pf" => 1,
+            other => panic!("Unsupported .pfm type {other}"),
+        };
+        let dimensions: Vec<u64> = read_token()
+            .split_whitespace()
+            .map(|value| value.parse().unwrap())
+            .collect();
+        let (width, height) = (dimensions[0], dimensions[1]);
+        let little_endian = read_token().trim().parse::<f64>().unwrap() < 0.;
+
+        let mut data = vec![0u8; (width * height) as usize * channels * 4];
+        reader.read_exact(&mut data).unwrap();
+        let values: Vec<f64> = data
+            .chunks_exact(4)
+            .map(|bytes| {
+                let bytes = bytes.try_into().unwrap();
+                match little_endian {
+                    true => f32::from_le_bytes(bytes) as f64,
+                    false => f32::from_be_bytes(bytes) as f64,
+                }
+            })
+            .collect();
+
+        // Rows are stored bottom to top
+        let buffer = values
+            .chunks_exact(width as usize * channels)
+            .rev()
+            .flat_map(|row| row.chunks_exact(channels))
+            .map(|pixel| match channels {
+                3 => Color::rgb(pixel[0], pixel[1], pixel[2]),
+                _ => Color::gray(pixel[0]),
+            })
+            .collect();
+        Image {
+            width,
+            height,
+            buffer,
+        }
+    }
+
+    /// Read the first layer of an OpenEXR file with RGB channels
+    fn read_exr(reader: impl Read + Seek) -> Self {
+        let image = exr::prelude::read()
+            .no_deep_data()
+            .largest_resolution_level()
+            .rgb_channels(
+                |resolution, _| {
+                    Image::from_dimensions(resolution.x() as u64, resolution.y() as u64)
+                },
+                |image: &mut Image, Vec2(x, y), (r, g, b): (f32, f32, f32)| {
+                    image.buffer[y * image.width as usize + x] =
+                        Color::rgb(r as f64, g as f64, b as f64)
+                },
+            )
+            .first_valid_layer()
+            .all_attributes()
+            .from_buffered(reader)
+            .unwrap();
+        image.layer_data.channel_data.pixels
+    }
 }
 
 impl Default for Image {
@@ -176,7 +712,17 @@ impl Default for Image {
 
 #[cfg(test)]
 mod image_tests {
-    use crate::utilities::{color::Color, image::Image};
+    use std::io::Cursor;
+
+    use exr::prelude::{read, ReadChannels, ReadLayers, Vec2};
+    use png::{BitDepth, ScaledFloat};
+
+    use crate::utilities::{
+        color::Color,
+        colorspace::ColorSpace,
+        image::{Image, ImageFormat},
+        tonemap::ToneMap,
+    };
 
     #[test]
     fn can_get_default() {
@@ -262,4 +808,316 @@ mod image_tests {
         let image = Image::from_dimensions(5, 3);
         assert_eq!(image.get_index(4, 2), 4)
     }
+
+    #[test]
+    fn can_parse_format() {
+        assert_eq!("ppm".parse::<ImageFormat>(), Ok(ImageFormat::Ppm));
+        assert_eq!("png16".parse::<ImageFormat>(), Ok(ImageFormat::Png16));
+        assert_eq!(ImageFormat::from_extension("PPM"), Some(ImageFormat::Ppm));
+        assert_eq!(ImageFormat::from_extension("png"), Some(ImageFormat::Png));
+        assert!("gif".parse::<ImageFormat>().is_err());
+    }
+
+    #[test]
+    fn can_write_ppm() {
+        let mut image = Image::from_dimensions(2, 1);
+        *image.color_at(0, 0) = Color::gray(1.);
+        let mut data = vec![];
+        image.write_ppm(&mut data, ColorSpace::Linear);
+        assert_eq!(
+            String::from_utf8(data).unwrap(),
+            "P3\n2 1\n255\n255 255 255\n0 0 0\n"
+        );
+    }
+
+    fn decode_png(data: Vec<u8>) -> (png::OutputInfo, Vec<u8>) {
+        let decoder = png::Decoder::new(Cursor::new(data));
+        let mut reader = decoder.read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size().unwrap()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        (info, pixels)
+    }
+
+    #[test]
+    fn can_write_png() {
+        let mut image = Image::from_dimensions(2, 1);
+        *image.color_at(0, 0) = Color::rgb(1., 0.5, 0.);
+        let mut data = vec![];
+        image.write_png(&mut data, BitDepth::Eight, ColorSpace::Linear);
+
+        let (info, pixels) = decode_png(data);
+        assert_eq!((info.width, info.height), (2, 1));
+        assert_eq!(info.bit_depth, BitDepth::Eight);
+        assert_eq!(pixels, vec![255, 128, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn can_tag_png_color_space() {
+        let image = Image::from_dimensions(1, 1);
+        let info = |color_space| {
+            let mut data = vec![];
+            image.write_png(&mut data, BitDepth::Eight, color_space);
+            let reader = png::Decoder::new(Cursor::new(data)).read_info().unwrap();
+            let info = reader.info();
+            (info.srgb.is_some(), info.gamma())
+        };
+        assert!(info(ColorSpace::Srgb).0);
+        assert_eq!(
+            info(ColorSpace::Linear),
+            (false, Some(ScaledFloat::new(1.)))
+        );
+        assert_eq!(
+            info(ColorSpace::Gamma { gamma: 2. }),
+            (false, Some(ScaledFloat::new(0.5)))
+        );
+    }
+
+    #[test]
+    fn can_write_png_16() {
+        let mut image = Image::from_dimensions(1, 2);
+        *image.color_at(0, 1) = Color::rgb(1., 0.5, 0.);
+        let mut data = vec![];
+        image.write_png(&mut data, BitDepth::Sixteen, ColorSpace::Linear);
+
+        let (info, pixels) = decode_png(data);
+        assert_eq!(info.bit_depth, BitDepth::Sixteen);
+        // The top row comes first
+        assert_eq!(pixels, vec![255, 255, 128, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn can_write_hdr() {
+        let mut image = Image::from_dimensions(2, 1);
+        *image.color_at(0, 0) = Color::rgb(250., 1., 0.);
+        let mut data = vec![];
+        image.write_hdr(&mut data);
+
+        let header = "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 1 +X 2\n";
+        assert_eq!(&data[..header.len()], header.as_bytes());
+        assert_eq!(&data[header.len()..], &[250, 1, 0, 136, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn can_write_hdr_run_length() {
+        let mut image = Image::from_dimensions(8, 1);
+        *image.color_at(0, 0) = Color::rgb(1., 0.5, 0.);
+        let mut data = vec![];
+        image.write_hdr(&mut data);
+
+        let header = "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 1 +X 8\n";
+        let scanline = &data[header.len()..];
+        assert_eq!(&scanline[..4], &[2, 2, 0, 8]);
+        // Each channel is one literal run of 8 values
+        assert_eq!(scanline.len(), 4 + 4 * 9);
+        assert_eq!(&scanline[4..13], &[8, 128, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(&scanline[31..40], &[8, 129, 0, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn can_write_pfm() {
+        let mut image = Image::from_dimensions(1, 2);
+        *image.color_at(0, 0) = Color::rgb(250., -1., 0.5);
+        let mut data = vec![];
+        image.write_pfm(&mut data);
+
+        let header = "PF\n1 2\n-1.0\n";
+        assert_eq!(&data[..header.len()], header.as_bytes());
+        let values: Vec<f32> = data[header.len()..]
+            .chunks(4)
+            .map(|bytes| f32::from_le_bytes(bytes.try_into().unwrap()))
+            .collect();
+        // The bottom row comes first and keeps values outside of 0..1
+        assert_eq!(values, vec![250., -1., 0.5, 0., 0., 0.]);
+    }
+
+    #[test]
+    fn can_write_exr() {
+        let mut image = Image::from_dimensions(3, 2);
+        *image.color_at(2, 1) = Color::rgb(250., 1., 0.);
+        let mut data = Cursor::new(vec![]);
+        image.write_exr(&mut data);
+
+        let bytes = data.into_inner();
+        // OpenEXR magic number
+        assert_eq!(&bytes[..4], &[0x76, 0x2f, 0x31, 0x01]);
+
+        let decoded = read()
+            .no_deep_data()
+            .largest_resolution_level()
+            .rgb_channels(
+                |resolution, _| vec![[0f32; 3]; resolution.area()],
+                |pixels: &mut Vec<[f32; 3]>, Vec2(x, y), (r, g, b): (f32, f32, f32)| {
+                    pixels[y * 3 + x] = [r, g, b]
+                },
+            )
+            .first_valid_layer()
+            .all_attributes()
+            .from_buffered(Cursor::new(bytes))
+            .unwrap();
+        let pixels = decoded.layer_data.channel_data.pixels;
+        // The top row comes first and keeps values outside of 0..1
+        assert_eq!(pixels[2], [250., 1., 0.]);
+        assert_eq!(pixels[5], [0., 0., 0.]);
+    }
+
+    #[test]
+    fn can_read_ppm() {
+        let mut image = Image::from_dimensions(2, 2);
+        *image.color_at(0, 0) = Color::rgb(1., 0.5, 0.);
+        *image.color_at(1, 1) = Color::gray(0.2);
+        let mut data = vec![];
+        image.write_ppm(&mut data, ColorSpace::Srgb);
+
+        let decoded = Image::read_ppm(&mut Cursor::new(data));
+        assert_eq!((decoded.width, decoded.height), (2, 2));
+        decoded
+            .buffer
+            .iter()
+            .zip(&image.buffer)
+            .for_each(|(decoded, color)| {
+                assert!((decoded.r - color.r).abs() < 0.01);
+                assert!((decoded.g - color.g).abs() < 0.01);
+                assert!((decoded.b - color.b).abs() < 0.01);
+            });
+    }
+
+    #[test]
+    fn can_read_ppm_binary() {
+        let mut data = b"P6\n# comment\n2 1\n255\n".to_vec();
+        data.extend([255, 0, 0, 0, 0, 255]);
+
+        let decoded = Image::read_ppm(&mut Cursor::new(data));
+        assert_eq!(
+            decoded.buffer,
+            vec![Color::rgb(1., 0., 0.), Color::rgb(0., 0., 1.)]
+        );
+    }
+
+    #[test]
+    fn can_read_png() {
+        let mut image = Image::from_dimensions(2, 1);
+        *image.color_at(0, 0) = Color::rgb(1., 0.5, 0.);
+        [ColorSpace::Srgb, ColorSpace::Linear]
+            .iter()
+            .for_each(|color_space| {
+                let mut data = vec![];
+                image.write_png(&mut data, BitDepth::Sixteen, *color_space);
+
+                let decoded = Image::read_png(Cursor::new(data));
+                assert_eq!((decoded.width, decoded.height), (2, 1));
+                assert!((decoded.buffer[0].g - 0.5).abs() < 1e-4);
+                assert_eq!(decoded.buffer[1], Color::gray(0.));
+            });
+    }
+
+    #[test]
+    fn can_read_hdr() {
+        // Widths below 8 are written flat, 8 and up are run-length encoded
+        [3, 10].iter().for_each(|&width| {
+            let mut image = Image::from_dimensions(width, 2);
+            *image.color_at(1, 1) = Color::rgb(1., 0.5, 0.);
+            *image.color_at(2, 0) = Color::rgb(250., 1., 0.);
+            let mut data = vec![];
+            image.write_hdr(&mut data);
+
+            let decoded = Image::read_hdr(&mut Cursor::new(data));
+            assert_eq!((decoded.width, decoded.height), (width, 2));
+            let pixel = decoded.buffer[1];
+            assert!((pixel.r - 1.).abs() < 0.01 && (pixel.g - 0.5).abs() < 0.01);
+            let pixel = decoded.buffer[width as usize + 2];
+            assert!((pixel.r - 250.).abs() < 1. && pixel.b < 1.);
+        });
+    }
+
+    #[test]
+    fn can_read_hdr_runs() {
+        let mut data = hdr_scanline();
+        // A run of 8 for red, a literal and a run for green, then runs for blue and exponent
+        data.extend([136, 128]);
+        data.extend([2, 64, 0, 134, 32]);
+        data.extend([136, 0, 136, 129]);
+
+        let decoded = Image::read_hdr(&mut Cursor::new(data));
+        assert_eq!(decoded.buffer[0], Color::from_rgbe(&[128, 64, 0, 129]));
+        assert_eq!(decoded.buffer[1], Color::from_rgbe(&[128, 0, 0, 129]));
+        assert_eq!(decoded.buffer[7], Color::from_rgbe(&[128, 32, 0, 129]));
+    }
+
+    /// Header and start of a run-length encoded scanline 8 pixels wide
+    fn hdr_scanline() -> Vec<u8> {
+        let mut data = b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 1 +X 8\n".to_vec();
+        data.extend([2, 2, 0, 8]);
+        data
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid .hdr run of 0")]
+    fn can_reject_empty_hdr_literal() {
+        let mut data = hdr_scanline();
+        data.extend([0, 0, 0, 0]);
+        Image::read_hdr(&mut Cursor::new(data));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid .hdr run of 9 with 8 pixels left")]
+    fn can_reject_long_hdr_literal() {
+        let mut data = hdr_scanline();
+        data.extend([9, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        Image::read_hdr(&mut Cursor::new(data));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid .hdr run of 5 with 4 pixels left")]
+    fn can_reject_long_hdr_run() {
+        let mut data = hdr_scanline();
+        data.extend([132, 1, 133, 2]);
+        Image::read_hdr(&mut Cursor::new(data));
+    }
+
+    #[test]
+    fn can_read_pfm() {
+        let mut image = Image::from_dimensions(2, 2);
+        *image.color_at(0, 0) = Color::rgb(250., -1., 0.5);
+        *image.color_at(1, 1) = Color::rgb(0.25, 2., 3.);
+        let mut data = vec![];
+        image.write_pfm(&mut data);
+
+        let decoded = Image::read_pfm(&mut Cursor::new(data));
+        assert_eq!((decoded.width, decoded.height), (2, 2));
+        assert_eq!(decoded.buffer, image.buffer);
+    }
+
+    #[test]
+    fn can_read_pfm_grayscale() {
+        let mut data = b"Pf\n1 2\n1.0\n".to_vec();
+        data.extend(0.5f32.to_be_bytes());
+        data.extend(4f32.to_be_bytes());
+
+        let decoded = Image::read_pfm(&mut Cursor::new(data));
+        // The bottom row comes first
+        assert_eq!(decoded.buffer, vec![Color::gray(4.), Color::gray(0.5)]);
+    }
+
+    #[test]
+    fn can_read_exr() {
+        let mut image = Image::from_dimensions(3, 2);
+        *image.color_at(2, 1) = Color::rgb(250., 1., 0.);
+        *image.color_at(0, 0) = Color::rgb(0.5, 0.25, 8.);
+        let mut data = Cursor::new(vec![]);
+        image.write_exr(&mut data);
+
+        let decoded = Image::read_exr(Cursor::new(data.into_inner()));
+        assert_eq!((decoded.width, decoded.height), (3, 2));
+        assert_eq!(decoded.buffer, image.buffer);
+    }
+
+    #[test]
+    fn can_tone_map() {
+        let mut image = Image::from_dimensions(2, 1);
+        *image.color_at(0, 0) = Color::gray(3.);
+        let mapped = image.tone_mapped(ToneMap::Reinhard, 0.);
+        assert_eq!(mapped.buffer, vec![Color::gray(0.75), Color::default()]);
+        let exposed = image.tone_mapped(ToneMap::Clamp, -1.);
+        assert_eq!(exposed.buffer[0], Color::gray(1.5));
+    }
 }
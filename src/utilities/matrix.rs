@@ -0,0 +1,207 @@
+use std::ops::Mul;
+
+use serde::{Deserialize, Serialize};
+
+use crate::utilities::point::Point;
+
+/// A 4x4 affine transformation matrix, stored row by row, acting on column vectors
+///
+/// https://en.wikipedia.org/wiki/Transformation_matrix#Affine_transformations
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Matrix {
+    rows: [[f64; 4]; 4],
+}
+
+impl Matrix {
+    pub fn new(rows: [[f64; 4]; 4]) -> Self {
+        Self { rows }
+    }
+
+    pub fn identity() -> Self {
+        Self::scaling(Point::new(1., 1., 1.))
+    }
+
+    /// Move points by `offset`
+    pub fn translation(offset: Point) -> Self {
+        Self::new([
+            [1., 0., 0., offset.x],
+            [0., 1., 0., offset.y],
+            [0., 0., 1., offset.z],
+            [0., 0., 0., 1.],
+        ])
+    }
+
+    /// Stretch each axis by the matching component of `factors`
+    pub fn scaling(factors: Point) -> Self {
+        Self::new([
+            [factors.x, 0., 0., 0.],
+            [0., factors.y, 0., 0.],
+            [0., 0., factors.z, 0.],
+            [0., 0., 0., 1.],
+        ])
+    }
+
+    /// Rotate by `degrees` around the x, y, then z axis, counterclockwise looking down each axis
+    pub fn rotation(degrees: Point) -> Self {
+        let [x, y, z] = [degrees.x, degrees.y, degrees.z].map(|angle| angle.to_radians());
+        let (sin_x, cos_x) = x.sin_cos();
+        let (sin_y, cos_y) = y.sin_cos();
+        let (sin_z, cos_z) = z.sin_cos();
+        let around_x = Self::new([
+            [1., 0., 0., 0.],
+            [0., cos_x, -sin_x, 0.],
+            [0., sin_x, cos_x, 0.],
+            [0., 0., 0., 1.],
+        ]);
+        let around_y = Self::new([
+            [cos_y, 0., sin_y, 0.],
+            [0., 1., 0., 0.],
+            [-sin_y, 0., cos_y, 0.],
+            [0., 0., 0., 1.],
+        ]);
+        let around_z = Self::new([
+            [cos_z, -sin_z, 0., 0.],
+            [sin_z, cos_z, 0., 0.],
+            [0., 0., 1., 0.],
+            [0., 0., 0., 1.],
+        ]);
+        around_z * around_y * around_x
+    }
+
+    pub fn transpose(&self) -> Self {
+        Self::new(std::array::from_fn(|row| {
+            std::array::from_fn(|col| self.rows[col][row])
+        }))
+    }
+
+    /// Inverse found with Gauss-Jordan elimination, or `None` if the matrix flattens space
+    pub fn inverse(&self) -> Option<Self> {
+        let mut left = self.rows;
+        let mut right = Self::identity().rows;
+        for col in 0..4 {
+            // Swap in the row with the largest pivot to keep the elimination stable
+            let pivot =
+                (col..4).max_by(|a, b| left[*a][col].abs().total_cmp(&left[*b][col].abs()))?;
+            if left[pivot][col].abs() < 1e-12 {
+                return None;
+            }
+            left.swap(col, pivot);
+            right.swap(col, pivot);
+
+            let scale = 1. / left[col][col];
+            (0..4).for_each(|index| {
+                left[col][index] *= scale;
+                right[col][index] *= scale;
+            });
+            for row in (0..4).filter(|row| *row != col) {
+                let factor = left[row][col];
+                (0..4).for_each(|index| {
+                    left[row][index] -= factor * left[col][index];
+                    right[row][index] -= factor * right[col][index];
+                });
+            }
+        }
+        Some(Self::new(right))
+    }
+
+    /// Determinant of the upper left 3x3 block, the factor the matrix scales volumes by
+    pub fn determinant(&self) -> f64 {
+        let m = &self.rows;
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+
+    /// Apply the full transformation to a position
+    pub fn transform_point(&self, point: &Point) -> Point {
+        self.transform_vector(point) + Point::new(self.rows[0][3], self.rows[1][3], self.rows[2][3])
+    }
+
+    /// Apply the transformation to a direction, which ignores translation
+    pub fn transform_vector(&self, vector: &Point) -> Point {
+        let row = |index: usize| {
+            let [x, y, z, _] = self.rows[index];
+            x * vector.x + y * vector.y + z * vector.z
+        };
+        Point::new(row(0), row(1), row(2))
+    }
+}
+
+impl Default for Matrix {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Mul for Matrix {
+    type Output = Matrix;
+
+    fn mul(self, rhs: Matrix) -> Matrix {
+        Matrix::new(std::array::from_fn(|row| {
+            std::array::from_fn(|col| (0..4).map(|k| self.rows[row][k] * rhs.rows[k][col]).sum())
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utilities::{matrix::Matrix, point::Point};
+
+    fn assert_near(a: Point, b: Point) {
+        assert!((a - b).len() < 1e-12, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn can_translate() {
+        let matrix = Matrix::translation(Point::new(1., 2., 3.));
+        let point = Point::new(1., 1., 1.);
+        assert_eq!(matrix.transform_point(&point), Point::new(2., 3., 4.));
+        assert_eq!(matrix.transform_vector(&point), point);
+    }
+
+    #[test]
+    fn can_rotate() {
+        let x = Point::new(1., 0., 0.);
+        let z = Point::new(0., 0., 1.);
+        assert_near(
+            Matrix::rotation(Point::new(0., 0., 90.)).transform_vector(&x),
+            Point::new(0., 1., 0.),
+        );
+        assert_near(
+            Matrix::rotation(Point::new(0., 90., 0.)).transform_vector(&z),
+            x,
+        );
+        // Rotations around x happen before rotations around z
+        assert_near(
+            Matrix::rotation(Point::new(90., 0., 90.)).transform_vector(&z),
+            x,
+        );
+    }
+
+    #[test]
+    fn can_invert() {
+        let matrix = Matrix::translation(Point::new(1., -2., 3.))
+            * Matrix::rotation(Point::new(30., 45., 60.))
+            * Matrix::scaling(Point::new(2., 0.5, 3.));
+        let inverse = matrix.inverse().unwrap();
+        let product = matrix * inverse;
+        (0..4).for_each(|row| {
+            (0..4).for_each(|col| {
+                let expected = if row == col { 1. } else { 0. };
+                assert!((product.rows[row][col] - expected).abs() < 1e-12);
+            })
+        });
+        assert!((matrix.determinant() - 3.).abs() < 1e-12);
+        assert!(Matrix::scaling(Point::new(1., 0., 1.)).inverse().is_none());
+    }
+
+    #[test]
+    fn can_deserialize() {
+        let matrix: Matrix = serde_yml::from_str(
+            "- [1.0, 0.0, 0.0, 5.0]\n- [0.0, 1.0, 0.0, 0.0]\n- [0.0, 0.0, 1.0, 0.0]\n- [0.0, 0.0, 0.0, 1.0]\n",
+        )
+        .unwrap();
+        assert_eq!(matrix, Matrix::translation(Point::new(5., 0., 0.)));
+    }
+}
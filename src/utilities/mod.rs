@@ -1,8 +1,18 @@
+pub mod atmosphere;
 pub mod camera;
+pub mod cli;
 pub mod color;
+pub mod colorspace;
+pub mod distribution;
+pub mod frame;
 pub mod image;
+pub mod matrix;
+pub mod noise;
 pub mod point;
+pub mod polynomial;
 pub mod progress;
 pub mod ray;
 pub mod scene;
 pub mod scenebuilder;
+pub mod spectrum;
+pub mod tonemap;
@@ -0,0 +1,251 @@
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use serde::{Deserialize, Serialize};
+
+use crate::utilities::point::Point;
+
+/// Ken Perlin's improved gradient noise, repeatable for a given seed
+///
+/// Scene files only store the seed; the permutation table is rebuilt from it when loading.
+///
+/// https://mrl.cs.nyu.edu/~perlin/noise/
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(from = "u64", into = "u64")]
+pub struct Perlin {
+    seed: u64,
+    /// A shuffle of 0..256, repeated twice so lookups can skip wrapping
+    permutation: Vec<usize>,
+}
+
+impl From<u64> for Perlin {
+    fn from(seed: u64) -> Self {
+        Perlin::new(seed)
+    }
+}
+
+impl From<Perlin> for u64 {
+    fn from(perlin: Perlin) -> Self {
+        perlin.seed
+    }
+}
+
+impl Default for Perlin {
+    fn default() -> Self {
+        Perlin::new(0)
+    }
+}
+
+impl Perlin {
+    pub fn new(seed: u64) -> Self {
+        let mut table: Vec<usize> = (0..256).collect();
+        table.shuffle(&mut StdRng::seed_from_u64(seed));
+        let permutation = table.iter().chain(table.iter()).copied().collect();
+        Self { seed, permutation }
+    }
+
+    /// Noise at `point`, between about -1 and 1, and 0 at every integer lattice point
+    pub fn noise(&self, point: &Point) -> f64 {
+        let cell = [point.x, point.y, point.z].map(|coordinate| coordinate.floor());
+        let [x, y, z] = [point.x - cell[0], point.y - cell[1], point.z - cell[2]];
+        let [i, j, k] = cell.map(|coordinate| (coordinate as i64).rem_euclid(256) as usize);
+        let [u, v, w] = [x, y, z].map(fade);
+
+        let p = &self.permutation;
+        let (a, b) = (p[i] + j, p[i + 1] + j);
+        let (aa, ab, ba, bb) = (p[a] + k, p[a + 1] + k, p[b] + k, p[b + 1] + k);
+
+        lerp(
+            w,
+            lerp(
+                v,
+                lerp(u, grad(p[aa], x, y, z), grad(p[ba], x - 1., y, z)),
+                lerp(u, grad(p[ab], x, y - 1., z), grad(p[bb], x - 1., y - 1., z)),
+            ),
+            lerp(
+                v,
+                lerp(
+                    u,
+                    grad(p[aa + 1], x, y, z - 1.),
+                    grad(p[ba + 1], x - 1., y, z - 1.),
+                ),
+                lerp(
+                    u,
+                    grad(p[ab + 1], x, y - 1., z - 1.),
+                    grad(p[bb + 1], x - 1., y - 1., z - 1.),
+                ),
+            ),
+        )
+    }
+
+    /// Sum of the absolute noise over `octaves` doublings of frequency, each half as strong as
+    /// the last, which gives the folded look of marble veins and smoke
+    pub fn turbulence(&self, point: &Point, octaves: u32) -> f64 {
+        (0..octaves)
+            .fold((0., *point, 1.), |(total, point, weight), _| {
+                (
+                    total + weight * self.noise(&point).abs(),
+                    point * 2.,
+                    weight * 0.5,
+                )
+            })
+            .0
+    }
+}
+
+/// Smooth step with zero first and second derivatives at both ends
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6. - 15.) + 10.)
+}
+
+fn lerp(t: f64, a: f64, b: f64) -> f64 {
+    a + t * (b - a)
+}
+
+/// Dot product of `(x, y, z)` with one of twelve gradient directions picked by `hash`
+fn grad(hash: usize, x: f64, y: f64, z: f64) -> f64 {
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = match h {
+        0..=3 => y,
+        12 | 14 => x,
+        _ => z,
+    };
+    let u = if h & 1 == 0 { u } else { -u };
+    let v = if h & 2 == 0 { v } else { -v };
+    u + v
+}
+
+/// Steven Worley's cellular noise: the distance to the nearest of a set of points scattered one
+/// per unit cube, repeatable for a given seed
+///
+/// https://www.rhythmiccanvas.com/research/papers/worley.pdf
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Worley {
+    seed: u64,
+}
+
+impl Worley {
+    #[allow(dead_code)]
+    pub fn new(seed: u64) -> Self {
+        Self { seed }
+    }
+
+    /// Distance from `point` to the closest feature point, between 0 and about 1
+    pub fn distance(&self, point: &Point) -> f64 {
+        let cell = [point.x, point.y, point.z].map(|coordinate| coordinate.floor() as i64);
+        // Only the cell containing `point` and its neighbors are searched. The closest point is
+        // almost always among them, but with one point per cell a closer one two cells away
+        // can rarely be missed, which is not visible in the textures
+        (-1..=1)
+            .flat_map(|i| (-1..=1).flat_map(move |j| (-1..=1).map(move |k| [i, j, k])))
+            .map(|offset| {
+                let neighbor = [0, 1, 2].map(|axis| cell[axis] + offset[axis]);
+                let feature = self.feature(neighbor);
+                (feature - *point).len()
+            })
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    /// Distance averaged over `octaves` doublings of frequency, each half as strong as the last
+    pub fn fractal(&self, point: &Point, octaves: u32) -> f64 {
+        let (total, _, _, weights) = (0..octaves).fold(
+            (0., *point, 1., 0.),
+            |(total, point, weight, weights), _| {
+                (
+                    total + weight * self.distance(&point),
+                    point * 2.,
+                    weight * 0.5,
+                    weights + weight,
+                )
+            },
+        );
+        match weights > 0. {
+            true => total / weights,
+            false => 0.,
+        }
+    }
+
+    /// Position of the feature point inside the unit cube at `cell`
+    fn feature(&self, cell: [i64; 3]) -> Point {
+        let hash = cell.iter().fold(self.seed, |hash, coordinate| {
+            split_mix(hash ^ (*coordinate as u64))
+        });
+        let [x, y, z] = [hash, split_mix(hash), split_mix(split_mix(hash))]
+            .map(|bits| (bits >> 11) as f64 / (1u64 << 53) as f64);
+        Point::new(cell[0] as f64 + x, cell[1] as f64 + y, cell[2] as f64 + z)
+    }
+}
+
+/// Scramble the bits of `value`, so that neighboring inputs give unrelated outputs
+///
+/// https://prng.di.unimi.it/splitmix64.c
+fn split_mix(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utilities::{
+        noise::{Perlin, Worley},
+        point::Point,
+    };
+
+    fn points() -> impl Iterator<Item = Point> {
+        (0..1000).map(|index| {
+            let t = index as f64;
+            Point::new(t * 0.137 - 60., t * 0.071 + 3.3, -t * 0.193)
+        })
+    }
+
+    #[test]
+    fn can_repeat_perlin() {
+        let point = Point::new(1.3, -2.7, 0.4);
+        assert_eq!(Perlin::new(7).noise(&point), Perlin::new(7).noise(&point));
+        assert_ne!(Perlin::new(7).noise(&point), Perlin::new(8).noise(&point));
+    }
+
+    #[test]
+    fn can_bound_perlin() {
+        let perlin = Perlin::new(1);
+        assert_eq!(perlin.noise(&Point::new(3., -4., 5.)), 0.);
+        points().for_each(|point| {
+            let noise = perlin.noise(&point);
+            assert!((-1.1..=1.1).contains(&noise));
+            let turbulence = perlin.turbulence(&point, 7);
+            assert!((0. ..2.).contains(&turbulence));
+        });
+    }
+
+    #[test]
+    fn can_vary_perlin_smoothly() {
+        let perlin = Perlin::new(3);
+        let point = Point::new(0.3, 0.6, 0.9);
+        let nearby = point + Point::new(1e-6, 0., 0.);
+        assert!((perlin.noise(&point) - perlin.noise(&nearby)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn can_serialize_perlin_seed() {
+        let perlin: Perlin = serde_yml::from_str("42").unwrap();
+        assert_eq!(perlin.permutation, Perlin::new(42).permutation);
+        assert_eq!(serde_yml::to_string(&perlin).unwrap().trim(), "42");
+    }
+
+    #[test]
+    fn can_find_worley_features() {
+        let worley = Worley::new(5);
+        // Every point is within a cube diagonal of the feature in its own cell
+        points().for_each(|point| {
+            let distance = worley.distance(&point);
+            assert!((0. ..=3f64.sqrt()).contains(&distance));
+            assert!((0. ..=3f64.sqrt()).contains(&worley.fractal(&point, 4)));
+        });
+        let feature = worley.feature([2, -3, 4]);
+        assert!(worley.distance(&feature) < 1e-12);
+        assert_ne!(feature, Worley::new(6).feature([2, -3, 4]));
+    }
+}
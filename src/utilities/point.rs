@@ -1,4 +1,4 @@
-use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Range, Sub, SubAssign};
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Range, Sub, SubAssign};
 
 use rand::distributions::{Distribution, Uniform};
 
@@ -36,7 +36,7 @@ impl Point {
     /// https://en.wikipedia.org/wiki/Cross_product
     pub fn cross(self, rhs: Point) -> Point {
         Point::new(
-            self.y * rhs.z + self.z * rhs.y,
+            self.y * rhs.z - self.z * rhs.y,
             self.z * rhs.x - self.x * rhs.z,
             self.x * rhs.y - self.y * rhs.x,
         )
@@ -90,12 +90,44 @@ impl Point {
     /// https://spie.org/publications/fg08_p13_index_of_refraction?SSO=1
     pub fn refract(self, normal: Point, refraction_index: f64) -> Point {
         // Calcualte parallel ray
-        let cos_theta = (-1. * self).dot(normal).min(1.);
+        let cos_theta = (-self).dot(normal).min(1.);
         let r_perpendicular = refraction_index * (self + cos_theta * normal);
         // Calcualte parallel ray
-        let r_parallel = -1. * (1. - r_perpendicular.len().powi(2)).abs().sqrt() * normal;
+        let r_parallel = -(1. - r_perpendicular.len().powi(2)).abs().sqrt() * normal;
         r_perpendicular + r_parallel
     }
+
+    /// Get a component by axis index, where 0 is x, 1 is y, and 2 is z
+    pub fn axis(&self, axis: usize) -> f64 {
+        match axis {
+            0 => self.x,
+            1 => self.y,
+            _ => self.z,
+        }
+    }
+
+    /// Component-wise minimum of two points
+    pub fn min(self, rhs: Point) -> Point {
+        Point::new(self.x.min(rhs.x), self.y.min(rhs.y), self.z.min(rhs.z))
+    }
+
+    /// Component-wise maximum of two points
+    pub fn max(self, rhs: Point) -> Point {
+        Point::new(self.x.max(rhs.x), self.y.max(rhs.y), self.z.max(rhs.z))
+    }
+
+    /// Two unit vectors that form an orthonormal basis with this unit vector
+    ///
+    /// https://graphics.pixar.com/library/OrthonormalB/paper.pdf
+    pub fn orthonormal_basis(self) -> (Point, Point) {
+        let sign = 1f64.copysign(self.z);
+        let a = -1. / (sign + self.z);
+        let b = self.x * self.y * a;
+        (
+            Point::new(1. + sign * self.x * self.x * a, sign * b, -sign * self.x),
+            Point::new(b, sign + self.y * self.y * a, -self.y),
+        )
+    }
 }
 
 impl Default for Point {
@@ -180,6 +212,14 @@ impl Mul<Point> for f64 {
     }
 }
 
+impl Neg for Point {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Point::new(-self.x, -self.y, -self.z)
+    }
+}
+
 impl Div<f64> for Point {
     type Output = Self;
 
@@ -295,6 +335,32 @@ mod tests {
         assert_eq!(v3.z, 10.);
     }
 
+    #[test]
+    fn can_neg() {
+        let v = -Point::new(1., -2., 3.);
+        assert_eq!(v.x, -1.);
+        assert_eq!(v.y, 2.);
+        assert_eq!(v.z, -3.);
+    }
+
+    #[test]
+    fn can_get_orthonormal_basis() {
+        [
+            Point::new(0., 0., 1.),
+            Point::new(0., 0., -1.),
+            Point::new(1., 2., -3.).normalized(),
+        ]
+        .iter()
+        .for_each(|&n| {
+            let (u, v) = n.orthonormal_basis();
+            assert!((u.len() - 1.).abs() < 1e-12);
+            assert!((v.len() - 1.).abs() < 1e-12);
+            assert!(u.dot(v).abs() < 1e-12);
+            assert!(u.dot(n).abs() < 1e-12);
+            assert!(v.dot(n).abs() < 1e-12);
+        });
+    }
+
     #[test]
     fn can_div() {
         let v1 = Point::new(4., 4., 4.);
@@ -336,7 +402,7 @@ mod tests {
         let v1 = Point::new(2., 2., 2.);
         let v2 = Point::new(3., 4., 5.);
         let v3 = v1.cross(v2);
-        assert_eq!(v3.x, 18.);
+        assert_eq!(v3.x, 2.);
         assert_eq!(v3.y, -4.);
         assert_eq!(v3.z, 2.);
     }
@@ -381,4 +447,20 @@ mod tests {
         assert!((refracted.y + 0.16227766016837952).abs() < f64::EPSILON);
         assert!((refracted.z - 4.16227766016838).abs() < f64::EPSILON);
     }
+
+    #[test]
+    fn can_get_axis() {
+        let v = Point::new(1., 2., 3.);
+        assert_eq!(v.axis(0), 1.);
+        assert_eq!(v.axis(1), 2.);
+        assert_eq!(v.axis(2), 3.);
+    }
+
+    #[test]
+    fn can_min_max() {
+        let v1 = Point::new(1., 5., -3.);
+        let v2 = Point::new(2., -1., 0.);
+        assert_eq!(v1.min(v2), Point::new(1., -1., -3.));
+        assert_eq!(v1.max(v2), Point::new(2., 5., 0.));
+    }
 }
@@ -0,0 +1,145 @@
+use std::f64::consts::PI;
+
+/// Coefficients closer to zero than this are treated as zero
+const EPSILON: f64 = 1e-9;
+
+/// Real roots of `a x² + b x + c`, in increasing order
+pub fn solve_quadratic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    if a.abs() < EPSILON {
+        return match b.abs() < EPSILON {
+            true => vec![],
+            false => vec![-c / b],
+        };
+    }
+    let discriminant = b * b - 4. * a * c;
+    if discriminant < 0. {
+        return vec![];
+    }
+    // Avoids subtracting nearly equal numbers when `b` dominates
+    let q = -0.5 * (b + discriminant.sqrt().copysign(b));
+    let mut roots = match q == 0. {
+        true => vec![0.],
+        false => vec![q / a, c / q],
+    };
+    roots.sort_by(f64::total_cmp);
+    roots
+}
+
+/// Real roots of `x³ + a x² + b x + c`, in no particular order
+///
+/// https://github.com/erich666/GraphicsGems/blob/master/gems/Roots3And4.c
+fn solve_normalized_cubic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    // Substitute x = y - a/3 to remove the squared term: y³ + 3p y + 2q = 0
+    let p = (-a * a / 3. + b) / 3.;
+    let q = (2. / 27. * a * a * a - a * b / 3. + c) / 2.;
+    let discriminant = q * q + p * p * p;
+
+    let roots = if discriminant.abs() < EPSILON {
+        match q.abs() < EPSILON {
+            true => vec![0.],
+            false => {
+                let u = (-q).cbrt();
+                vec![2. * u, -u]
+            }
+        }
+    } else if discriminant < 0. {
+        // Three real roots, found with trigonometry
+        let phi = (-q / (-p * p * p).sqrt()).clamp(-1., 1.).acos() / 3.;
+        let t = 2. * (-p).sqrt();
+        vec![
+            t * phi.cos(),
+            -t * (phi + PI / 3.).cos(),
+            -t * (phi - PI / 3.).cos(),
+        ]
+    } else {
+        let root = discriminant.sqrt();
+        vec![(root - q).cbrt() - (root + q).cbrt()]
+    };
+    roots.into_iter().map(|root| root - a / 3.).collect()
+}
+
+/// Real roots of `a x⁴ + b x³ + c x² + d x + e`, in increasing order
+///
+/// Solved with Ferrari's method, then refined with a few Newton steps, since the closed form
+/// loses precision when the roots are far apart.
+///
+/// https://github.com/erich666/GraphicsGems/blob/master/gems/Roots3And4.c
+pub fn solve_quartic(a: f64, b: f64, c: f64, d: f64, e: f64) -> Vec<f64> {
+    let [b, c, d, e] = [b / a, c / a, d / a, e / a];
+
+    // Substitute x = y - b/4 to remove the cubed term: y⁴ + p y² + q y + r = 0
+    let p = -3. / 8. * b * b + c;
+    let q = b * b * b / 8. - b * c / 2. + d;
+    let r = -3. / 256. * b * b * b * b + b * b * c / 16. - b * d / 4. + e;
+
+    let mut roots = if r.abs() < EPSILON {
+        // y (y³ + p y + q) = 0
+        let mut roots = solve_normalized_cubic(0., p, q);
+        roots.push(0.);
+        roots
+    } else {
+        // Any root of the resolvent cubic splits the quartic into two quadratics
+        let z = solve_normalized_cubic(-p / 2., -r, r * p / 2. - q * q / 8.)[0];
+        let u = z * z - r;
+        let v = 2. * z - p;
+        let root = |value: f64| match value.abs() < EPSILON {
+            true => Some(0.),
+            false => (value > 0.).then(|| value.sqrt()),
+        };
+        let (Some(u), Some(v)) = (root(u), root(v)) else {
+            return vec![];
+        };
+        let v = if q < 0. { -v } else { v };
+        let mut roots = solve_quadratic(1., v, z - u);
+        roots.extend(solve_quadratic(1., -v, z + u));
+        roots
+    };
+
+    let polynomial = |x: f64| (((x + b) * x + c) * x + d) * x + e;
+    let derivative = |x: f64| ((4. * x + 3. * b) * x + 2. * c) * x + d;
+    roots.iter_mut().for_each(|root| {
+        *root -= b / 4.;
+        for _ in 0..3 {
+            let slope = derivative(*root);
+            if slope.abs() < EPSILON {
+                break;
+            }
+            *root -= polynomial(*root) / slope;
+        }
+    });
+    roots.sort_by(f64::total_cmp);
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utilities::polynomial::{solve_quadratic, solve_quartic};
+
+    fn assert_roots(found: Vec<f64>, expected: &[f64]) {
+        assert_eq!(found.len(), expected.len(), "{found:?} != {expected:?}");
+        found
+            .iter()
+            .zip(expected)
+            .for_each(|(a, b)| assert!((a - b).abs() < 1e-9, "{found:?} != {expected:?}"));
+    }
+
+    #[test]
+    fn can_solve_quadratic() {
+        assert_roots(solve_quadratic(1., -3., 2.), &[1., 2.]);
+        assert_roots(solve_quadratic(2., 0., -8.), &[-2., 2.]);
+        assert_roots(solve_quadratic(0., 2., -1.), &[0.5]);
+        assert_roots(solve_quadratic(1., 0., 1.), &[]);
+    }
+
+    #[test]
+    fn can_solve_quartic() {
+        // (x - 1)(x - 2)(x - 3)(x - 4)
+        assert_roots(solve_quartic(1., -10., 35., -50., 24.), &[1., 2., 3., 4.]);
+        // (x² + 1)(x - 2)(x + 5), with two complex roots
+        assert_roots(solve_quartic(1., 3., -9., 3., -10.), &[-5., 2.]);
+        // (x² + 1)(x² + 4) has no real roots
+        assert_roots(solve_quartic(1., 0., 5., 0., 4.), &[]);
+        // Scaled, and with a root at zero
+        assert_roots(solve_quartic(2., -4., -10., 12., 0.), &[-2., 0., 1., 3.]);
+    }
+}
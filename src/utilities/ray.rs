@@ -1,9 +1,11 @@
-use crate::utilities::point::Point;
+use crate::utilities::{point::Point, spectrum::Wavelength};
 
 pub struct Ray {
     pub origin: Point,
     pub direction: Point,
     pub time: f64,
+    /// Light the ray carries, which is only narrowed down in spectral mode
+    pub wavelength: Wavelength,
 }
 
 impl Ray {
@@ -12,9 +14,15 @@ impl Ray {
             origin,
             direction,
             time,
+            wavelength: Wavelength::default(),
         }
     }
 
+    pub fn with_wavelength(mut self, wavelength: Wavelength) -> Self {
+        self.wavelength = wavelength;
+        self
+    }
+
     pub fn at(&self, time: f64) -> Point {
         self.origin + time * self.direction
     }
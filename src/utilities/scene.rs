@@ -5,13 +5,25 @@ use std::{
 };
 
 use crate::{
-    shapes::world::World,
+    environments::{background::Environment, gradient::Gradient},
+    shapes::{
+        bvh::Bvh,
+        hit::{Hit, Hittable},
+        instance::Library,
+        world::World,
+    },
     utilities::{
+        atmosphere::Atmosphere,
         camera::{Camera, CameraSettings},
-        image::Image,
+        colorspace::ColorSpace,
+        image::{Image, ImageFormat},
+        point::Point,
+        ray::Ray,
+        tonemap::ToneMap,
     },
 };
 
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 use serde_yml;
 
@@ -27,32 +39,87 @@ impl Settings {
     }
 }
 
+/// Scene file representation of the render settings, which also accepts the older `gamma`
+#[derive(Deserialize)]
+struct RenderSettingsFile {
+    msaa_samples: f64,
+    max_depth: u64,
+    #[serde(default = "RenderSettings::default_roulette_depth")]
+    roulette_depth: u64,
+    gamma: Option<f64>,
+    color_space: Option<ColorSpace>,
+    #[serde(default)]
+    tone_map: ToneMap,
+    #[serde(default)]
+    exposure: f64,
+    #[serde(default)]
+    spectral: bool,
+    shutter_open: f64,
+    shutter_close: f64,
+}
+
 #[derive(Deserialize, Serialize)]
+#[serde(from = "RenderSettingsFile")]
 pub struct RenderSettings {
     /// Sample rays cast per pixel
     pub msaa_samples: f64,
     /// Maximum number of times a ray is allowed to bounce
     pub max_depth: u64,
-    /// Gamma correction applied after render
-    pub gamma: f64,
+    /// Number of bounces before paths carrying little light may be ended by Russian roulette
+    pub roulette_depth: u64,
+    /// Transfer function used to encode low dynamic range images
+    pub color_space: ColorSpace,
+    /// Operator compressing radiance into 0..1 for low dynamic range images
+    pub tone_map: ToneMap,
+    /// Exposure adjustment in stops, applied before tone mapping
+    pub exposure: f64,
+    /// Trace a hero wavelength with each path, so that dispersive glass splits light
+    pub spectral: bool,
     /// Initial time the camera shutter was opened
     pub shutter_open: f64,
     /// Time the shutter was closed
     pub shutter_close: f64,
 }
 
+impl From<RenderSettingsFile> for RenderSettings {
+    fn from(file: RenderSettingsFile) -> Self {
+        let color_space = file
+            .color_space
+            .unwrap_or_else(|| file.gamma.map_or(ColorSpace::Srgb, ColorSpace::from_gamma));
+        Self {
+            msaa_samples: file.msaa_samples,
+            max_depth: file.max_depth,
+            roulette_depth: file.roulette_depth,
+            color_space,
+            tone_map: file.tone_map,
+            exposure: file.exposure,
+            spectral: file.spectral,
+            shutter_open: file.shutter_open,
+            shutter_close: file.shutter_close,
+        }
+    }
+}
+
 impl RenderSettings {
+    fn default_roulette_depth() -> u64 {
+        3
+    }
+
     pub fn new(
         msaa_samples: f64,
         max_depth: u64,
-        gamma: f64,
+        color_space: ColorSpace,
         shutter_open: f64,
         shutter_close: f64,
     ) -> Self {
         Self {
             msaa_samples,
             max_depth,
-            gamma,
+            roulette_depth: Self::default_roulette_depth(),
+            color_space,
+            tone_map: ToneMap::default(),
+            exposure: 0.,
+            spectral: false,
             shutter_open,
             shutter_close,
         }
@@ -68,23 +135,122 @@ pub struct Scene {
     /// Camera location
     #[serde(skip_serializing, skip_deserializing)]
     pub camera: Camera,
+    /// Shapes that are only rendered where an `Instance` in `world` places them
+    #[serde(default, skip_serializing_if = "Library::is_empty")]
+    pub shapes: Library,
     /// Objects to render
     pub world: World,
+    /// Light arriving from rays that miss every object
+    #[serde(default = "Scene::default_environment")]
+    pub environment: Environment,
+    /// Medium such as haze filling the space between the objects
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub atmosphere: Option<Atmosphere>,
+    /// Acceleration structure over `world`
+    #[serde(skip_serializing, skip_deserializing)]
+    pub bvh: Bvh,
+    /// Indices of the shapes in `world` that emit light, in ascending order
+    #[serde(skip_serializing, skip_deserializing)]
+    pub lights: Vec<usize>,
+}
+
+/// A source of light that the scene can sample directly
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Emitter {
+    /// The shape at this index in `world`
+    Shape(usize),
+    /// The scene's environment
+    Environment,
 }
 
 impl Scene {
     pub fn new(settings: Settings, image: Image, camera: Camera, world: World) -> Self {
+        let bvh = Bvh::new(&world);
+        let lights = Scene::find_lights(&world);
         Self {
             settings,
             image,
             camera,
+            shapes: Library::new(),
             world,
+            environment: Scene::default_environment(),
+            atmosphere: None,
+            bvh,
+            lights,
+        }
+    }
+
+    fn default_environment() -> Environment {
+        Box::new(Gradient::default())
+    }
+
+    fn find_lights(world: &World) -> Vec<usize> {
+        (0..world.len())
+            .filter(|&index| world[index].is_emitter())
+            .collect()
+    }
+
+    /// Find the closest object in the world hit by a ray, along with its index in `world`
+    pub fn hit_shape(&self, ray: &Ray, time_min: f64, time_max: f64) -> Option<(usize, Hit<'_>)> {
+        self.bvh.hit(&self.world, ray, time_min, time_max)
+    }
+
+    /// Number of emitters `sample_light` picks from
+    fn light_count(&self) -> usize {
+        self.lights.len() + usize::from(self.environment.is_emitter())
+    }
+
+    /// Pick a light uniformly and sample a direction towards it from `origin`
+    pub fn sample_light(&self, origin: &Point, time: f64) -> Option<(Emitter, Point)> {
+        let count = self.light_count();
+        if count == 0 {
+            return None;
+        }
+        match self.lights.get(rand::thread_rng().gen_range(0..count)) {
+            Some(&index) => {
+                let direction = self.world[index].sample(origin, time)?;
+                Some((Emitter::Shape(index), direction))
+            }
+            None => Some((Emitter::Environment, self.environment.sample()?)),
         }
     }
 
-    pub fn render(&self, filepath: &str, filename: &str) {
-        self.image
-            .save(filepath, filename, self.settings.render.gamma)
+    /// Density of `sample_light` picking `direction` towards `emitter`, or 0 if the emitter is
+    /// not sampled
+    pub fn light_pdf(&self, emitter: Emitter, origin: &Point, direction: &Point, time: f64) -> f64 {
+        let pdf = match emitter {
+            Emitter::Shape(index) => match self.lights.binary_search(&index) {
+                Ok(_) => self.world[index].pdf(origin, direction, time),
+                Err(_) => 0.,
+            },
+            Emitter::Environment => match self.environment.is_emitter() {
+                true => self.environment.pdf(direction),
+                false => 0.,
+            },
+        };
+        match pdf > 0. {
+            true => pdf / self.light_count() as f64,
+            false => 0.,
+        }
+    }
+
+    pub fn render(&self, filepath: &str, filename: &str, format: ImageFormat) {
+        let render = &self.settings.render;
+        self.image.save(
+            filepath,
+            filename,
+            format,
+            render.tone_map,
+            render.exposure,
+            render.color_space,
+        )
+    }
+
+    /// Change the output image size, updating the camera to match the new aspect ratio
+    pub fn resize(&mut self, width: u64, height: u64) {
+        self.image = Image::from_dimensions(width, height);
+        self.settings.camera.aspect_ratio = self.image.aspect_ratio();
+        self.camera = Camera::new(&self.settings.camera);
     }
 
     fn path(filepath: &str, filename: &str) -> PathBuf {
@@ -113,14 +279,97 @@ impl Scene {
         let buf_file = BufReader::new(file);
         let mut scene: Self = serde_yml::from_reader(buf_file).unwrap();
 
-        // Fill image buffer
-        scene.image.buffer = Image::generate_buffer(scene.image.width, scene.image.height);
-
-        // Update camera aspect ratio for image
-        scene.settings.camera.aspect_ratio = scene.image.aspect_ratio();
+        // Fill image buffer and build a camera matching its aspect ratio
+        scene.resize(scene.image.width, scene.image.height);
 
-        // Build camera for scene
-        scene.camera = Camera::new(&scene.settings.camera);
+        // Point instances at their shared shapes, then build acceleration structure for the
+        // world and find the shapes to sample as lights
+        scene.world.link(&scene.shapes);
+        scene.bvh = Bvh::new(&scene.world);
+        scene.lights = Scene::find_lights(&scene.world);
         scene
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::utilities::{
+        colorspace::ColorSpace,
+        point::Point,
+        scene::{Emitter, RenderSettings},
+        scenebuilder::build_scene,
+    };
+
+    const SETTINGS: &str =
+        "msaa_samples: 10.0\nmax_depth: 5\nshutter_open: 0.0\nshutter_close: 1.0\n";
+
+    fn parse(extra: &str) -> RenderSettings {
+        serde_yml::from_str(&format!("{SETTINGS}{extra}")).unwrap()
+    }
+
+    #[test]
+    fn can_default_to_srgb() {
+        assert_eq!(parse("").color_space, ColorSpace::Srgb);
+        assert_eq!(parse("gamma: 1.0").color_space, ColorSpace::Srgb);
+    }
+
+    #[test]
+    fn can_default_roulette_depth() {
+        assert_eq!(parse("").roulette_depth, 3);
+        assert_eq!(parse("roulette_depth: 0").roulette_depth, 0);
+    }
+
+    #[test]
+    fn can_read_gamma() {
+        assert_eq!(
+            parse("gamma: 2.2").color_space,
+            ColorSpace::Gamma { gamma: 2.2 }
+        );
+    }
+
+    #[test]
+    fn can_prefer_color_space() {
+        let settings = parse("gamma: 2.2\ncolor_space:\n  type: Linear");
+        assert_eq!(settings.color_space, ColorSpace::Linear);
+    }
+
+    #[test]
+    fn can_round_trip() {
+        let settings = parse("color_space:\n  type: Gamma\n  gamma: 1.8");
+        let saved = serde_yml::to_string(&settings).unwrap();
+        assert!(!saved.contains("gamma: 1.0"));
+        let loaded: RenderSettings = serde_yml::from_str(&saved).unwrap();
+        assert_eq!(loaded.color_space, ColorSpace::Gamma { gamma: 1.8 });
+    }
+
+    #[test]
+    fn can_find_lights() {
+        let scene = build_scene();
+        assert_eq!(scene.lights.len(), 2);
+        assert!(scene
+            .lights
+            .iter()
+            .all(|&index| scene.world[index].is_emitter()));
+    }
+
+    #[test]
+    fn can_sample_light() {
+        let scene = build_scene();
+        let origin = Point::new(0., 0.5, 0.);
+        for _ in 0..100 {
+            let (light, direction) = scene.sample_light(&origin, 0.).unwrap();
+            assert!(matches!(light, Emitter::Shape(index) if scene.lights.contains(&index)));
+            assert!(scene.light_pdf(light, &origin, &direction, 0.) > 0.);
+        }
+        // Shapes that are not lights are never sampled
+        let shape = (0..scene.world.len())
+            .find(|index| !scene.lights.contains(index))
+            .unwrap();
+        let direction = Point::new(0., 1., 0.);
+        let pdf = scene.light_pdf(Emitter::Shape(shape), &origin, &direction, 0.);
+        assert_eq!(pdf, 0.);
+        // The default gradient is only found by rays that miss
+        let pdf = scene.light_pdf(Emitter::Environment, &origin, &direction, 0.);
+        assert_eq!(pdf, 0.);
+    }
+}
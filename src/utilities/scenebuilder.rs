@@ -1,25 +1,19 @@
-use rand::Rng;
-
 use crate::{
-    materials::{
-        diffuse::Lambertian, glass::Dielectric, light::Light, metal::Metal, mirror::Mirror,
-    },
+    materials::{diffuse::Lambertian, glass::Dielectric, light::Light},
     shapes::{sphere::Sphere, triangle::Triangle, world::World},
 };
 
 use super::{
     camera::{Camera, CameraSettings},
     color::Color,
-    image::{
-        Image,
-        Orientation::{Landscape, Portrait},
-    },
+    colorspace::ColorSpace,
+    image::Image,
     point::Point,
     scene::{RenderSettings, Scene, Settings},
 };
 
 pub fn build_scene() -> Scene {
-    let render_settings = RenderSettings::new(10., 5, 1., 0., 1.);
+    let render_settings = RenderSettings::new(10., 5, ColorSpace::Srgb, 0., 1.);
     let image = Image::square(1000);
 
     // let image = Image::hd(Landscape);
@@ -49,7 +43,7 @@ pub fn build_scene() -> Scene {
             settings.camera.shutter_open,
             settings.camera.shutter_close,
             0.2,
-            Box::new(Dielectric::new(Color::gray(1.), 1.9)),
+            Box::new(Dielectric::new(Color::gray(1.).into(), 1.9)),
         )),
         // Left lamp
         Box::new(Sphere::new(
@@ -66,7 +60,7 @@ pub fn build_scene() -> Scene {
             Point::new(0., -4., 0.),
             Point::new(0., 5., -5.),
             // Box::new(Mirror::new(Color::gray(1.))),
-            Box::new(Lambertian::new(Color::rgb(1., 0., 0.), 0.5)),
+            Box::new(Lambertian::new(Color::rgb(1., 0., 0.).into(), 1.)),
         )),
         // Right Triangle
         Box::new(Triangle::new(
@@ -74,7 +68,7 @@ pub fn build_scene() -> Scene {
             Point::new(0., -4., 0.),
             Point::new(0., 5., -5.),
             // Box::new(Mirror::new(Color::gray(1.))),
-            Box::new(Lambertian::new(Color::rgb(0., 1., 0.), 0.5)),
+            Box::new(Lambertian::new(Color::rgb(0., 1., 0.).into(), 1.)),
         )),
         // Sun
         Box::new(Sphere::new(
@@ -92,7 +86,7 @@ pub fn build_scene() -> Scene {
             settings.camera.shutter_open,
             settings.camera.shutter_close,
             100.,
-            Box::new(Lambertian::new(Color::rgb(0.4, 0.3, 0.3), 1.)),
+            Box::new(Lambertian::new(Color::rgb(0.4, 0.3, 0.3).into(), 1.)),
         )),
     ];
 
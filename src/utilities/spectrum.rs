@@ -0,0 +1,208 @@
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+use crate::utilities::color::Color;
+
+/// Shortest wavelength sampled in spectral mode, in nanometres
+pub const MIN_WAVELENGTH: f64 = 380.;
+/// Longest wavelength sampled in spectral mode, in nanometres
+pub const MAX_WAVELENGTH: f64 = 780.;
+
+/// The light a path carries
+///
+/// Materials work in RGB, which stands for every visible wavelength at once. In spectral mode
+/// each camera path also picks a hero wavelength, which it narrows down to when it passes
+/// through something whose effect depends on the wavelength, like refraction in dispersive
+/// glass. From then on the path only carries that wavelength, weighted by the film's response.
+///
+/// https://cg.ivd.kit.edu/publications/2014/hero/HWSS-preprint.pdf
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Wavelength {
+    /// Every wavelength, without spectral mode
+    #[default]
+    Rgb,
+    /// Every wavelength, with the one the path narrows down to
+    Hero(f64),
+    /// Only this wavelength
+    Single(f64),
+}
+
+impl Wavelength {
+    /// Pick a hero wavelength uniformly from `sample` in 0..1
+    pub fn sample(sample: f64) -> Self {
+        Wavelength::Hero(MIN_WAVELENGTH + sample * (MAX_WAVELENGTH - MIN_WAVELENGTH))
+    }
+
+    /// The wavelength the path narrows down to, if spectral mode is on
+    pub fn hero(&self) -> Option<f64> {
+        match *self {
+            Wavelength::Rgb => None,
+            Wavelength::Hero(wavelength) | Wavelength::Single(wavelength) => Some(wavelength),
+        }
+    }
+
+    /// Narrow the path down to its hero wavelength, along with the weight that turns the RGB
+    /// light it carries into what that wavelength contributes to the film
+    pub fn narrow(&self) -> (Self, Color) {
+        match *self {
+            Wavelength::Hero(wavelength) => (Wavelength::Single(wavelength), response(wavelength)),
+            other => (other, Color::gray(1.)),
+        }
+    }
+}
+
+/// How the refractive index of a dispersive material changes with wavelength
+///
+/// Coefficients use wavelengths in micrometres, as in glass catalogs.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Dispersion {
+    /// `n = a + b / λ² + c / λ⁴`
+    ///
+    /// https://en.wikipedia.org/wiki/Cauchy%27s_equation
+    Cauchy {
+        a: f64,
+        b: f64,
+        #[serde(default)]
+        c: f64,
+    },
+    /// `n² = 1 + Σ bᵢλ² / (λ² - cᵢ)`
+    ///
+    /// https://en.wikipedia.org/wiki/Sellmeier_equation
+    Sellmeier { b: [f64; 3], c: [f64; 3] },
+}
+
+impl Dispersion {
+    /// Wavelength of the sodium d-line, where glasses quote their refractive index
+    pub const REFERENCE_WAVELENGTH: f64 = 587.6;
+
+    /// Refractive index at `wavelength` in nanometres
+    pub fn refraction_index(&self, wavelength: f64) -> f64 {
+        let micrometres2 = (wavelength / 1000.).powi(2);
+        match self {
+            Dispersion::Cauchy { a, b, c } => a + b / micrometres2 + c / micrometres2.powi(2),
+            Dispersion::Sellmeier { b, c } => (1.
+                + (0..3)
+                    .map(|i| b[i] * micrometres2 / (micrometres2 - c[i]))
+                    .sum::<f64>())
+            .sqrt(),
+        }
+    }
+}
+
+/// One lobe of the fit to the CIE 1931 color matching functions, a Gaussian with a different
+/// width on each side of its peak
+fn lobe(wavelength: f64, peak: f64, below: f64, above: f64) -> f64 {
+    let width = match wavelength < peak {
+        true => below,
+        false => above,
+    };
+    (-0.5 * ((wavelength - peak) / width).powi(2)).exp()
+}
+
+/// Linear sRGB color of a single wavelength, leaving out colors sRGB cannot show
+///
+/// https://jcgt.org/published/0002/02/01/paper.pdf
+fn rgb(wavelength: f64) -> Color {
+    let x = 1.056 * lobe(wavelength, 599.8, 37.9, 31.0)
+        + 0.362 * lobe(wavelength, 442.0, 16.0, 26.7)
+        - 0.065 * lobe(wavelength, 501.1, 20.4, 26.2);
+    let y =
+        0.821 * lobe(wavelength, 568.8, 46.9, 40.5) + 0.286 * lobe(wavelength, 530.9, 16.3, 31.1);
+    let z =
+        1.217 * lobe(wavelength, 437.0, 11.8, 36.0) + 0.681 * lobe(wavelength, 459.0, 26.0, 13.8);
+    Color::rgb(
+        (3.2406 * x - 1.5372 * y - 0.4986 * z).max(0.),
+        (-0.9689 * x + 1.8758 * y + 0.0415 * z).max(0.),
+        (0.0557 * x - 0.2040 * y + 1.0570 * z).max(0.),
+    )
+}
+
+/// The film's RGB response to `wavelength`, scaled so that averaging it over uniformly sampled
+/// wavelengths gives white
+pub fn response(wavelength: f64) -> Color {
+    static AVERAGE: OnceLock<Color> = OnceLock::new();
+    let average = AVERAGE.get_or_init(|| {
+        let steps = 4000;
+        let total = (0..steps)
+            .map(|step| {
+                let t = (step as f64 + 0.5) / steps as f64;
+                rgb(MIN_WAVELENGTH + t * (MAX_WAVELENGTH - MIN_WAVELENGTH))
+            })
+            .fold(Color::default(), |total, color| total + color);
+        total * (1. / steps as f64)
+    });
+    let color = rgb(wavelength);
+    Color::rgb(
+        color.r / average.r,
+        color.g / average.g,
+        color.b / average.b,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utilities::{
+        color::Color,
+        spectrum::{response, Dispersion, Wavelength},
+    };
+
+    #[test]
+    fn can_average_to_white() {
+        let samples = 1000;
+        let total = (0..samples)
+            .map(|i| {
+                let Wavelength::Hero(wavelength) = Wavelength::sample((i as f64 + 0.5) / 1000.)
+                else {
+                    unreachable!()
+                };
+                response(wavelength)
+            })
+            .fold(Color::default(), |total, color| total + color);
+        let average = total * (1. / samples as f64);
+        [average.r, average.g, average.b]
+            .iter()
+            .for_each(|channel| assert!((channel - 1.).abs() < 1e-3, "{channel}"));
+    }
+
+    #[test]
+    fn can_color_wavelengths() {
+        let red = response(650.);
+        let green = response(530.);
+        let blue = response(450.);
+        assert!(red.r > red.g && red.r > red.b);
+        assert!(green.g > green.r && green.g > green.b);
+        assert!(blue.b > blue.r && blue.b > blue.g);
+    }
+
+    #[test]
+    fn can_narrow() {
+        assert_eq!(Wavelength::Rgb.narrow(), (Wavelength::Rgb, Color::gray(1.)));
+        let (narrowed, weight) = Wavelength::Hero(650.).narrow();
+        assert_eq!(narrowed, Wavelength::Single(650.));
+        assert_eq!(weight, response(650.));
+        // Narrowing twice only weighs the path once
+        assert_eq!(narrowed.narrow().1, Color::gray(1.));
+        assert_eq!(narrowed.hero(), Some(650.));
+    }
+
+    #[test]
+    fn can_disperse() {
+        // BK7 glass bends blue light more than red
+        let bk7 = Dispersion::Sellmeier {
+            b: [1.03961212, 0.231792344, 1.01046945],
+            c: [0.00600069867, 0.0200179144, 103.560653],
+        };
+        let index = bk7.refraction_index(Dispersion::REFERENCE_WAVELENGTH);
+        assert!((index - 1.5168).abs() < 1e-3, "{index}");
+        assert!(bk7.refraction_index(450.) > bk7.refraction_index(650.));
+
+        let cauchy = Dispersion::Cauchy {
+            a: 1.5046,
+            b: 0.0042,
+            c: 0.,
+        };
+        assert!((cauchy.refraction_index(1000.) - 1.5088).abs() < 1e-9);
+    }
+}
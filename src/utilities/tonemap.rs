@@ -0,0 +1,135 @@
+use serde::{Deserialize, Serialize};
+
+use crate::utilities::color::Color;
+
+/// Operators that compress linear radiance into the 0..1 range of low dynamic range images
+///
+/// https://64.github.io/tonemapping/
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum ToneMap {
+    /// No tone mapping, values above 1 are clipped when quantized
+    #[default]
+    Clamp,
+    /// `c / (1 + c)`, which never reaches white
+    Reinhard,
+    /// Reinhard scaled so that `white_point` maps to 1
+    ExtendedReinhard { white_point: f64 },
+    /// Krzysztof Narkowicz's fit of the ACES filmic curve
+    Aces,
+    /// John Hable's filmic curve from Uncharted 2, with `white_point` mapping to 1
+    Hable { white_point: f64 },
+}
+
+impl ToneMap {
+    /// Scale the color by `2^exposure`, then apply the operator to each channel
+    pub fn apply(&self, color: Color, exposure: f64) -> Color {
+        let scale = 2f64.powf(exposure);
+        let map = |channel: f64| self.map_channel(channel * scale);
+        Color::new(map(color.r), map(color.g), map(color.b), color.a)
+    }
+
+    fn map_channel(&self, x: f64) -> f64 {
+        match *self {
+            ToneMap::Clamp => x,
+            ToneMap::Reinhard => x / (1. + x),
+            ToneMap::ExtendedReinhard { white_point } => {
+                x * (1. + x / white_point.powi(2)) / (1. + x)
+            }
+            ToneMap::Aces => {
+                ((x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14)).clamp(0., 1.)
+            }
+            ToneMap::Hable { white_point } => {
+                // The curve expects input scaled by an exposure bias of 2, so the white point
+                // is scaled the same way
+                Self::hable_partial(2. * x) / Self::hable_partial(2. * white_point)
+            }
+        }
+    }
+
+    /// Filmic curve constants from http://filmicworlds.com/blog/filmic-tonemapping-operators/
+    fn hable_partial(x: f64) -> f64 {
+        let shoulder_strength = 0.15;
+        let linear_strength = 0.5;
+        let linear_angle = 0.1;
+        let toe_strength = 0.2;
+        let toe_numerator = 0.02;
+        let toe_denominator = 0.3;
+        ((x * (shoulder_strength * x + linear_angle * linear_strength)
+            + toe_strength * toe_numerator)
+            / (x * (shoulder_strength * x + linear_strength) + toe_strength * toe_denominator))
+            - toe_numerator / toe_denominator
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utilities::{color::Color, tonemap::ToneMap};
+
+    const OPERATORS: [ToneMap; 4] = [
+        ToneMap::Reinhard,
+        ToneMap::ExtendedReinhard { white_point: 4. },
+        ToneMap::Aces,
+        ToneMap::Hable { white_point: 11.2 },
+    ];
+
+    #[test]
+    fn can_clamp() {
+        let color = Color::rgb(2., 0.5, 0.);
+        assert_eq!(ToneMap::Clamp.apply(color, 0.), color);
+    }
+
+    #[test]
+    fn can_apply_exposure() {
+        let color = Color::rgb(2., 0.5, 0.);
+        assert_eq!(ToneMap::Clamp.apply(color, 1.), Color::rgb(4., 1., 0.));
+        assert_eq!(ToneMap::Clamp.apply(color, -1.), Color::rgb(1., 0.25, 0.));
+    }
+
+    #[test]
+    fn can_reinhard() {
+        let color = ToneMap::Reinhard.apply(Color::rgb(1., 3., 0.), 0.);
+        assert_eq!(color, Color::rgb(0.5, 0.75, 0.));
+    }
+
+    #[test]
+    fn can_map_white_point() {
+        let extended = ToneMap::ExtendedReinhard { white_point: 4. };
+        assert!((extended.apply(Color::gray(4.), 0.).r - 1.).abs() < 1e-12);
+        let hable = ToneMap::Hable { white_point: 11.2 };
+        assert!((hable.apply(Color::gray(11.2), 0.).r - 1.).abs() < 1e-12);
+    }
+
+    #[test]
+    fn can_compress_bright_values() {
+        OPERATORS.iter().for_each(|operator| {
+            let dark = operator.apply(Color::gray(0.), 0.);
+            let light = operator.apply(Color::gray(250.), 0.);
+            assert!(dark.r.abs() < 1e-2, "{operator:?} {dark:?}");
+            assert!(light.r > 0.9, "{operator:?} {light:?}");
+        });
+    }
+
+    #[test]
+    fn can_keep_order() {
+        OPERATORS.iter().for_each(|operator| {
+            let values: Vec<f64> = [0.1, 0.5, 1., 2., 4.]
+                .iter()
+                .map(|value| operator.apply(Color::gray(*value), 0.).r)
+                .collect();
+            assert!(
+                values.windows(2).all(|pair| pair[0] < pair[1]),
+                "{operator:?}"
+            );
+        });
+    }
+
+    #[test]
+    fn can_deserialize() {
+        let operator: ToneMap =
+            serde_yml::from_str("type: ExtendedReinhard\nwhite_point: 2.5").unwrap();
+        assert_eq!(operator, ToneMap::ExtendedReinhard { white_point: 2.5 });
+        let operator: ToneMap = serde_yml::from_str("type: Aces").unwrap();
+        assert_eq!(operator, ToneMap::Aces);
+    }
+}